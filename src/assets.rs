@@ -0,0 +1,151 @@
+// src/assets.rs - Arquivos enviados uma vez para `/uploads` e referenciados por ID em `/suggest` e `/process`
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::upload::{StoredUpload, UploadError};
+
+/// Diretório onde os assets ficam até expirarem
+pub const ASSETS_DIR: &str = "assets";
+/// Tempo sem uso após o qual um asset é apagado, quando `ASSET_TTL_MINUTES` não está definido
+pub const DEFAULT_ASSET_TTL: Duration = Duration::from_secs(60 * 60);
+const ASSET_TTL_ENV: &str = "ASSET_TTL_MINUTES";
+/// Intervalo entre passagens do janitor
+const JANITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Arquivo guardado, devolvido por `POST /uploads`
+#[derive(Debug, Clone, Serialize)]
+pub struct Asset {
+    pub asset_id: Uuid,
+    pub file_name: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Cada uso adia a expiração por mais um TTL
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+pub struct AssetStore {
+    assets: Mutex<HashMap<Uuid, Asset>>,
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl AssetStore {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        AssetStore { assets: Mutex::new(HashMap::new()), dir: dir.into(), ttl }
+    }
+
+    /// Store em `ASSETS_DIR`, com o TTL de `ASSET_TTL_MINUTES`
+    pub fn from_env() -> Self {
+        let ttl = std::env::var(ASSET_TTL_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|&minutes| minutes > 0)
+            .map(|minutes| Duration::from_secs(minutes * 60))
+            .unwrap_or(DEFAULT_ASSET_TTL);
+        tracing::debug!("Assets expiram após {} min sem uso", ttl.as_secs() / 60);
+        AssetStore::new(ASSETS_DIR, ttl)
+    }
+
+    /// Diretório onde os uploads devem ser gravados antes de `register`
+    pub fn dir(&self) -> std::io::Result<&Path> {
+        std::fs::create_dir_all(&self.dir)?;
+        Ok(&self.dir)
+    }
+
+    /// Regista um upload já gravado em `dir()` e devolve o asset criado
+    pub fn register(&self, upload: StoredUpload) -> Asset {
+        let asset = Asset {
+            asset_id: Uuid::new_v4(),
+            file_name: upload.file_name,
+            size: upload.size,
+            sha256: upload.sha256,
+            expires_at: self.expiry(Utc::now()),
+            path: upload.path,
+        };
+        self.assets.lock().unwrap().insert(asset.asset_id, asset.clone());
+        asset
+    }
+
+    /// Caminho do asset indicado num campo do formulário (`gpxAssetId`, `videoAssetId`)
+    pub fn resolve(&self, value: &str) -> Result<Asset, UploadError> {
+        let id = parse_asset_id(value)?;
+        self.get(id).ok_or(UploadError::UnknownAsset(id))
+    }
+
+    /// Como `link_into`, a partir do valor de um campo do formulário
+    pub fn resolve_into(&self, value: &str, dir: &Path) -> Result<PathBuf, UploadError> {
+        let id = parse_asset_id(value)?;
+        self.link_into(id, dir).ok_or(UploadError::UnknownAsset(id))?.map_err(UploadError::Io)
+    }
+
+    /// Asset com este ID, se ainda existir; o uso renova a expiração
+    pub fn get(&self, id: Uuid) -> Option<Asset> {
+        let mut assets = self.assets.lock().unwrap();
+        let asset = assets.get_mut(&id)?;
+        asset.expires_at = self.expiry(Utc::now());
+        Some(asset.clone())
+    }
+
+    /// Coloca uma cópia do asset em `dir` (ligação física quando possível, sem duplicar o
+    /// arquivo em disco), para o job a poder usar e apagar sem depender da expiração
+    pub fn link_into(&self, id: Uuid, dir: &Path) -> Option<std::io::Result<PathBuf>> {
+        let asset = self.get(id)?;
+        let target = dir.join(format!("{}-{}", Uuid::new_v4(), asset.file_name));
+        let result = std::fs::hard_link(&asset.path, &target)
+            .or_else(|_| {
+                // A cópia mantém a data de modificação, que pode ser a hora de início do vídeo
+                std::fs::copy(&asset.path, &target)?;
+                let modified = std::fs::metadata(&asset.path)?.modified()?;
+                std::fs::File::options().write(true).open(&target)?.set_modified(modified)
+            })
+            .map(|_| target);
+        Some(result)
+    }
+
+    /// Apaga os assets cuja expiração já passou em `now` e devolve os IDs removidos
+    pub fn remove_expired(&self, now: DateTime<Utc>) -> Vec<Uuid> {
+        let expired: Vec<Asset> = {
+            let mut assets = self.assets.lock().unwrap();
+            let ids: Vec<Uuid> = assets.values().filter(|a| a.expires_at <= now).map(|a| a.asset_id).collect();
+            ids.iter().filter_map(|id| assets.remove(id)).collect()
+        };
+        for asset in &expired {
+            if let Err(e) = std::fs::remove_file(&asset.path) {
+                tracing::debug!("Não foi possível apagar o asset {:?}: {}", asset.path, e);
+            }
+        }
+        expired.into_iter().map(|a| a.asset_id).collect()
+    }
+
+    fn expiry(&self, from: DateTime<Utc>) -> DateTime<Utc> {
+        from + chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::hours(1))
+    }
+}
+
+fn parse_asset_id(value: &str) -> Result<Uuid, UploadError> {
+    value.trim().parse().map_err(|_| UploadError::Invalid(format!("ID de arquivo inválido: '{}'", value)))
+}
+
+/// Tarefa que apaga periodicamente os assets expirados
+pub fn spawn_janitor(store: Arc<AssetStore>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(JANITOR_INTERVAL);
+        loop {
+            interval.tick().await;
+            let store = Arc::clone(&store);
+            let removed = tokio::task::spawn_blocking(move || store.remove_expired(Utc::now())).await;
+            if let Ok(removed) = removed {
+                if !removed.is_empty() {
+                    tracing::debug!("Janitor: {} asset(s) expirado(s) removido(s)", removed.len());
+                }
+            }
+        }
+    })
+}
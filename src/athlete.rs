@@ -0,0 +1,63 @@
+// src/athlete.rs - Dados do atleta usados pelos widgets de treino
+
+use crate::heart_rate::{HeartRateZones, ZoneMethod};
+
+/// Configuração fisiológica do atleta; cada campo é opcional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AthleteProfile {
+    /// Zonas de FC configuradas; sem elas o widget de FC usa `HeartRateZones::default()`
+    pub heart_rate_zones: Option<HeartRateZones>,
+    /// Functional Threshold Power em watts, para o IF e o TSS
+    pub ftp: Option<f64>,
+    /// Peso em kg, para a potência em W/kg
+    pub weight_kg: Option<f64>,
+}
+
+impl AthleteProfile {
+    /// Perfil a partir dos campos de texto do formulário; campos vazios são ignorados
+    pub fn from_params(
+        zone_method: Option<&str>,
+        max_hr: Option<&str>,
+        resting_hr: Option<&str>,
+        lthr: Option<&str>,
+        ftp: Option<&str>,
+        weight_kg: Option<&str>,
+    ) -> Result<Self, String> {
+        fn present(value: Option<&str>) -> Option<&str> {
+            value.map(str::trim).filter(|s| !s.is_empty())
+        }
+        let number = |name: &str, value: Option<&str>| present(value)
+            .map(|v| v.parse::<f64>().map_err(|_| format!("Valor inválido para {}: {}", name, v)))
+            .transpose();
+        let method = present(zone_method).map(str::parse::<ZoneMethod>).transpose()?;
+        let heart_rate_zones = HeartRateZones::from_settings(
+            method,
+            number("FC máxima", max_hr)?,
+            number("FC de repouso", resting_hr)?,
+            number("LTHR", lthr)?,
+        )?;
+        AthleteProfile { heart_rate_zones, ..Default::default() }
+            .with_power(number("FTP", ftp)?, number("peso", weight_kg)?)
+    }
+
+    /// O mesmo perfil com o FTP e o peso, validados
+    pub fn with_power(self, ftp: Option<f64>, weight_kg: Option<f64>) -> Result<Self, String> {
+        if let Some(ftp) = ftp.filter(|ftp| !(50.0..=600.0).contains(ftp)) {
+            return Err(format!("FTP fora do intervalo 50–600 W: {}", ftp));
+        }
+        if let Some(weight) = weight_kg.filter(|weight| !(25.0..=250.0).contains(weight)) {
+            return Err(format!("Peso fora do intervalo 25–250 kg: {}", weight));
+        }
+        Ok(AthleteProfile { ftp, weight_kg, ..self })
+    }
+
+    /// Zonas a usar nos widgets e no tempo em zona
+    pub fn zones(&self) -> HeartRateZones {
+        self.heart_rate_zones.unwrap_or_default()
+    }
+
+    /// Potência relativa ao peso, quando o peso é conhecido
+    pub fn watts_per_kg(&self, watts: f64) -> Option<f64> {
+        self.weight_kg.map(|weight| watts / weight)
+    }
+}
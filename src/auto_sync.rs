@@ -0,0 +1,230 @@
+// src/auto_sync.rs - Sincronização automática pela correlação entre o movimento do vídeo e a velocidade GPS
+
+use std::error::Error;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command as StdCommand, Stdio};
+use chrono::{DateTime, Duration, Utc};
+use gpx::{Gpx, Waypoint};
+use serde::Serialize;
+use crate::utils::calculate_speed_kmh;
+
+/// Amostras por segundo dos dois sinais comparados
+pub const SIGNAL_RATE_HZ: f64 = 2.0;
+/// Maior desvio procurado em cada sentido em relação à hora de início do vídeo
+pub const DEFAULT_MAX_OFFSET_SECS: f64 = 300.0;
+/// Abaixo desta confiança o desvio é apenas informado e a sugestão não é alterada
+pub const MIN_CONFIDENCE: f64 = 0.2;
+
+/// Os frames são reduzidos a 64x36 em tons de cinza: suficiente para medir movimento
+const FRAME_WIDTH: usize = 64;
+const FRAME_HEIGHT: usize = 36;
+/// Média móvel aplicada aos dois sinais, em amostras (2,5 s)
+const SMOOTHING_WINDOW: usize = 5;
+/// Tendência removida dos dois sinais (média móvel de 30 s): mudanças lentas de exposição
+/// ou de ritmo correlacionam-se em quase qualquer desvio e escondem o pico verdadeiro
+const DETREND_WINDOW: usize = 61;
+/// Intervalo máximo entre pontos da trilha para a velocidade ser considerada válida
+const MAX_POINT_GAP_SECS: i64 = 10;
+/// Fração mínima do vídeo que tem de se sobrepor à trilha para um desvio ser avaliado
+const MIN_OVERLAP_RATIO: f64 = 0.5;
+const MIN_OVERLAP_SAMPLES: usize = 20;
+/// Desvios a menos de 3 s do pico pertencem ao mesmo pico
+const PEAK_EXCLUSION_SECS: f64 = 3.0;
+
+/// Resultado da sincronização automática
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AutoSyncResult {
+    /// Segundos a somar à hora de início do vídeo para alinhá-lo com a trilha
+    pub offset_seconds: f64,
+    /// Correlação de Pearson no melhor desvio (-1 a 1)
+    pub correlation: f64,
+    /// 0 a 1: quanto o melhor desvio se destaca do segundo melhor pico
+    pub confidence: f64,
+}
+
+impl AutoSyncResult {
+    pub fn is_reliable(&self) -> bool {
+        self.confidence >= MIN_CONFIDENCE
+    }
+}
+
+/// Estima o desvio entre o relógio da câmara e a trilha: mede o movimento do vídeo e
+/// procura o desvio em que ele melhor acompanha a velocidade calculada pelos pontos GPS.
+pub fn estimate_video_offset(
+    video_path: &Path,
+    gpx: &Gpx,
+    video_start: DateTime<Utc>,
+    max_offset_secs: f64,
+) -> Result<AutoSyncResult, Box<dyn Error>> {
+    let motion = extract_motion_signal(video_path)?;
+    estimate_offset(&motion, gpx, video_start, max_offset_secs)
+        .ok_or_else(|| "Não foi possível correlacionar o movimento do vídeo com a velocidade da trilha.".into())
+}
+
+/// Decodifica o vídeo com o FFmpeg a `SIGNAL_RATE_HZ` frames por segundo, reduzidos e em
+/// tons de cinza, e devolve a diferença média entre frames consecutivos. A amostra `k`
+/// corresponde ao instante `(k + 0.5) / SIGNAL_RATE_HZ` segundos do vídeo.
+pub fn extract_motion_signal(video_path: &Path) -> Result<Vec<f64>, Box<dyn Error>> {
+    let filter = format!("fps={},scale={}:{},format=gray", SIGNAL_RATE_HZ, FRAME_WIDTH, FRAME_HEIGHT);
+    let mut child = StdCommand::new("ffmpeg")
+        .args(["-v", "error", "-i"])
+        .arg(video_path)
+        .args(["-an", "-vf", &filter, "-f", "rawvideo", "-pix_fmt", "gray", "pipe:1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdout = child.stdout.take().ok_or("Saída do FFmpeg indisponível.")?;
+    let mut previous = vec![0u8; FRAME_WIDTH * FRAME_HEIGHT];
+    let mut current = vec![0u8; FRAME_WIDTH * FRAME_HEIGHT];
+    let mut signal = Vec::new();
+
+    if stdout.read_exact(&mut previous).is_ok() {
+        while stdout.read_exact(&mut current).is_ok() {
+            signal.push(frame_difference(&previous, &current));
+            std::mem::swap(&mut previous, &mut current);
+        }
+    }
+
+    if !child.wait()?.success() {
+        return Err("O FFmpeg falhou ao decodificar o vídeo para a sincronização automática.".into());
+    }
+    Ok(signal)
+}
+
+/// Diferença absoluta média entre dois frames em tons de cinza (0-255)
+pub fn frame_difference(previous: &[u8], current: &[u8]) -> f64 {
+    let total: u64 = previous.iter()
+        .zip(current)
+        .map(|(a, b)| a.abs_diff(*b) as u64)
+        .sum();
+    total as f64 / current.len().max(1) as f64
+}
+
+/// Correlaciona o sinal de movimento com a velocidade da trilha para cada desvio entre
+/// `-max_offset_secs` e `+max_offset_secs` e devolve o melhor.
+pub fn estimate_offset(
+    motion: &[f64],
+    gpx: &Gpx,
+    video_start: DateTime<Utc>,
+    max_offset_secs: f64,
+) -> Option<AutoSyncResult> {
+    let motion: Vec<Option<f64>> = motion.iter().copied().map(Some).collect();
+    let motion: Vec<f64> = detrend(&smooth(&motion, SMOOTHING_WINDOW)).into_iter().flatten().collect();
+    let max_lag = (max_offset_secs * SIGNAL_RATE_HZ).round() as usize;
+
+    // Velocidade amostrada desde `max_offset` antes do início até `max_offset` depois do fim
+    let points = timed_points(gpx);
+    let window_start = video_start - seconds(max_offset_secs);
+    let speed: Vec<Option<f64>> = (0..motion.len() + 2 * max_lag)
+        .map(|k| speed_at(&points, window_start + seconds((k as f64 + 0.5) / SIGNAL_RATE_HZ)))
+        .collect();
+    let speed = detrend(&smooth(&speed, SMOOTHING_WINDOW));
+
+    let min_overlap = ((motion.len() as f64 * MIN_OVERLAP_RATIO) as usize).max(MIN_OVERLAP_SAMPLES);
+    let scores: Vec<Option<f64>> = (0..=2 * max_lag)
+        .map(|lag| pearson(&motion, &speed[lag..lag + motion.len()], min_overlap))
+        .collect();
+
+    let (best_lag, correlation) = scores.iter()
+        .enumerate()
+        .filter_map(|(lag, score)| score.map(|s| (lag, s)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    // Segundo melhor pico: só máximos locais contam, as encostas do pico principal não
+    let exclusion = (PEAK_EXCLUSION_SECS * SIGNAL_RATE_HZ) as usize;
+    let score_at = |lag: Option<usize>| lag.and_then(|l| scores.get(l).copied().flatten()).unwrap_or(f64::MIN);
+    let runner_up = scores.iter()
+        .enumerate()
+        .filter(|(lag, _)| lag.abs_diff(best_lag) > exclusion)
+        .filter_map(|(lag, score)| {
+            let score = (*score)?;
+            let is_peak = score >= score_at(lag.checked_sub(1)) && score >= score_at(Some(lag + 1));
+            is_peak.then_some(score)
+        })
+        .fold(0.0_f64, f64::max);
+
+    Some(AutoSyncResult {
+        offset_seconds: (best_lag as f64 - max_lag as f64) / SIGNAL_RATE_HZ,
+        correlation,
+        confidence: (correlation - runner_up).clamp(0.0, 1.0),
+    })
+}
+
+fn seconds(secs: f64) -> Duration {
+    Duration::milliseconds((secs * 1000.0).round() as i64)
+}
+
+fn timed_points(gpx: &Gpx) -> Vec<(DateTime<Utc>, &Waypoint)> {
+    gpx.tracks.iter()
+        .flat_map(|t| t.segments.iter())
+        .flat_map(|s| s.points.iter())
+        .filter_map(|p| {
+            let time = p.time.and_then(|t| t.format().ok())?.parse::<DateTime<Utc>>().ok()?;
+            Some((time, p))
+        })
+        .collect()
+}
+
+/// Velocidade do trecho da trilha que contém `time`, ou `None` fora da trilha e em pausas longas
+fn speed_at(points: &[(DateTime<Utc>, &Waypoint)], time: DateTime<Utc>) -> Option<f64> {
+    let next = points.partition_point(|(t, _)| *t <= time);
+    let (t1, p1) = points.get(next.checked_sub(1)?)?;
+    let (t2, p2) = points.get(next)?;
+    if (*t2 - *t1).num_seconds() > MAX_POINT_GAP_SECS {
+        return None;
+    }
+    calculate_speed_kmh(p1, p2)
+}
+
+/// Coeficiente de Pearson entre os pares com velocidade conhecida
+fn pearson(motion: &[f64], speed: &[Option<f64>], min_overlap: usize) -> Option<f64> {
+    let pairs: Vec<(f64, f64)> = motion.iter()
+        .zip(speed)
+        .filter_map(|(m, s)| s.map(|s| (*m, s)))
+        .collect();
+    if pairs.len() < min_overlap {
+        return None;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_m = pairs.iter().map(|(m, _)| m).sum::<f64>() / n;
+    let mean_s = pairs.iter().map(|(_, s)| s).sum::<f64>() / n;
+    let (mut cov, mut var_m, mut var_s) = (0.0, 0.0, 0.0);
+    for (m, s) in &pairs {
+        cov += (m - mean_m) * (s - mean_s);
+        var_m += (m - mean_m).powi(2);
+        var_s += (s - mean_s).powi(2);
+    }
+
+    // Um sinal constante (vídeo parado, trilha sem variação) não tem correlação definida
+    if var_m < 1e-9 || var_s < 1e-9 {
+        return None;
+    }
+    Some(cov / (var_m * var_s).sqrt())
+}
+
+/// Média móvel centrada que ignora lacunas; uma amostra em falta continua em falta
+fn smooth(values: &[Option<f64>], window: usize) -> Vec<Option<f64>> {
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            values[i]?;
+            let neighbours: Vec<f64> = values[i.saturating_sub(half)..(i + half + 1).min(values.len())]
+                .iter()
+                .flatten()
+                .copied()
+                .collect();
+            Some(neighbours.iter().sum::<f64>() / neighbours.len() as f64)
+        })
+        .collect()
+}
+
+/// Subtrai a tendência lenta, deixando só as variações de curto prazo
+fn detrend(values: &[Option<f64>]) -> Vec<Option<f64>> {
+    values.iter()
+        .zip(smooth(values, DETREND_WINDOW))
+        .map(|(value, trend)| Some(value.as_ref()? - trend?))
+        .collect()
+}
@@ -0,0 +1,199 @@
+// src/basemap.rs - Mapa base offline sob o mini-mapa: tiles raster de um MBTiles ou de um diretório XYZ
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use image::{imageops, Rgba, RgbaImage};
+use image::imageops::FilterType;
+use crate::drawing::{MapProjection, MapProjectionKind};
+use crate::mbtiles::MbtilesReader;
+
+/// Lado de um tile em pixels (os tiles de outros tamanhos são redimensionados)
+pub const TILE_SIZE: u32 = 256;
+/// Limite de tiles de um mapa, para um zoom mínimo alto demais para a trilha não esgotar a memória
+const MAX_TILES: u32 = 256;
+/// Zooms procurados quando a fonte não os declara
+const DEFAULT_ZOOM_RANGE: (u8, u8) = (0, 22);
+/// Extensões procuradas num diretório XYZ, por ordem
+const TILE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+
+/// Origem dos tiles, sempre local
+pub enum TileSource {
+    Mbtiles(MbtilesReader),
+    /// Diretório `{z}/{x}/{y}.png` (ou `.jpg`, `.jpeg`, `.webp`) no esquema XYZ
+    Directory(PathBuf),
+}
+
+impl TileSource {
+    /// Um arquivo é lido como MBTiles e um diretório como tiles XYZ
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if path.is_dir() {
+            Ok(TileSource::Directory(path.to_path_buf()))
+        } else if path.is_file() {
+            Ok(TileSource::Mbtiles(MbtilesReader::open(path)?))
+        } else {
+            Err(format!("Mapa base não encontrado: {}", path.display()).into())
+        }
+    }
+
+    /// Atribuição declarada no `metadata` do MBTiles, sem as marcas HTML
+    pub fn attribution(&self) -> Option<String> {
+        match self {
+            TileSource::Mbtiles(reader) => reader.metadata("attribution").map(strip_html).filter(|text| !text.is_empty()),
+            TileSource::Directory(_) => None,
+        }
+    }
+
+    /// Zooms disponíveis: os do `metadata` do MBTiles ou os subdiretórios numéricos
+    pub fn zoom_range(&self) -> (u8, u8) {
+        match self {
+            TileSource::Mbtiles(reader) => reader.zoom_range().unwrap_or(DEFAULT_ZOOM_RANGE),
+            TileSource::Directory(root) => {
+                let zooms: Vec<u8> = std::fs::read_dir(root).into_iter().flatten()
+                    .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+                    .collect();
+                match (zooms.iter().min(), zooms.iter().max()) {
+                    (Some(&min), Some(&max)) => (min, max),
+                    _ => DEFAULT_ZOOM_RANGE,
+                }
+            },
+        }
+    }
+
+    /// Tile `x`, `y` do zoom `z` no esquema XYZ; `None` quando não existe
+    pub fn tile(&mut self, z: u8, x: u32, y: u32) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let data = match self {
+            TileSource::Mbtiles(reader) => reader.tile(z, x, y)?,
+            TileSource::Directory(root) => {
+                let base = root.join(z.to_string()).join(x.to_string());
+                match TILE_EXTENSIONS.iter().map(|ext| base.join(format!("{}.{}", y, ext))).find(|path| path.is_file()) {
+                    Some(path) => Some(std::fs::read(&path).map_err(|e| format!("Não foi possível ler o tile {}: {}", path.display(), e))?),
+                    None => None,
+                }
+            },
+        };
+        data.map(|bytes| {
+            image::load_from_memory(&bytes)
+                .map(|tile| tile.to_rgba8())
+                .map_err(|e| format!("Tile {}/{}/{} inválido: {}", z, x, y, e).into())
+        }).transpose()
+    }
+}
+
+/// Tiles já lidos, incluindo os que faltam, para não os procurar de novo
+struct TileCache<'a> {
+    source: &'a mut TileSource,
+    tiles: HashMap<(u8, u32, u32), Option<RgbaImage>>,
+}
+
+impl TileCache<'_> {
+    fn get(&mut self, z: u8, x: u32, y: u32) -> Result<Option<&RgbaImage>, Box<dyn Error>> {
+        if !self.tiles.contains_key(&(z, x, y)) {
+            let tile = self.source.tile(z, x, y)?;
+            self.tiles.insert((z, x, y), tile);
+        }
+        Ok(self.tiles[&(z, x, y)].as_ref())
+    }
+
+    /// Tile em `TILE_SIZE`; sem ele, o pedaço correspondente do antecessor mais próximo até
+    /// `min_zoom`, ampliado
+    fn get_or_parent(&mut self, z: u8, x: u32, y: u32, min_zoom: u8) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        for levels in 0..=z.saturating_sub(min_zoom) {
+            let Some(parent) = self.get(z - levels, x >> levels, y >> levels)? else { continue };
+            let side = parent.width().min(parent.height()) >> levels;
+            if side == 0 {
+                break;
+            }
+            let mask = (1 << levels) - 1;
+            let piece = imageops::crop_imm(parent, (x & mask) * side, (y & mask) * side, side, side).to_image();
+            return Ok(Some(if side == TILE_SIZE { piece } else { imageops::resize(&piece, TILE_SIZE, TILE_SIZE, FilterType::Triangle) }));
+        }
+        Ok(None)
+    }
+}
+
+/// Zoom cujos tiles têm pelo menos a resolução do mapa, dentro dos disponíveis
+pub fn zoom_for(projection: &MapProjection, zoom_range: (u8, u8)) -> u8 {
+    let ideal = (projection.scale() / TILE_SIZE as f64).log2().ceil();
+    ideal.clamp(zoom_range.0 as f64, zoom_range.1 as f64) as u8
+}
+
+/// Imagem de `width` x `height` com os tiles sob a área de `projection`, que tem de ser Web
+/// Mercator; as zonas sem tiles ficam transparentes
+pub fn render_basemap(source: &mut TileSource, projection: &MapProjection, width: u32, height: u32) -> Result<RgbaImage, Box<dyn Error>> {
+    if projection.kind() != MapProjectionKind::WebMercator {
+        return Err("O mapa base precisa da projeção Web Mercator".into());
+    }
+    let (min_zoom, max_zoom) = source.zoom_range();
+    let zoom = zoom_for(projection, (min_zoom, max_zoom));
+    let world = TILE_SIZE as f64 * (1u64 << zoom) as f64;
+
+    // Área do mapa em pixels do mundo neste zoom
+    let (left, top) = projection.plane_at(0.0, 0.0);
+    let (right, bottom) = projection.plane_at(width as f64, height as f64);
+    let (left, top, right, bottom) = (left * world, top * world, right * world, bottom * world);
+    let first_x = (left / TILE_SIZE as f64).floor() as i64;
+    let first_y = (top / TILE_SIZE as f64).floor() as i64;
+    let last_x = ((right / TILE_SIZE as f64).ceil() as i64 - 1).max(first_x);
+    let last_y = ((bottom / TILE_SIZE as f64).ceil() as i64 - 1).max(first_y);
+
+    let columns = (last_x - first_x + 1) as u32;
+    let rows = (last_y - first_y + 1) as u32;
+    if columns.saturating_mul(rows) > MAX_TILES {
+        return Err(format!("O mapa base precisaria de {} tiles no zoom {}; a trilha é grande demais para os zooms disponíveis", columns.saturating_mul(rows), zoom).into());
+    }
+    let mut mosaic = RgbaImage::new(columns * TILE_SIZE, rows * TILE_SIZE);
+    let mut cache = TileCache { source, tiles: HashMap::new() };
+    let tiles_per_side = 1i64 << zoom;
+    for tile_y in first_y..=last_y {
+        if !(0..tiles_per_side).contains(&tile_y) {
+            continue;
+        }
+        for tile_x in first_x..=last_x {
+            // A longitude dá a volta ao mundo
+            let wrapped_x = tile_x.rem_euclid(tiles_per_side) as u32;
+            if let Some(tile) = cache.get_or_parent(zoom, wrapped_x, tile_y as u32, min_zoom)? {
+                let offset = |tile: i64, first: i64| (tile - first) * TILE_SIZE as i64;
+                imageops::replace(&mut mosaic, &tile, offset(tile_x, first_x), offset(tile_y, first_y));
+            }
+        }
+    }
+
+    // Cada pixel lê o mosaico na sua posição exata, para coincidir com a trilha mesmo quando
+    // os tiles são muito ampliados
+    let origin_x = (first_x * TILE_SIZE as i64) as f64;
+    let origin_y = (first_y * TILE_SIZE as i64) as f64;
+    Ok(RgbaImage::from_fn(width, height, |x, y| {
+        let (plane_x, plane_y) = projection.plane_at(x as f64 + 0.5, y as f64 + 0.5);
+        sample_bilinear(&mosaic, plane_x * world - origin_x - 0.5, plane_y * world - origin_y - 0.5)
+    }))
+}
+
+fn sample_bilinear(image: &RgbaImage, x: f64, y: f64) -> Rgba<u8> {
+    let x = x.clamp(0.0, image.width() as f64 - 1.0);
+    let y = y.clamp(0.0, image.height() as f64 - 1.0);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(image.width() - 1), (y0 + 1).min(image.height() - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+    let corners = [image.get_pixel(x0, y0).0, image.get_pixel(x1, y0).0, image.get_pixel(x0, y1).0, image.get_pixel(x1, y1).0];
+    Rgba(std::array::from_fn(|i| {
+        let top = corners[0][i] as f64 * (1.0 - fx) + corners[1][i] as f64 * fx;
+        let bottom = corners[2][i] as f64 * (1.0 - fx) + corners[3][i] as f64 * fx;
+        (top * (1.0 - fy) + bottom * fy).round() as u8
+    }))
+}
+
+/// Texto de uma atribuição em HTML (`<a href="...">&copy; OpenStreetMap</a>`)
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {},
+        }
+    }
+    text.replace("&copy;", "©").replace("&nbsp;", " ").replace("&amp;", "&").split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,25 @@
+// src/cancellation.rs - Pedido de cancelamento partilhado entre o servidor e o processamento
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Sinal de cancelamento de um job. O servidor chama `cancel`; o processamento consulta
+/// `is_cancelled` entre etapas e em cada frame e termina o FFmpeg quando o vê.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
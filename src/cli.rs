@@ -0,0 +1,408 @@
+// src/cli.rs - Linha de comando: renderização e sugestão de sincronização sem o servidor
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crate::cancellation::CancellationToken;
+use crate::athlete::AthleteProfile;
+use crate::clock_sync::SyncAnchor;
+use crate::heart_rate::{HeartRateZones, ZoneMethod};
+use crate::layout::{Layout, PRESETS};
+use crate::processing::{self, ProcessRequest};
+use crate::progress::{ProgressEvent, ProgressReporter};
+use crate::timezone::TimezoneSettings;
+use crate::units::UnitSystem;
+use crate::SuggestionResponse;
+
+/// Vídeo gerado (ou ponto sugerido) com sucesso
+pub const EXIT_OK: u8 = 0;
+/// O processamento falhou (FFmpeg, gravação do vídeo final, ...)
+pub const EXIT_FAILURE: u8 = 1;
+/// Argumentos inválidos; é também o código usado pelo clap
+pub const EXIT_USAGE: u8 = 2;
+/// O vídeo ou a trilha não existem ou não puderam ser lidos
+pub const EXIT_INPUT: u8 = 3;
+/// Nenhum ponto da trilha depois do início do vídeo
+pub const EXIT_NO_SYNC_POINT: u8 = 4;
+/// Interrompido com Ctrl+C
+pub const EXIT_CANCELLED: u8 = 130;
+
+/// Sem subcomando, o servidor web é iniciado
+#[derive(Debug, Parser)]
+#[command(name = "extrator_gpx", version, about = "Overlays de telemetria GPS em vídeos")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Os argumentos são lidos uma só vez; não vale a pena pô-los numa Box
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Gera o vídeo com os overlays, com o mesmo processamento do `/process`
+    Render(RenderArgs),
+    /// Escreve em JSON o ponto de sincronização sugerido, como o `/suggest`
+    Suggest(SuggestArgs),
+}
+
+/// Arquivos e opções de leitura comuns aos dois subcomandos
+#[derive(Debug, Args)]
+pub struct InputArgs {
+    /// Trilha (GPX, TCX, FIT ou SRT da DJI); sem ela usa-se a telemetria GPMF do vídeo
+    #[arg(long)]
+    pub track: Option<PathBuf>,
+    #[arg(long)]
+    pub video: PathBuf,
+    /// Nível de interpolação dos pontos: intervalo máximo entre pontos, em segundos (1 ou mais)
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i64).range(1..))]
+    pub interpolation: i64,
+    /// Fuso IANA do relógio da câmara
+    #[arg(long)]
+    pub camera_tz: Option<String>,
+    /// Fuso IANA de exibição das horas, ou "auto" para o da trilha
+    #[arg(long)]
+    pub display_tz: Option<String>,
+    /// Corrige o relógio da câmara pelo movimento do vídeo
+    #[arg(long)]
+    pub auto_sync: bool,
+    /// Unidades dos overlays e do resumo: "metric", "imperial", "nautical", "pace-km" ou "pace-mi"
+    #[arg(long, default_value = "metric")]
+    pub units: UnitSystem,
+    /// FTP do atleta (W), para o IF e o TSS
+    #[arg(long)]
+    pub ftp: Option<f64>,
+    /// Peso do atleta (kg), para a potência em W/kg
+    #[arg(long)]
+    pub weight: Option<f64>,
+}
+
+impl InputArgs {
+    fn timezones(&self) -> Result<TimezoneSettings, String> {
+        TimezoneSettings::from_params(self.camera_tz.as_deref(), self.display_tz.as_deref()).map_err(|e| e.to_string())
+    }
+
+    /// Perfil com o FTP e o peso indicados
+    fn athlete(&self, heart_rate_zones: Option<HeartRateZones>) -> Result<AthleteProfile, String> {
+        AthleteProfile { heart_rate_zones, ..Default::default() }.with_power(self.ftp, self.weight)
+    }
+
+    /// Trilha a usar: a indicada ou, sem ela, o próprio vídeo (GoPro)
+    fn track_path(&self) -> PathBuf {
+        self.track.clone().unwrap_or_else(|| self.video.clone())
+    }
+
+    /// Confirma que os arquivos existem antes de chamar o ffprobe
+    fn check_files(&self) -> Result<(), String> {
+        for path in std::iter::once(&self.video).chain(self.track.as_ref()) {
+            if !path.is_file() {
+                return Err(format!("Arquivo não encontrado: {}", path.display()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lê os metadados do vídeo e a trilha, para que um arquivo ilegível seja um erro de
+    /// entrada antes do processamento (em `--sync auto` a sugestão já os lê)
+    fn check_readable(&self, timezones: TimezoneSettings) -> Result<(), String> {
+        crate::video_time::get_video_time_range(&self.video, timezones.camera, "pt")
+            .map_err(|e| format!("Não foi possível ler os metadados do vídeo {}: {}", self.video.display(), e))?;
+        let track_path = self.track_path();
+        crate::track::read_track_file(&track_path, timezones.camera)
+            .map_err(|e| format!("Não foi possível ler a trilha {}: {}", track_path.display(), e))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RenderArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Hora da trilha no início do vídeo (RFC 3339) ou "auto" para a sugestão
+    #[arg(long, default_value = "auto")]
+    pub sync: SyncPoint,
+    /// Segunda âncora: tempo do vídeo ("754.5" ou "12:34.5"), com --second-track-time
+    #[arg(long, requires = "second_track_time")]
+    pub second_video_time: Option<String>,
+    /// Segunda âncora: hora da trilha em RFC 3339, com --second-video-time
+    #[arg(long, requires = "second_video_time")]
+    pub second_track_time: Option<String>,
+    /// Posição do velocímetro; sem ela o velocímetro não é desenhado
+    #[arg(long, value_enum)]
+    pub speedo: Option<Position>,
+    /// Posição do mapa do percurso
+    #[arg(long, value_enum)]
+    pub map: Option<Position>,
+    /// Posição do painel de estatísticas
+    #[arg(long, value_enum)]
+    pub stats: Option<Position>,
+    /// Layout dos overlays (JSON ou TOML), no lugar de --speedo, --map e --stats
+    #[arg(long, conflicts_with_all = ["speedo", "map", "stats", "preset"])]
+    pub layout: Option<PathBuf>,
+    /// Layout incluído ("classic", "minimal", "dashboard", "training" ou "cycling")
+    #[arg(long, conflicts_with_all = ["speedo", "map", "stats"])]
+    pub preset: Option<String>,
+    /// Mapa base offline sob o mini-mapa: arquivo MBTiles ou diretório de tiles XYZ
+    /// (`{z}/{x}/{y}.png`)
+    #[arg(long)]
+    pub basemap: Option<PathBuf>,
+    /// Atribuição dos tiles; sem ela usa-se a do MBTiles ("" esconde-a)
+    #[arg(long, requires = "basemap")]
+    pub attribution: Option<String>,
+    /// Zonas de FC do widget `heart_rate`: "max-hr", "lthr" ou "reserve"; sem ele deduz-se
+    /// das FC indicadas
+    #[arg(long)]
+    pub hr_zones: Option<ZoneMethod>,
+    /// FC máxima do atleta (bpm)
+    #[arg(long)]
+    pub max_hr: Option<f64>,
+    /// FC de repouso (bpm), para as zonas por reserva
+    #[arg(long)]
+    pub resting_hr: Option<f64>,
+    /// FC do limiar de lactato (bpm)
+    #[arg(long)]
+    pub lthr: Option<f64>,
+    /// Idioma dos logs e dos overlays ("pt" ou "en")
+    #[arg(long, default_value = "pt")]
+    pub lang: String,
+    /// Vídeo final
+    #[arg(long)]
+    pub out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct SuggestArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Inclui os pontos interpolados da trilha na saída
+    #[arg(long)]
+    pub points: bool,
+}
+
+/// Canto do vídeo onde um overlay é desenhado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Position {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Position {
+    /// Nome usado pelo formulário e por `Anchor::from_corner`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Position::TopLeft => "top-left",
+            Position::TopRight => "top-right",
+            Position::BottomLeft => "bottom-left",
+            Position::BottomRight => "bottom-right",
+        }
+    }
+}
+
+/// Valor de `--sync`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncPoint {
+    /// Primeiro ponto da trilha depois do início do vídeo, como no `suggest`
+    Auto,
+    At(DateTime<Utc>),
+}
+
+impl FromStr for SyncPoint {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.trim().eq_ignore_ascii_case("auto") {
+            return Ok(SyncPoint::Auto);
+        }
+        value.trim().parse::<DateTime<Utc>>()
+            .map(SyncPoint::At)
+            .map_err(|e| format!("use \"auto\" ou uma hora RFC 3339 ({})", e))
+    }
+}
+
+/// Executa um subcomando e devolve o código de saída
+pub fn run(command: Command) -> u8 {
+    match command {
+        Command::Render(args) => render(args),
+        Command::Suggest(args) => suggest(args),
+    }
+}
+
+fn render(args: RenderArgs) -> u8 {
+    let timezones = match args.input.timezones() {
+        Ok(timezones) => timezones,
+        Err(message) => return fail(EXIT_USAGE, &message),
+    };
+    let second_anchor = match (args.second_video_time.as_deref(), args.second_track_time.as_deref()) {
+        (Some(video_time), Some(track_time)) => match SyncAnchor::parse(video_time, track_time) {
+            Ok(anchor) => Some(anchor),
+            Err(message) => return fail(EXIT_USAGE, &message),
+        },
+        _ => None,
+    };
+    let mut layout = match (&args.layout, &args.preset) {
+        (Some(path), _) => {
+            let document = match std::fs::read_to_string(path) {
+                Ok(document) => document,
+                Err(e) => return fail(EXIT_INPUT, &format!("Não foi possível ler o layout {}: {}", path.display(), e)),
+            };
+            match Layout::parse(&document) {
+                Ok(layout) => layout,
+                Err(e) => return fail(EXIT_USAGE, &e.to_string()),
+            }
+        },
+        (None, Some(name)) => match Layout::preset(name) {
+            Some(layout) => layout,
+            None => {
+                let names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+                return fail(EXIT_USAGE, &format!("Layout desconhecido: {} (disponíveis: {})", name, names.join(", ")));
+            },
+        },
+        (None, None) => Layout::from_corners(position_name(args.speedo), position_name(args.map), position_name(args.stats)),
+    };
+    if let Some(path) = &args.basemap {
+        if !layout.contains("track_map") {
+            return fail(EXIT_USAGE, "--basemap precisa do mapa do percurso (--map ou um layout com track_map)");
+        }
+        layout.set_option("track_map", "basemap", serde_json::json!(path.to_string_lossy()));
+        if let Some(text) = &args.attribution {
+            layout.set_option("track_map", "attribution", serde_json::json!(text));
+        }
+    }
+    let athlete = match HeartRateZones::from_settings(args.hr_zones, args.max_hr, args.resting_hr, args.lthr)
+        .and_then(|zones| args.input.athlete(zones))
+    {
+        Ok(athlete) => athlete,
+        Err(message) => return fail(EXIT_USAGE, &message),
+    };
+    if let Err(message) = args.input.check_files() {
+        return fail(EXIT_INPUT, &message);
+    }
+
+    let track_file_path = args.input.track_path();
+    let sync_time = match args.sync {
+        SyncPoint::At(time) => match args.input.check_readable(timezones) {
+            Ok(()) => time,
+            Err(message) => return fail(EXIT_INPUT, &message),
+        },
+        SyncPoint::Auto => {
+            let suggestion = crate::build_suggestion(
+                &track_file_path,
+                &args.input.video,
+                args.input.interpolation,
+                timezones,
+                args.input.auto_sync,
+                args.input.units,
+                &athlete,
+            );
+            match suggestion {
+                Ok(suggestion) => match suggestion.timestamp.as_deref().and_then(|ts| ts.parse::<DateTime<Utc>>().ok()) {
+                    Some(time) => time,
+                    None => return fail(EXIT_NO_SYNC_POINT, &suggestion.message),
+                },
+                Err(message) => return fail(EXIT_INPUT, &message),
+            }
+        },
+    };
+    eprintln!("Sincronização: {}", sync_time.to_rfc3339_opts(SecondsFormat::Millis, true));
+
+    let request = ProcessRequest {
+        track_file_path,
+        video_path: args.input.video,
+        sync_timestamp: sync_time.to_rfc3339(),
+        second_anchor,
+        layout,
+        lang: args.lang,
+        units: args.input.units,
+        athlete,
+        interpolation_level: args.input.interpolation,
+        timezones,
+        output_file: args.out.clone(),
+    };
+
+    let cancel = CancellationToken::new();
+    cancel_on_ctrl_c(cancel.clone());
+    let progress = ProgressReporter::new(|event| {
+        if let ProgressEvent::Log { message } = event {
+            eprintln!("{}", message);
+        }
+    });
+
+    match processing::run_processing(request, progress, cancel.clone()) {
+        Ok(_) => {
+            println!("{}", args.out.display());
+            EXIT_OK
+        },
+        // O erro já foi escrito pelos logs do processamento
+        Err(_) if cancel.is_cancelled() => {
+            std::fs::remove_file(&args.out).ok();
+            EXIT_CANCELLED
+        },
+        Err(_) => EXIT_FAILURE,
+    }
+}
+
+fn suggest(args: SuggestArgs) -> u8 {
+    let timezones = match args.input.timezones() {
+        Ok(timezones) => timezones,
+        Err(message) => return print_suggestion(EXIT_USAGE, &SuggestionResponse::error(message)),
+    };
+    let athlete = match args.input.athlete(None) {
+        Ok(athlete) => athlete,
+        Err(message) => return print_suggestion(EXIT_USAGE, &SuggestionResponse::error(message)),
+    };
+    if let Err(message) = args.input.check_files() {
+        return print_suggestion(EXIT_INPUT, &SuggestionResponse::error(message));
+    }
+
+    let suggestion = crate::build_suggestion(
+        &args.input.track_path(),
+        &args.input.video,
+        args.input.interpolation,
+        timezones,
+        args.input.auto_sync,
+        args.input.units,
+        &athlete,
+    );
+    match suggestion {
+        Ok(mut suggestion) => {
+            if !args.points {
+                suggestion.interpolated_points = None;
+            }
+            let code = if suggestion.timestamp.is_some() { EXIT_OK } else { EXIT_NO_SYNC_POINT };
+            print_suggestion(code, &suggestion)
+        },
+        Err(message) => print_suggestion(EXIT_INPUT, &SuggestionResponse::error(message)),
+    }
+}
+
+/// O JSON vai sempre para a saída padrão, também nos erros, para os scripts o lerem
+fn print_suggestion(code: u8, suggestion: &SuggestionResponse) -> u8 {
+    match serde_json::to_string_pretty(suggestion) {
+        Ok(json) => println!("{}", json),
+        Err(e) => return fail(EXIT_FAILURE, &e.to_string()),
+    }
+    code
+}
+
+fn position_name(position: Option<Position>) -> Option<&'static str> {
+    position.map(|p| p.as_str())
+}
+
+fn fail(code: u8, message: &str) -> u8 {
+    eprintln!("Erro: {}", message);
+    code
+}
+
+/// Ctrl+C pede o cancelamento, como o `DELETE /jobs/{id}`: o FFmpeg é terminado na etapa seguinte
+fn cancel_on_ctrl_c(cancel: CancellationToken) {
+    std::thread::spawn(move || {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else { return };
+        if runtime.block_on(tokio::signal::ctrl_c()).is_ok() {
+            eprintln!("A cancelar...");
+            cancel.cancel();
+        }
+    });
+}
+
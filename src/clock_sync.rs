@@ -0,0 +1,89 @@
+// src/clock_sync.rs - Correspondência entre o tempo do vídeo e o tempo da trilha
+
+use chrono::{DateTime, Utc};
+
+/// Maior deriva aceite entre os dois relógios (1 % = 36 s por hora); acima disso
+/// a âncora está quase certamente errada
+const MAX_DRIFT_RATIO: f64 = 0.01;
+
+/// Um instante do vídeo e a hora da trilha que lhe corresponde
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncAnchor {
+    /// Segundos desde o início do vídeo
+    pub video_secs: f64,
+    pub track_time: DateTime<Utc>,
+}
+
+impl SyncAnchor {
+    /// Lê uma âncora dos campos do formulário: segundos do vídeo ("754.5" ou "12:34.5")
+    /// e hora da trilha em RFC 3339
+    pub fn parse(video_time: &str, track_time: &str) -> Result<Self, String> {
+        let video_secs = parse_video_time(video_time)
+            .ok_or_else(|| format!("Tempo de vídeo inválido: '{}'", video_time))?;
+        let track_time = track_time.parse::<DateTime<Utc>>()
+            .map_err(|e| format!("Hora da trilha inválida '{}': {}", track_time, e))?;
+        Ok(SyncAnchor { video_secs, track_time })
+    }
+}
+
+/// Transformação linear do tempo da trilha para o tempo do vídeo. Com uma âncora é um
+/// desvio constante; com duas, a diferença de ritmo entre os relógios é distribuída ao
+/// longo da gravação.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSync {
+    origin: SyncAnchor,
+    /// Segundos de vídeo por segundo de trilha
+    video_per_track: f64,
+}
+
+impl ClockSync {
+    pub fn new(anchor: SyncAnchor) -> Self {
+        ClockSync { origin: anchor, video_per_track: 1.0 }
+    }
+
+    /// Ajusta a reta às duas âncoras. Devolve `None` se a segunda não vier pelo menos 1 s
+    /// depois da primeira, no vídeo e na trilha, ou se implicarem uma deriva superior a
+    /// `MAX_DRIFT_RATIO`.
+    pub fn from_anchors(first: SyncAnchor, second: SyncAnchor) -> Option<Self> {
+        let video_span = second.video_secs - first.video_secs;
+        let track_span = (second.track_time - first.track_time).num_milliseconds() as f64 / 1000.0;
+        if video_span < 1.0 || track_span < 1.0 {
+            return None;
+        }
+
+        let video_per_track = video_span / track_span;
+        if (video_per_track - 1.0).abs() > MAX_DRIFT_RATIO {
+            return None;
+        }
+        Some(ClockSync { origin: first, video_per_track })
+    }
+
+    /// Segundos do vídeo em que aparece o instante `track_time` da trilha
+    pub fn video_secs(&self, track_time: DateTime<Utc>) -> f64 {
+        let track_elapsed = (track_time - self.origin.track_time).num_milliseconds() as f64 / 1000.0;
+        self.origin.video_secs + track_elapsed * self.video_per_track
+    }
+
+    /// Segundos que o relógio da câmara perde (positivo) ou ganha (negativo) por hora
+    pub fn drift_secs_per_hour(&self) -> f64 {
+        (1.0 / self.video_per_track - 1.0) * 3600.0
+    }
+}
+
+/// Aceita segundos ("754.5"), "mm:ss" ou "hh:mm:ss", com fração opcional
+fn parse_video_time(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', ".");
+    let mut secs = 0.0;
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    for part in &parts {
+        let part: f64 = part.parse().ok()?;
+        if part < 0.0 {
+            return None;
+        }
+        secs = secs * 60.0 + part;
+    }
+    secs.is_finite().then_some(secs)
+}
@@ -0,0 +1,275 @@
+// src/dji_srt.rs - Leitura das legendas .SRT de telemetria gravadas pelos drones DJI
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use gpx::{Gpx, Track, TrackSegment, Waypoint, GpxVersion};
+use geo_types::Point;
+use crate::tcx_adapter::TcxExtraData;
+use crate::telemetry::{AltitudeSource, CameraSettings, TelemetrySample};
+use crate::utils::{distance_2d, local_to_utc};
+
+/// O drone escreve uma legenda por frame (25-60 por segundo); como o pipeline gera um
+/// frame de overlay por ponto, mantém-se no máximo uma legenda por segundo de vídeo.
+const MIN_POINT_INTERVAL_SECS: f64 = 1.0;
+
+/// Estrutura para o resultado do processamento de um arquivo SRT da DJI
+pub struct DjiSrtProcessResult {
+    pub gpx: Gpx,
+    pub extra_data: TcxExtraData,
+    /// Telemetria por ponto, paralela aos pontos de `gpx`, com os parâmetros da câmara
+    pub telemetry: Vec<TelemetrySample>,
+    /// Início do vídeo em UTC, derivado da hora da primeira legenda com posição
+    pub video_start_time: Option<DateTime<Utc>>,
+}
+
+/// Lê um arquivo .SRT da DJI e converte as legendas em pontos com hora, altitude e
+/// parâmetros da câmara. Suporta o formato com campos entre colchetes (Mini, Air 2S,
+/// Mavic 3), o formato `GPS(...)`/`BAROMETER:` (Phantom 4, Mavic Pro) e o formato
+/// em lista separada por vírgulas (Mavic Air 2). As horas das legendas são do relógio
+/// do drone e são lidas no fuso `camera_tz`.
+pub fn read_and_process_dji_srt(path: &Path, camera_tz: Tz) -> Result<DjiSrtProcessResult, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)?;
+    parse_dji_srt(&content, camera_tz)
+}
+
+/// Converte o conteúdo de um .SRT da DJI; separado da leitura do arquivo para facilitar testes.
+pub fn parse_dji_srt(content: &str, camera_tz: Tz) -> Result<DjiSrtProcessResult, Box<dyn Error>> {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        creator: Some("GPX Video Sync - DJI SRT Adapter".to_string()),
+        ..Default::default()
+    };
+    let mut extra_data = TcxExtraData {
+        device_name: Some("DJI".to_string()),
+        ..Default::default()
+    };
+    let mut telemetry = Vec::new();
+    let mut segment = TrackSegment::new();
+    let mut video_start_time = None;
+    let mut cue_count = 0;
+    let mut first_video_time: Option<f64> = None;
+    let mut last_video_time: f64 = 0.0;
+
+    for block in content.split("\n\n").map(str::trim).filter(|b| !b.is_empty()) {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(video_time_secs) = lines.next().and_then(parse_cue_start) else { continue };
+        let text = strip_tags(&lines.collect::<Vec<_>>().join("\n"));
+        cue_count += 1;
+
+        let Some(local_time) = text.lines().find_map(parse_cue_datetime) else { continue };
+        let Some(utc) = local_to_utc(&local_time, camera_tz) else { continue };
+
+        let fields = parse_cue_fields(&text);
+        let Some((lat, lon)) = cue_position(&fields) else { continue };
+        // Sem fix de GPS o drone escreve 0.0 nas duas coordenadas
+        if lat == 0.0 && lon == 0.0 { continue; }
+
+        if first_video_time.is_some_and(|_| video_time_secs - last_video_time < MIN_POINT_INTERVAL_SECS - 1e-6) {
+            continue;
+        }
+        first_video_time.get_or_insert(video_time_secs);
+        last_video_time = video_time_secs;
+
+        if video_start_time.is_none() {
+            video_start_time = Some(utc - Duration::microseconds((video_time_secs * 1_000_000.0) as i64));
+        }
+
+        let mut waypoint = Waypoint::new(Point::new(lon, lat));
+        let (elevation, altitude_source) = cue_altitude(&fields).unzip();
+        waypoint.elevation = elevation;
+        if let Some(nanos) = utc.timestamp_nanos_opt() {
+            if let Ok(offset_dt) = time::OffsetDateTime::from_unix_timestamp_nanos(nanos as i128) {
+                waypoint.time = Some(gpx::Time::from(offset_dt));
+            }
+        }
+
+        if let Some(previous) = segment.points.last() {
+            extra_data.total_distance_meters += distance_2d(previous, &waypoint);
+        }
+
+        let speed_mps = field_number(&fields, &["h.s"]);
+        if let Some(speed) = speed_mps {
+            extra_data.speed_data.push(speed);
+            extra_data.max_speed = extra_data.max_speed.max(speed);
+        }
+
+        segment.points.push(waypoint);
+        let camera = cue_camera_settings(&fields);
+        telemetry.push(TelemetrySample { speed_mps, altitude_source, camera, ..Default::default() });
+    }
+
+    if cue_count == 0 {
+        return Err("Nenhuma legenda encontrada no arquivo SRT.".into());
+    }
+
+    if let Some(first) = first_video_time {
+        extra_data.total_time_seconds = last_video_time - first;
+    }
+
+    if !segment.points.is_empty() {
+        let mut track = Track::new();
+        track.name = extra_data.device_name.clone();
+        track.segments.push(segment);
+        gpx.tracks.push(track);
+    }
+
+    Ok(DjiSrtProcessResult { gpx, extra_data, telemetry, video_start_time })
+}
+
+/// Converte o início da legenda ("00:01:02,500 --> ...") em segundos de vídeo
+fn parse_cue_start(line: &str) -> Option<f64> {
+    let start = line.split("-->").next()?.trim().replace(',', ".");
+    let mut parts = start.split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Procura uma data/hora local ("2023-05-20 14:23:11.123" ou "2018.05.20 14:23:11")
+fn parse_cue_datetime(line: &str) -> Option<NaiveDateTime> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    tokens.windows(2).find_map(|pair| {
+        let date = pair[0].replace('.', "-");
+        // O Mavic Air 2 acrescenta contadores após vírgulas ("10:05:21,000,123")
+        let time = pair[1].split(',').next()?;
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S%.f").ok()
+    })
+}
+
+/// Remove as marcas HTML (`<font ...>`) que alguns modelos colocam nas legendas
+fn strip_tags(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => output.push(c),
+            _ => {}
+        }
+    }
+    output
+}
+
+/// Extrai os pares chave/valor da legenda, com as chaves em minúsculas.
+/// Aceita `[chave: valor]`, `CHAVE:valor`, `CHAVE valor` e `F/2.8`; os grupos
+/// `GPS(lon, lat, n)` e `HOME(lon, lat)` ficam como texto em "gps" e "home".
+fn parse_cue_fields(text: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let mut remaining = text.to_string();
+
+    for group in ["GPS", "HOME"] {
+        if let Some(start) = remaining.find(group) {
+            let after = &remaining[start + group.len()..];
+            if let (Some(open), Some(close)) = (after.find('('), after.find(')')) {
+                if open < close && after[..open].trim().is_empty() {
+                    fields.insert(group.to_lowercase(), after[open + 1..close].to_string());
+                    let end = start + group.len() + close + 1;
+                    remaining.replace_range(start..end, " ");
+                }
+            }
+        }
+    }
+
+    let normalized = remaining
+        .replace(['[', ']', ','], " ")
+        .replace(" :", ":");
+    let tokens: Vec<&str> = normalized.split_whitespace().collect();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        i += 1;
+
+        let (key, value) = if let Some((key, value)) = token.split_once(':') {
+            if value.is_empty() {
+                let Some(next) = tokens.get(i) else { break };
+                i += 1;
+                (key, next.to_string())
+            } else {
+                (key, value.to_string())
+            }
+        } else if let Some((key, value)) = token.split_once('/').filter(|(k, _)| k.eq_ignore_ascii_case("f")) {
+            (key, value.to_string())
+        } else if token.chars().all(|c| c.is_ascii_alphabetic() || c == '.') {
+            match tokens.get(i).filter(|next| parse_number(next).is_some()) {
+                Some(next) => {
+                    i += 1;
+                    (token, next.to_string())
+                },
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+
+        // Horas ("14:23:11") e contadores não são campos
+        if key.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            fields.insert(key.to_lowercase(), value);
+        }
+    }
+
+    fields
+}
+
+/// Lê o prefixo numérico de um valor, ignorando unidades ("24.33m", "0.00m/s", "F2.8")
+fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim_start_matches(['F', 'f']);
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || ((c == '-' || c == '+') && i == 0)))
+        .map(|(i, _)| i)
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+fn field_number(fields: &HashMap<String, String>, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| fields.get(*key).and_then(|v| parse_number(v)))
+}
+
+fn cue_position(fields: &HashMap<String, String>) -> Option<(f64, f64)> {
+    let lat = field_number(fields, &["latitude", "lat"]);
+    let lon = field_number(fields, &["longitude", "longtitude", "lon"]);
+    if let (Some(lat), Some(lon)) = (lat, lon) {
+        return Some((lat, lon));
+    }
+
+    // Formatos antigos: GPS(longitude, latitude, satélites)
+    let gps = fields.get("gps")?;
+    let mut values = gps.split(',').map(|v| parse_number(v.trim()));
+    let lon = values.next()??;
+    let lat = values.next()??;
+    Some((lat, lon))
+}
+
+/// Altitude absoluta quando existe; caso contrário a altura barométrica relativa à descolagem
+fn cue_altitude(fields: &HashMap<String, String>) -> Option<(f64, AltitudeSource)> {
+    field_number(fields, &["abs_alt", "altitude"])
+        .map(|alt| (alt, AltitudeSource::Gps))
+        .or_else(|| field_number(fields, &["rel_alt", "barometer", "h"]).map(|alt| (alt, AltitudeSource::Barometric)))
+}
+
+/// Parâmetros da câmara da legenda; `None` quando o drone não grava nenhum
+fn cue_camera_settings(fields: &HashMap<String, String>) -> Option<CameraSettings> {
+    let settings = CameraSettings {
+        iso: field_number(fields, &["iso"]),
+        exposure_secs: ["shutter", "ss"].iter().find_map(|key| fields.get(*key)).and_then(|value| parse_exposure(value)),
+        f_number: field_number(fields, &["fnum", "f"]),
+        ev: field_number(fields, &["ev"]),
+        focal_length_mm: field_number(fields, &["focal_len"]),
+    };
+    Some(settings).filter(|s| *s != CameraSettings::default())
+}
+
+/// Tempo de exposição em segundos: "1/500.0" ou só o denominador ("500")
+fn parse_exposure(value: &str) -> Option<f64> {
+    let (numerator, denominator) = value.split_once('/').unwrap_or(("1", value));
+    let secs = numerator.trim().parse::<f64>().ok()? / denominator.trim().parse::<f64>().ok()?;
+    (secs.is_finite() && secs > 0.0).then_some(secs)
+}
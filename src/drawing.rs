@@ -1,22 +1,115 @@
 use std::error::Error;
+use std::io::Read;
 use image::{Rgba, RgbaImage};
 use image::imageops::FilterType;
 use imageproc::point::Point;
-use imageproc::drawing::{draw_polygon_mut, draw_filled_circle_mut, draw_line_segment_mut, draw_text_mut};
+use imageproc::drawing::{draw_polygon_mut, draw_filled_circle_mut, draw_filled_rect_mut, draw_line_segment_mut, draw_text_mut};
 use rusttype::{Font, Scale};
 use gpx::Gpx;
 use chrono::{DateTime, Utc, FixedOffset};
+use crate::elevation_profile::{gradient_color, ElevationProfile};
+use crate::power::{gauge_max, PowerSummary};
+use crate::heart_rate::{format_duration, HeartRateZones, TimeInZones, ZONE_COLORS, ZONE_COUNT};
+use crate::telemetry::CameraSettings;
+use crate::units::UnitSystem;
 use crate::utils::calculate_speed_kmh;
 
-pub fn generate_speedometer_image(
+const FONT_REGULAR: &[u8] = include_bytes!("../DejaVuSans.ttf");
+const FONT_BOLD: &[u8] = include_bytes!("../DejaVuSans-Bold.ttf");
+/// Nomes das fontes incluídas, aceitos por `load_font` sem ler arquivos
+pub const BUILTIN_FONTS: [&str; 2] = ["DejaVuSans", "DejaVuSans-Bold"];
+/// Tamanho máximo de um arquivo de fonte
+pub const MAX_FONT_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Cores e fontes com que um widget é desenhado; o layout pode substituir cada uma
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub text_color: Rgba<u8>,
+    /// Arco do velocímetro, marcador do mapa e valores de telemetria das estatísticas
+    pub accent_color: Rgba<u8>,
+    pub background_color: Rgba<u8>,
+    pub font: Font<'static>,
+    pub bold_font: Font<'static>,
+}
+
+impl Theme {
+    fn with_colors(text_color: Rgba<u8>, accent_color: Rgba<u8>, background_color: Rgba<u8>) -> Self {
+        Theme {
+            text_color,
+            accent_color,
+            background_color,
+            font: Font::try_from_bytes(FONT_REGULAR).expect("Fonte regular incluída"),
+            bold_font: Font::try_from_bytes(FONT_BOLD).expect("Fonte em negrito incluída"),
+        }
+    }
+
+    pub fn speedometer() -> Self {
+        Theme::with_colors(Rgba([255, 255, 255, 255]), Rgba([0, 150, 255, 255]), Rgba([0, 0, 0, 180]))
+    }
+
+    pub fn track_map() -> Self {
+        Theme::with_colors(Rgba([255, 255, 255, 255]), Rgba([255, 0, 0, 255]), Rgba([0, 0, 0, 100]))
+    }
+
+    pub fn stats() -> Self {
+        Theme::with_colors(Rgba([255, 255, 255, 255]), Rgba([3, 218, 198, 255]), Rgba([0, 0, 0, 0]))
+    }
+
+    pub fn heart_rate() -> Self {
+        Theme::with_colors(Rgba([255, 255, 255, 255]), Rgba([244, 67, 54, 255]), Rgba([0, 0, 0, 160]))
+    }
+
+    pub fn power() -> Self {
+        Theme::with_colors(Rgba([255, 255, 255, 255]), Rgba([255, 193, 7, 255]), Rgba([0, 0, 0, 160]))
+    }
+
+    pub fn elevation_profile() -> Self {
+        Theme::with_colors(Rgba([255, 255, 255, 255]), Rgba([255, 152, 0, 255]), Rgba([0, 0, 0, 140]))
+    }
+}
+
+/// Fonte incluída ("DejaVuSans", "DejaVuSans-Bold") ou arquivo TTF/OTF
+pub fn load_font(name: &str) -> Result<Font<'static>, Box<dyn Error>> {
+    let font = match name {
+        "DejaVuSans" => Font::try_from_bytes(FONT_REGULAR),
+        "DejaVuSans-Bold" => Font::try_from_bytes(FONT_BOLD),
+        path => Font::try_from_vec(read_font_file(path)?),
+    };
+    font.ok_or_else(|| format!("Fonte inválida: {}", name).into())
+}
+
+/// Conteúdo de um arquivo de fonte comum de até `MAX_FONT_BYTES`
+fn read_font_file(path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let unreadable = |e: std::io::Error| format!("Não foi possível ler a fonte {}: {}", path, e);
+    let metadata = std::fs::metadata(path).map_err(unreadable)?;
+    if !metadata.is_file() {
+        return Err(format!("A fonte {} não é um arquivo", path).into());
+    }
+    if metadata.len() > MAX_FONT_BYTES {
+        return Err(format!("A fonte {} tem mais de {} MB", path, MAX_FONT_BYTES / (1024 * 1024)).into());
+    }
+    // O limite vale também se o arquivo crescer depois da verificação
+    let mut data = Vec::new();
+    std::fs::File::open(path).and_then(|file| file.take(MAX_FONT_BYTES + 1).read_to_end(&mut data)).map_err(unreadable)?;
+    if data.len() as u64 > MAX_FONT_BYTES {
+        return Err(format!("A fonte {} tem mais de {} MB", path, MAX_FONT_BYTES / (1024 * 1024)).into());
+    }
+    Ok(data)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_speedometer_image(
     speed_kmh: f64,
     bearing: f64,
     g_force: f64,
     elevation: f64,
-    output_path: &str,
     lang: &str,
     detected_max_speed: Option<f64>, // NOVO
-) -> Result<(), Box<dyn Error>> {
+    theme: &Theme,
+    units: UnitSystem,
+    output_size: (u32, u32),
+) -> Result<RgbaImage, Box<dyn Error>> {
+    // Desenhado a 4x do tamanho natural (300) e reduzido diretamente para `output_size`
     const SCALE_FACTOR: u32 = 4;
     const FINAL_IMG_SIZE: u32 = 300;
     const IMG_SIZE: u32 = FINAL_IMG_SIZE * SCALE_FACTOR;
@@ -24,47 +117,48 @@ pub fn generate_speedometer_image(
     const CENTER: (i32, i32) = (IMG_SIZE as i32 / 2, IMG_SIZE as i32 / 2);
     const RADIUS: f32 = 120.0 * SCALE_FACTOR as f32;
 
-    let base_max = detected_max_speed.unwrap_or(120.0);
-    let max_speed = ((base_max / 10.0).ceil() * 10.0).max(20.0);
+    // Escala, traços e valor na unidade do sistema; no ritmo o arco continua a ser velocidade
+    let gauge = units.gauge_scale(detected_max_speed);
+    let max_speed = gauge.max;
+    let speed = units.speed(speed_kmh);
 
     let mut img = RgbaImage::new(IMG_SIZE, IMG_SIZE);
-    let white = Rgba([255u8, 255, 255, 255]);
-    let transparent_black = Rgba([0u8, 0, 0, 180]);
-    let blue_arc = Rgba([0, 150, 255, 255]);
-
-    let font_data_regular = include_bytes!("../DejaVuSans.ttf");
-    let font_data_bold = include_bytes!("../DejaVuSans-Bold.ttf");
-    let font_regular = Font::try_from_bytes(&font_data_regular[..]).ok_or("Falha ao carregar a fonte regular")?;
-    let font_bold = Font::try_from_bytes(&font_data_bold[..]).ok_or("Falha ao carregar a fonte em negrito")?;
+    let white = theme.text_color;
+    let transparent_black = theme.background_color;
+    let blue_arc = theme.accent_color;
+    let font_regular = &theme.font;
+    let font_bold = &theme.bold_font;
     
     draw_filled_circle_mut(&mut img, CENTER, (RADIUS + 15.0 * SCALE_FACTOR as f32) as i32, transparent_black);
 
     // --- arco proporcional ---
-    let speed_ratio = (speed_kmh / max_speed).min(1.0);
+    let speed_ratio = (speed / max_speed).min(1.0);
     let start_angle = 90.0;
     let sweep_angle = speed_ratio * 270.0;
     draw_arc_mut(&mut img, CENTER, RADIUS as i32, start_angle, sweep_angle, blue_arc, 12 * SCALE_FACTOR as i32);
 
     let end_angle_rad = (start_angle + sweep_angle).to_radians() as f32;
     let (end_x, end_y) = (
-        CENTER.0 as f32 + RADIUS as f32 * end_angle_rad.cos(),
-        CENTER.1 as f32 + RADIUS as f32 * end_angle_rad.sin(),
+        CENTER.0 as f32 + RADIUS * end_angle_rad.cos(),
+        CENTER.1 as f32 + RADIUS * end_angle_rad.sin(),
     );
     draw_filled_circle_mut(&mut img, (end_x as i32, end_y as i32), 6 * SCALE_FACTOR as i32, white);
 
     let scale_text = Scale::uniform(22.0 * SCALE_FACTOR as f32);
-    for i in 0..=max_speed as i32 {
-        if i % 5 == 0 {
+    for i in 0..=max_speed as u32 {
+        if i % gauge.minor_step == 0 {
             let angle = 90.0 + (i as f64 / max_speed) * 270.0;
             let rad = angle.to_radians() as f32;
-            let tick_length = if i % 25 == 0 { 15.0 } else { 8.0 } * SCALE_FACTOR as f32;
+            let tick_length = if i % gauge.major_step == 0 { 15.0 } else { 8.0 } * SCALE_FACTOR as f32;
             let (x1, y1) = (CENTER.0 as f32 + rad.cos() * (RADIUS - tick_length), CENTER.1 as f32 + rad.sin() * (RADIUS - tick_length));
             let (x2, y2) = (CENTER.0 as f32 + rad.cos() * RADIUS, CENTER.1 as f32 + rad.sin() * RADIUS);
             draw_line_segment_mut(&mut img, (x1, y1), (x2, y2), white);
             
-            if i % 25 == 0 {
+            if let Some(label) = (i % gauge.major_step == 0).then(|| units.gauge_label(i)).flatten() {
+                // Os rótulos de ritmo ("12:00") são mais largos que os de velocidade
+                let label_scale = if units.is_pace() { Scale::uniform(16.0 * SCALE_FACTOR as f32) } else { scale_text };
                 let (tx, ty) = (CENTER.0 as f32 + rad.cos() * (RADIUS - 35.0 * SCALE_FACTOR as f32), CENTER.1 as f32 + rad.sin() * (RADIUS - 35.0 * SCALE_FACTOR as f32));
-                draw_centered_text_mut(&mut img, white, tx as i32, ty as i32, scale_text, &font_bold, &i.to_string());
+                draw_centered_text_mut(&mut img, white, tx as i32, ty as i32, label_scale, font_bold, &label);
             }
         }
     }
@@ -73,10 +167,10 @@ pub fn generate_speedometer_image(
     let (east_label, west_label) = if lang == "en" { ("E", "W") } else { ("L", "O") };
     let compass_radius = 40.0 * SCALE_FACTOR as f32;
     let bearing_rad = bearing.to_radians() as f32;
-    draw_centered_text_mut(&mut img, white, CENTER.0, CENTER.1 - (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, scale_text, &font_regular, "N");
-    draw_centered_text_mut(&mut img, white, CENTER.0, CENTER.1 + (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, scale_text, &font_regular, "S");
-    draw_centered_text_mut(&mut img, white, CENTER.0 + (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, CENTER.1, scale_text, &font_regular, east_label);
-    draw_centered_text_mut(&mut img, white, CENTER.0 - (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, CENTER.1, scale_text, &font_regular, west_label);
+    draw_centered_text_mut(&mut img, white, CENTER.0, CENTER.1 - (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, scale_text, font_regular, "N");
+    draw_centered_text_mut(&mut img, white, CENTER.0, CENTER.1 + (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, scale_text, font_regular, "S");
+    draw_centered_text_mut(&mut img, white, CENTER.0 + (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, CENTER.1, scale_text, font_regular, east_label);
+    draw_centered_text_mut(&mut img, white, CENTER.0 - (compass_radius + 10.0 * SCALE_FACTOR as f32) as i32, CENTER.1, scale_text, font_regular, west_label);
 
     let p_n = Point { x: CENTER.0, y: CENTER.1 - compass_radius as i32 };
     let p_s = Point { x: CENTER.0, y: CENTER.1 + (15 * SCALE_FACTOR as i32) };
@@ -90,139 +184,175 @@ pub fn generate_speedometer_image(
 
     draw_polygon_mut(&mut img, &[rotated_n, rotated_e, rotated_s, rotated_w], Rgba([255, 0, 0, 255]));
 
-    let speed_color = speed_to_color(speed_kmh, max_speed);
-    let scale_speed = Scale::uniform(60.0 * SCALE_FACTOR as f32);
-    let speed_text = format!("{:.0}", speed_kmh);
-    draw_text_mut(&mut img, speed_color, CENTER.0 + (30 * SCALE_FACTOR as i32), CENTER.1 + (50 * SCALE_FACTOR as i32), scale_speed, &font_bold, &speed_text);
+    let speed_color = speed_to_color(speed, max_speed);
+    let scale_speed = Scale::uniform(if units.is_pace() { 40.0 } else { 60.0 } * SCALE_FACTOR as f32);
+    let speed_text = units.format_speed(speed_kmh, 0);
+    draw_text_mut(&mut img, speed_color, CENTER.0 + (30 * SCALE_FACTOR as i32), CENTER.1 + (50 * SCALE_FACTOR as i32), scale_speed, font_bold, &speed_text);
     let scale_unit = Scale::uniform(20.0 * SCALE_FACTOR as f32);
-    draw_text_mut(&mut img, white, CENTER.0 + (35 * SCALE_FACTOR as i32), CENTER.1 + (100 * SCALE_FACTOR as i32), scale_unit, &font_regular, "KM/H");
+    draw_text_mut(&mut img, white, CENTER.0 + (35 * SCALE_FACTOR as i32), CENTER.1 + (100 * SCALE_FACTOR as i32), scale_unit, font_regular, units.speed_unit());
 
     // Coordenadas ajustadas para os marcadores de Força G e Altitude
     let g_force_text = format!("{:.1} g", g_force);
     let g_force_center = ((40 * SCALE_FACTOR as i32), (40 * SCALE_FACTOR as i32));
     draw_filled_circle_mut(&mut img, g_force_center, 35 * SCALE_FACTOR as i32, transparent_black);
-    draw_centered_text_mut(&mut img, white, g_force_center.0, g_force_center.1, Scale::uniform(20.0 * SCALE_FACTOR as f32), &font_regular, &g_force_text);
+    draw_centered_text_mut(&mut img, white, g_force_center.0, g_force_center.1, Scale::uniform(20.0 * SCALE_FACTOR as f32), font_regular, &g_force_text);
     
-    let elevation_text = format!("{:.0} m", elevation);
+    let elevation_text = format!("{:.0} {}", units.elevation(elevation), units.elevation_unit().to_lowercase());
     let elevation_center = (
         (40 * SCALE_FACTOR as i32),
         (IMG_SIZE as i32 - (40 * SCALE_FACTOR as i32))
     );
     draw_filled_circle_mut(&mut img, elevation_center, 35 * SCALE_FACTOR as i32, transparent_black);
-    draw_centered_text_mut(&mut img, white, elevation_center.0, elevation_center.1 - (5 * SCALE_FACTOR as i32), Scale::uniform(16.0 * SCALE_FACTOR as f32), &font_regular, "ALT");
-    draw_centered_text_mut(&mut img, white, elevation_center.0, elevation_center.1 + (10 * SCALE_FACTOR as i32), Scale::uniform(20.0 * SCALE_FACTOR as f32), &font_bold, &elevation_text);
+    draw_centered_text_mut(&mut img, white, elevation_center.0, elevation_center.1 - (5 * SCALE_FACTOR as i32), Scale::uniform(16.0 * SCALE_FACTOR as f32), font_regular, "ALT");
+    draw_centered_text_mut(&mut img, white, elevation_center.0, elevation_center.1 + (10 * SCALE_FACTOR as i32), Scale::uniform(20.0 * SCALE_FACTOR as f32), font_bold, &elevation_text);
 
     let final_img = image::imageops::resize(
         &img,
-        FINAL_IMG_SIZE,
-        FINAL_IMG_SIZE,
+        output_size.0.max(1),
+        output_size.1.max(1),
         FilterType::Lanczos3,
     );
-    final_img.save(output_path)?;
 
-    Ok(())
+    Ok(final_img)
 }
 
-pub fn generate_stats_image(
-    distance_km: f64,
-    altitude_m: f64,
-    elevation_gain_m: f64,
-    current_time_utc: DateTime<Utc>,
-    output_path: &str,
+/// Valores exibidos no painel de estatísticas num instante da trilha
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsReading {
+    pub distance_km: f64,
+    pub altitude_m: f64,
+    pub elevation_gain_m: f64,
+    pub point_time: DateTime<Utc>,
+    pub heart_rate: Option<f64>,
+    /// Zona (0 = Z1) da FC quando o atleta configurou as zonas
+    pub heart_rate_zone: Option<usize>,
+    pub cadence: Option<f64>,
+    pub speed_kmh: Option<f64>,
+    pub calories: Option<f64>,
+    pub camera: Option<CameraSettings>,
+    pub power: Option<f64>,
+    /// NP, IF e TSS até este instante, numa linha por baixo da potência
+    pub power_summary: Option<PowerSummary>,
+    /// Desvio do fuso de exibição, para a hora e a data do painel
+    pub utc_offset_secs: i32,
+}
+
+pub fn render_stats_image(
+    reading: &StatsReading,
     lang: &str,
-    heart_rate: Option<f64>,
-    cadence: Option<f64>,
-    speed_kmh: Option<f64>,
-    calories: Option<f64>,
-    timezone_offset_secs: i32, // NOVO
-) -> Result<(), Box<dyn Error>> {
+    theme: &Theme,
+    units: UnitSystem,
+    scale: f32,
+) -> Result<RgbaImage, Box<dyn Error>> {
+    // Medidas no tamanho natural (280x420), multiplicadas por `scale`
     const WIDTH: u32 = 280;
     const HEIGHT: u32 = 420; 
-    let mut img = RgbaImage::new(WIDTH, HEIGHT);
-
-    for pixel in img.pixels_mut() {
-        *pixel = Rgba([0, 0, 0, 0]);
-    }
-
-    let white = Rgba([255, 255, 255, 255]);
-    let tcx_color = Rgba([3, 218, 198, 255]); 
-    let font_data_bold = include_bytes!("../DejaVuSans-Bold.ttf");
-    let font_bold = Font::try_from_bytes(&font_data_bold[..]).ok_or("Falha ao carregar a fonte em negrito")?;
-
-    let scale_label = Scale::uniform(14.0); 
-    let scale_value = Scale::uniform(24.0); 
-    let scale_sub_value = Scale::uniform(16.0);
-    let y_start = 10;
-    let line_height = 50; 
+    let px = |value: i32| (value as f32 * scale).round() as i32;
+    let size = |value: u32| ((value as f32 * scale).round() as u32).max(1);
+    let mut img = RgbaImage::from_pixel(size(WIDTH), size(HEIGHT), theme.background_color);
+
+    let white = theme.text_color;
+    let tcx_color = theme.accent_color;
+    let font_bold = &theme.bold_font;
+
+    let scale_label = Scale::uniform(14.0 * scale); 
+    let scale_value = Scale::uniform(24.0 * scale); 
+    let scale_sub_value = Scale::uniform(16.0 * scale);
+    let y_start = px(10);
+    let line_height = px(50); 
     let mut current_y = y_start;
 
     // Distância
     let distance_label = if lang == "en" { "DISTANCE" } else { "DISTÂNCIA" };
-    let distance_value_unit = format!("{:.1} KM", distance_km);
-    draw_text_mut(&mut img, white, 10, current_y, scale_label, &font_bold, distance_label);
-    draw_text_mut(&mut img, white, 10, current_y + 18, scale_value, &font_bold, &distance_value_unit);
+    let distance_value_unit = format!("{:.1} {}", units.distance(reading.distance_km), units.distance_unit());
+    draw_text_mut(&mut img, white, px(10), current_y, scale_label, font_bold, distance_label);
+    draw_text_mut(&mut img, white, px(10), current_y + px(18), scale_value, font_bold, &distance_value_unit);
     current_y += line_height;
 
     // Altitude
-    let altitude_label = if lang == "en" { "ALTITUDE" } else { "ALTITUDE" };
-    let altitude_value_unit = format!("{:.0} M", altitude_m);
-    draw_text_mut(&mut img, white, 10, current_y, scale_label, &font_bold, altitude_label);
-    draw_text_mut(&mut img, white, 10, current_y + 18, scale_value, &font_bold, &altitude_value_unit);
+    let altitude_label = "ALTITUDE";
+    let altitude_value_unit = format!("{:.0} {}", units.elevation(reading.altitude_m), units.elevation_unit());
+    draw_text_mut(&mut img, white, px(10), current_y, scale_label, font_bold, altitude_label);
+    draw_text_mut(&mut img, white, px(10), current_y + px(18), scale_value, font_bold, &altitude_value_unit);
     current_y += line_height;
 
     // Ganho de elevação
     let elevation_gain_label = if lang == "en" { "ELEVATION GAIN" } else { "GANHO DE ELEVAÇÃO" };
-    let elevation_gain_value_unit = format!("{:.0} M", elevation_gain_m);
-    draw_text_mut(&mut img, white, 10, current_y, scale_label, &font_bold, elevation_gain_label);
-    draw_text_mut(&mut img, white, 10, current_y + 18, scale_value, &font_bold, &elevation_gain_value_unit);
+    let elevation_gain_value_unit = format!("{:.0} {}", units.elevation(reading.elevation_gain_m), units.elevation_unit());
+    draw_text_mut(&mut img, white, px(10), current_y, scale_label, font_bold, elevation_gain_label);
+    draw_text_mut(&mut img, white, px(10), current_y + px(18), scale_value, font_bold, &elevation_gain_value_unit);
     current_y += line_height;
 
     // NOVO: Frequência Cardíaca (se disponível)
-    if let Some(hr) = heart_rate {
+    if let Some(hr) = reading.heart_rate {
         let hr_label = if lang == "en" { "❤️ HEART RATE" } else { "❤️ FREQ. CARDÍACA" };
         let hr_value_unit = format!("{:.0} BPM", hr);
         
-        // Cor baseada na zona de FC (estimativa)
-        let hr_color = if hr > 160.0 { 
-            Rgba([255, 69, 0, 255])   // Vermelho para FC alta
-        } else if hr > 140.0 { 
-            Rgba([255, 165, 0, 255])  // Laranja para FC moderada
-        } else { 
-            tcx_color                 // Ciano para FC baixa
+        // Cor da zona configurada pelo atleta ou, sem ela, uma estimativa
+        let hr_color = match reading.heart_rate_zone {
+            Some(zone) => ZONE_COLORS[zone],
+            None if hr > 160.0 => Rgba([255, 69, 0, 255]),   // Vermelho para FC alta
+            None if hr > 140.0 => Rgba([255, 165, 0, 255]),  // Laranja para FC moderada
+            None => tcx_color,                               // Ciano para FC baixa
         };
         
-        draw_text_mut(&mut img, hr_color, 10, current_y, scale_label, &font_bold, hr_label);
-        draw_text_mut(&mut img, hr_color, 10, current_y + 18, scale_value, &font_bold, &hr_value_unit);
+        draw_text_mut(&mut img, hr_color, px(10), current_y, scale_label, font_bold, hr_label);
+        draw_text_mut(&mut img, hr_color, px(10), current_y + px(18), scale_value, font_bold, &hr_value_unit);
         current_y += line_height;
     }
 
     // NOVO: Cadência (se disponível)
-    if let Some(cad) = cadence {
+    if let Some(cad) = reading.cadence {
         let cadence_label = if lang == "en" { "🦵 CADENCE" } else { "🦵 CADÊNCIA" };
         let cadence_value_unit = format!("{:.0} SPM", cad);
-        draw_text_mut(&mut img, tcx_color, 10, current_y, scale_label, &font_bold, cadence_label);
-        draw_text_mut(&mut img, tcx_color, 10, current_y + 18, scale_value, &font_bold, &cadence_value_unit);
+        draw_text_mut(&mut img, tcx_color, px(10), current_y, scale_label, font_bold, cadence_label);
+        draw_text_mut(&mut img, tcx_color, px(10), current_y + px(18), scale_value, font_bold, &cadence_value_unit);
         current_y += line_height;
     }
 
+    // Potência, com o NP, o IF e o TSS acumulados por baixo
+    if let Some(power) = reading.power {
+        let power_label = if lang == "en" { "POWER" } else { "POTÊNCIA" };
+        draw_text_mut(&mut img, tcx_color, px(10), current_y, scale_label, font_bold, power_label);
+        draw_text_mut(&mut img, tcx_color, px(10), current_y + px(18), scale_value, font_bold, &format!("{:.0} W", power));
+        current_y += line_height;
+        if let Some(summary) = reading.power_summary {
+            draw_text_mut(&mut img, white, px(10), current_y - px(4), scale_label, font_bold, &summary.describe());
+            current_y += px(18);
+        }
+    }
+
     // NOVO: Velocidade (se disponível e diferente do velocímetro)
-    if let Some(spd) = speed_kmh {
-        let speed_label = if lang == "en" { "⚡ SPEED" } else { "⚡ VELOCIDADE" };
-        let speed_value_unit = format!("{:.1} KM/H", spd);
-        draw_text_mut(&mut img, tcx_color, 10, current_y, scale_label, &font_bold, speed_label);
-        draw_text_mut(&mut img, tcx_color, 10, current_y + 18, scale_value, &font_bold, &speed_value_unit);
+    if let Some(spd) = reading.speed_kmh {
+        let speed_label = match (units.is_pace(), lang == "en") {
+            (true, true) => "⚡ PACE",
+            (true, false) => "⚡ RITMO",
+            (false, true) => "⚡ SPEED",
+            (false, false) => "⚡ VELOCIDADE",
+        };
+        let speed_value_unit = format!("{} {}", units.format_speed(spd, 1), units.speed_unit());
+        draw_text_mut(&mut img, tcx_color, px(10), current_y, scale_label, font_bold, speed_label);
+        draw_text_mut(&mut img, tcx_color, px(10), current_y + px(18), scale_value, font_bold, &speed_value_unit);
         current_y += line_height;
     }
 
     // NOVO: Calorias (se disponível)
-    if let Some(cal) = calories {
+    if let Some(cal) = reading.calories {
         let calories_label = if lang == "en" { "🔥 CALORIES" } else { "🔥 CALORIAS" };
         let calories_value_unit = format!("{:.0}", cal);
-        draw_text_mut(&mut img, tcx_color, 10, current_y, scale_label, &font_bold, calories_label);
-        draw_text_mut(&mut img, tcx_color, 10, current_y + 18, scale_value, &font_bold, &calories_value_unit);
+        draw_text_mut(&mut img, tcx_color, px(10), current_y, scale_label, font_bold, calories_label);
+        draw_text_mut(&mut img, tcx_color, px(10), current_y + px(18), scale_value, font_bold, &calories_value_unit);
         current_y += line_height;
     }
 
+    // Exposição da câmara (SRT da DJI)
+    if let Some(camera) = reading.camera {
+        let camera_label = if lang == "en" { "📷 CAMERA" } else { "📷 CÂMARA" };
+        draw_text_mut(&mut img, white, px(10), current_y, scale_label, font_bold, camera_label);
+        draw_text_mut(&mut img, white, px(10), current_y + px(18), scale_sub_value, font_bold, &camera.describe());
+        current_y += px(44);
+    }
+
     // Horário e Data (sempre no final)
     //let brt_offset = FixedOffset::west_opt(3 * 3600).unwrap(); // Fuso horário UTC-3 (Horário de Brasília)
     // let local_time = current_time_utc.with_timezone(&brt_offset);
@@ -230,26 +360,162 @@ pub fn generate_stats_image(
     // let time_text = local_time.format("%H:%M").to_string();
     // let date_text = local_time.format("%d/%m/%Y").to_string();
 
-    let tz_offset = FixedOffset::east_opt(timezone_offset_secs)
+    let tz_offset = FixedOffset::east_opt(reading.utc_offset_secs)
         .unwrap_or(FixedOffset::east_opt(0).unwrap());
-    let local_time = current_time_utc.with_timezone(&tz_offset);
+    let local_time = reading.point_time.with_timezone(&tz_offset);
 
     let time_text = local_time.format("%H:%M").to_string();
     let date_text = local_time.format("%d/%m/%Y").to_string();
 
-    draw_text_mut(&mut img, white, 10, current_y, scale_value, &font_bold, &time_text);
-    draw_text_mut(&mut img, white, 10, current_y + 22, scale_sub_value, &font_bold, &date_text);
+    draw_text_mut(&mut img, white, px(10), current_y, scale_value, font_bold, &time_text);
+    draw_text_mut(&mut img, white, px(10), current_y + px(22), scale_sub_value, font_bold, &date_text);
+
+
+    draw_text_mut(&mut img, white, px(10), current_y, scale_value, font_bold, &time_text);
+    draw_text_mut(&mut img, white, px(10), current_y + px(22), scale_sub_value, font_bold, &date_text);
+
+    Ok(img)
+}
+
+
+/// Mostrador de frequência cardíaca: arco dividido nas zonas Z1–Z5, barra com a zona atual e
+/// tempo acumulado em cada zona. Desenhado a 3x do tamanho natural (300x360) e reduzido para
+/// `output_size`.
+pub fn render_heart_rate_image(
+    heart_rate: f64,
+    zones: &HeartRateZones,
+    time_in_zones: &TimeInZones,
+    lang: &str,
+    theme: &Theme,
+    output_size: (u32, u32),
+) -> Result<RgbaImage, Box<dyn Error>> {
+    const SCALE_FACTOR: f32 = 3.0;
+    const WIDTH: u32 = 300;
+    const HEIGHT: u32 = 360;
+    let px = |value: f32| (value * SCALE_FACTOR) as i32;
+
+    let mut img = RgbaImage::from_pixel(WIDTH * SCALE_FACTOR as u32, HEIGHT * SCALE_FACTOR as u32, theme.background_color);
+    let center = (px(150.0), px(135.0));
+    let radius = px(105.0);
+    let thickness = px(14.0);
+    // Arco de 270° aberto em baixo, como o velocímetro
+    let (start_angle, sweep) = (135.0, 270.0);
+    let zone = zones.zone(heart_rate);
+    let current_ratio = zones.ratio(heart_rate);
+
+    for (index, color) in ZONE_COLORS.iter().enumerate() {
+        let (from, to) = (zones.ratio(zones.bounds[index]), zones.ratio(zones.bounds[index + 1]));
+        draw_arc_mut(&mut img, center, radius, start_angle + from * sweep, (to - from) * sweep, dim_color(*color), thickness);
+        // Parte da zona já alcançada pela FC atual
+        if current_ratio > from {
+            draw_arc_mut(&mut img, center, radius, start_angle + from * sweep, (current_ratio.min(to) - from) * sweep, *color, thickness);
+        }
+    }
+    let marker_angle = ((start_angle + current_ratio * sweep) as f32).to_radians();
+    let marker = (center.0 + (radius as f32 * marker_angle.cos()) as i32, center.1 + (radius as f32 * marker_angle.sin()) as i32);
+    draw_filled_circle_mut(&mut img, marker, px(9.0), theme.text_color);
+
+    let zone_color = ZONE_COLORS[zone];
+    draw_centered_text_mut(&mut img, theme.text_color, center.0, px(120.0), Scale::uniform(72.0 * SCALE_FACTOR), &theme.bold_font, &format!("{:.0}", heart_rate));
+    draw_centered_text_mut(&mut img, theme.text_color, center.0, px(170.0), Scale::uniform(20.0 * SCALE_FACTOR), &theme.font, "BPM");
+    draw_centered_text_mut(&mut img, zone_color, center.0, px(215.0), Scale::uniform(28.0 * SCALE_FACTOR), &theme.bold_font, &format!("Z{}", zone + 1));
+
+    // Barra das zonas, com a atual em destaque, e o tempo passado em cada uma por baixo
+    let (bar_x, bar_y, bar_height, gap) = (15.0, 252.0, 24.0, 4.0);
+    let segment_width = (WIDTH as f32 - 2.0 * bar_x - gap * (ZONE_COUNT - 1) as f32) / ZONE_COUNT as f32;
+    let label_scale = Scale::uniform(14.0 * SCALE_FACTOR);
+    let time_scale = Scale::uniform(15.0 * SCALE_FACTOR);
+    for (index, color) in ZONE_COLORS.iter().enumerate() {
+        let x = bar_x + index as f32 * (segment_width + gap);
+        let fill = if index == zone { *color } else { dim_color(*color) };
+        let rect = imageproc::rect::Rect::at(px(x), px(bar_y)).of_size(px(segment_width) as u32, px(bar_height) as u32);
+        draw_filled_rect_mut(&mut img, rect, fill);
+        let middle = px(x + segment_width / 2.0);
+        draw_centered_text_mut(&mut img, theme.text_color, middle, px(bar_y + bar_height / 2.0), label_scale, &theme.bold_font, &format!("Z{}", index + 1));
+        draw_centered_text_mut(&mut img, theme.text_color, middle, px(330.0), time_scale, &theme.font, &format_duration(time_in_zones.0[index]));
+    }
+    let title = if lang == "en" { "TIME IN ZONE" } else { "TEMPO EM ZONA" };
+    draw_centered_text_mut(&mut img, theme.text_color, center.0, px(298.0), Scale::uniform(12.0 * SCALE_FACTOR), &theme.bold_font, title);
+
+    Ok(image::imageops::resize(&img, output_size.0.max(1), output_size.1.max(1), FilterType::Lanczos3))
+}
 
+/// Valores do mostrador de potência num instante da trilha
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerReading {
+    pub power_w: f64,
+    pub average_3s_w: Option<f64>,
+    pub average_10s_w: Option<f64>,
+    /// Potência instantânea por kg, com o peso do atleta
+    pub watts_per_kg: Option<f64>,
+    /// Com FTP, o arco tem uma marca no limiar e a potência aparece também em % do FTP
+    pub ftp: Option<f64>,
+}
 
-    draw_text_mut(&mut img, white, 10, current_y, scale_value, &font_bold, &time_text);
-    draw_text_mut(&mut img, white, 10, current_y + 22, scale_sub_value, &font_bold, &date_text);
+/// Mostrador de potência: arco até 150% do FTP (ou `DEFAULT_POWER_SCALE_W`), potência
+/// instantânea ao centro e as médias de 3 e 10 s e os W/kg por baixo. Desenhado a 3x do
+/// tamanho natural (300x320) e reduzido para `output_size`.
+pub fn render_power_image(
+    reading: &PowerReading,
+    lang: &str,
+    theme: &Theme,
+    output_size: (u32, u32),
+) -> Result<RgbaImage, Box<dyn Error>> {
+    const SCALE_FACTOR: f32 = 3.0;
+    const WIDTH: u32 = 300;
+    const HEIGHT: u32 = 320;
+    let px = |value: f32| (value * SCALE_FACTOR) as i32;
+
+    let mut img = RgbaImage::from_pixel(WIDTH * SCALE_FACTOR as u32, HEIGHT * SCALE_FACTOR as u32, theme.background_color);
+    let center = (px(150.0), px(135.0));
+    let radius = px(105.0);
+    let thickness = px(14.0);
+    let (start_angle, sweep) = (135.0, 270.0);
+    let scale_max = gauge_max(reading.ftp);
+    let ratio = (reading.power_w / scale_max).clamp(0.0, 1.0);
+    draw_arc_mut(&mut img, center, radius, start_angle, sweep, dim_color(theme.accent_color), thickness);
+    if ratio > 0.0 {
+        draw_arc_mut(&mut img, center, radius, start_angle, ratio * sweep, theme.accent_color, thickness);
+    }
+    if let Some(ftp) = reading.ftp {
+        // Marca do limiar, a atravessar o arco
+        let angle = ((start_angle + (ftp / scale_max).min(1.0) * sweep) as f32).to_radians();
+        let (inner, outer) = (radius as f32 - px(14.0) as f32, radius as f32 + px(14.0) as f32);
+        let point = |r: f32| (center.0 as f32 + r * angle.cos(), center.1 as f32 + r * angle.sin());
+        draw_thick_line_segment_mut(&mut img, point(inner), point(outer), 3.0 * SCALE_FACTOR, theme.text_color);
+    }
+
+    draw_centered_text_mut(&mut img, theme.text_color, center.0, px(120.0), Scale::uniform(72.0 * SCALE_FACTOR), &theme.bold_font, &format!("{:.0}", reading.power_w));
+    draw_centered_text_mut(&mut img, theme.text_color, center.0, px(170.0), Scale::uniform(20.0 * SCALE_FACTOR), &theme.font, "W");
+    if let Some(ftp) = reading.ftp {
+        let percent = format!("{:.0}% FTP", reading.power_w / ftp * 100.0);
+        draw_centered_text_mut(&mut img, theme.accent_color, center.0, px(210.0), Scale::uniform(22.0 * SCALE_FACTOR), &theme.bold_font, &percent);
+    }
 
-    img.save(output_path)?;
-    Ok(())
+    let label_scale = Scale::uniform(13.0 * SCALE_FACTOR);
+    let value_scale = Scale::uniform(26.0 * SCALE_FACTOR);
+    let averages = [("3 S", reading.average_3s_w, 80.0), ("10 S", reading.average_10s_w, 220.0)];
+    for (label, average, x) in averages {
+        let value = average.map_or("--".to_string(), |watts| format!("{:.0}", watts));
+        draw_centered_text_mut(&mut img, theme.text_color, px(x), px(245.0), label_scale, &theme.bold_font, label);
+        draw_centered_text_mut(&mut img, theme.text_color, px(x), px(272.0), value_scale, &theme.bold_font, &value);
+    }
+    let title = if lang == "en" { "AVERAGE W" } else { "MÉDIA W" };
+    draw_centered_text_mut(&mut img, theme.text_color, center.0, px(258.0), label_scale, &theme.font, title);
+    if let Some(watts_per_kg) = reading.watts_per_kg {
+        draw_centered_text_mut(&mut img, theme.accent_color, center.0, px(303.0), Scale::uniform(18.0 * SCALE_FACTOR), &theme.bold_font, &format!("{:.1} W/KG", watts_per_kg));
+    }
+
+    Ok(image::imageops::resize(&img, output_size.0.max(1), output_size.1.max(1), FilterType::Lanczos3))
 }
 
+/// Cor escurecida para as zonas fora da leitura atual
+fn dim_color(color: Rgba<u8>) -> Rgba<u8> {
+    let Rgba([r, g, b, a]) = color;
+    Rgba([r / 3, g / 3, b / 3, a])
+}
 
-pub fn generate_dot_image(path: &str, size: u32, color: Rgba<u8>) -> Result<(), Box<dyn Error>> {
+pub fn render_dot_image(size: u32, color: Rgba<u8>) -> RgbaImage {
     let mut img = RgbaImage::new(size, size);
     let center = (size as i32 / 2, size as i32 / 2);
     let radius = (size / 2) as i32 - (size as i32 / 10);
@@ -259,8 +525,7 @@ pub fn generate_dot_image(path: &str, size: u32, color: Rgba<u8>) -> Result<(),
     }
     
     draw_filled_circle_mut(&mut img, center, radius, color);
-    img.save(path)?;
-    Ok(())
+    img
 }
 
 fn draw_thick_line_segment_mut(
@@ -325,15 +590,148 @@ fn speed_to_gradient_color(speed: f64, min_speed: f64, max_speed: f64) -> Rgba<u
 }
 
 
-// FUNÇÃO MODIFICADA: `generate_track_map_image` agora usa o gradiente de cores
-pub fn generate_track_map_image(
+/// Como as posições da trilha são levadas ao plano do mini-mapa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapProjectionKind {
+    /// Longitude e latitude em graus, com a mesma escala
+    Equirectangular,
+    /// A projeção dos tiles do mapa base: o mundo de 0 a 1 nos dois eixos, y para sul
+    WebMercator,
+}
+
+/// Zoom usado numa trilha Web Mercator sem extensão (um só ponto), em pixels por mundo
+const SINGLE_POINT_MERCATOR_SCALE: f64 = 256.0 * 65536.0;
+
+/// Projeção da trilha numa imagem de `width` x `height` com margem `padding`, partilhada pelo
+/// mapa base e pelo marcador da posição atual
+#[derive(Debug, Clone, Copy)]
+pub struct MapProjection {
+    kind: MapProjectionKind,
+    min_x: f64,
+    min_y: f64,
+    scale: f64,
+    padding: f64,
+}
+
+impl MapProjection {
+    /// Projeção equiretangular, a do mini-mapa sem mapa base
+    pub fn new(gpx: &Gpx, width: u32, height: u32, padding: f64) -> Option<Self> {
+        MapProjection::fit(MapProjectionKind::Equirectangular, gpx, width, height, padding)
+    }
+
+    /// Projeção Web Mercator, para a trilha coincidir com os tiles do mapa base
+    pub fn web_mercator(gpx: &Gpx, width: u32, height: u32, padding: f64) -> Option<Self> {
+        MapProjection::fit(MapProjectionKind::WebMercator, gpx, width, height, padding)
+    }
+
+    fn fit(kind: MapProjectionKind, gpx: &Gpx, width: u32, height: u32, padding: f64) -> Option<Self> {
+        let mut points = gpx.tracks.iter()
+            .flat_map(|t| t.segments.iter())
+            .flat_map(|s| s.points.iter())
+            .map(|p| to_plane(kind, p.point().x(), p.point().y()));
+        let first = points.next()?;
+        let (min_x, max_x, min_y, max_y) = points.fold(
+            (first.0, first.0, first.1, first.1),
+            |(min_x, max_x, min_y, max_y), (x, y)| (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+        );
+
+        let map_width = width as f64 - 2.0 * padding;
+        let map_height = height as f64 - 2.0 * padding;
+        let x_range = max_x - min_x;
+        let y_range = max_y - min_y;
+
+        let scale_x = if x_range.abs() > 1e-9 { map_width / x_range } else { 0.0 };
+        let scale_y = if y_range.abs() > 1e-9 { map_height / y_range } else { 0.0 };
+        let scale = match kind {
+            MapProjectionKind::Equirectangular => scale_x.min(scale_y),
+            // Os tiles precisam de escala mesmo numa trilha só na vertical ou na horizontal
+            MapProjectionKind::WebMercator => match (scale_x > 0.0, scale_y > 0.0) {
+                (true, true) => scale_x.min(scale_y),
+                (true, false) => scale_x,
+                (false, true) => scale_y,
+                (false, false) => SINGLE_POINT_MERCATOR_SCALE,
+            },
+        };
+        Some(MapProjection { kind, min_x, min_y, scale, padding })
+    }
+
+    pub fn kind(&self) -> MapProjectionKind {
+        self.kind
+    }
+
+    /// Pixels por unidade do plano; na Web Mercator, a largura do mundo em pixels
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Coordenadas em pixels de uma posição, a partir do canto superior esquerdo do mapa
+    pub fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (x, y) = to_plane(self.kind, lon, lat);
+        (
+            self.padding + (x - self.min_x) * self.scale,
+            self.padding + (y - self.min_y) * self.scale,
+        )
+    }
+
+    /// Posição no plano (mundo Web Mercator de 0 a 1) do pixel `(x, y)`, o inverso de `project`
+    pub fn plane_at(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.min_x + (x - self.padding) / self.scale,
+            self.min_y + (y - self.padding) / self.scale,
+        )
+    }
+}
+
+/// Plano da projeção, com y a crescer para sul
+fn to_plane(kind: MapProjectionKind, lon: f64, lat: f64) -> (f64, f64) {
+    match kind {
+        MapProjectionKind::Equirectangular => (lon, -lat),
+        MapProjectionKind::WebMercator => {
+            // Limite de latitude da Web Mercator, onde o mundo fica quadrado
+            let lat = lat.clamp(-85.051_128_78, 85.051_128_78).to_radians();
+            let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0;
+            ((lon + 180.0) / 360.0, y)
+        },
+    }
+}
+
+/// Atribuição dos tiles (por exemplo "© OpenStreetMap") no canto inferior direito do mapa,
+/// numa faixa escura; a letra diminui até caber na largura
+pub fn draw_map_attribution(image: &mut RgbaImage, text: &str, theme: &Theme, scale: f32) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    let padding = (3.0 * scale).max(1.0);
+    let mut size = (11.0 * scale).max(7.0);
+    let available = image.width() as f32 - 2.0 * padding;
+    let width = text_width(&theme.font, Scale::uniform(size), text);
+    if width > available && width > 0.0 {
+        size *= available / width;
+    }
+    let font_scale = Scale::uniform(size);
+    let width = text_width(&theme.font, font_scale, text).ceil() as u32;
+    let height = (size + padding).ceil() as u32;
+    let x = image.width().saturating_sub(width + 2 * padding as u32);
+    let y = image.height().saturating_sub(height);
+    for py in y..image.height() {
+        for px in x..image.width() {
+            let under = *image.get_pixel(px, py);
+            image.put_pixel(px, py, blend_over(Rgba([0, 0, 0, 255]), 0.55, under));
+        }
+    }
+    draw_text_mut(image, theme.text_color, (x as f32 + padding) as i32, (y as f32 + padding / 2.0) as i32, font_scale, &theme.font, text);
+}
+
+// FUNÇÃO MODIFICADA: `render_track_map_image` agora usa o gradiente de cores
+pub fn render_track_map_image(
     gpx: &Gpx,
+    projection: &MapProjection,
     width: u32,
     height: u32,
-    path: &str,
     background_color: Rgba<u8>,
     line_thickness: f32,
-) -> Result<(), Box<dyn Error>> {
+) -> Result<RgbaImage, Box<dyn Error>> {
     let all_points: Vec<_> = gpx.tracks.iter()
         .flat_map(|t| t.segments.iter())
         .flat_map(|s| s.points.iter())
@@ -353,28 +751,10 @@ pub fn generate_track_map_image(
     let min_speed = if valid_speeds.is_empty() { 0.0 } else { valid_speeds.iter().fold(f64::INFINITY, |a, &b| a.min(b)) };
     let max_speed = if valid_speeds.is_empty() { 0.0 } else { valid_speeds.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b)) };
 
-    let (min_lon, max_lon, min_lat, max_lat) = all_points.iter().fold(
-        (all_points[0].point().x(), all_points[0].point().x(), all_points[0].point().y(), all_points[0].point().y()),
-        |(min_x, max_x, min_y, max_y), p| {
-            (min_x.min(p.point().x()), max_x.max(p.point().x()), min_y.min(p.point().y()), max_y.max(p.point().y()))
-        }
-    );
-
     let mut img = RgbaImage::from_pixel(width, height, background_color);
-    let padding = 20.0;
-    let map_width = width as f64 - 2.0 * padding;
-    let map_height = height as f64 - 2.0 * padding;
-
-    let lon_range = max_lon - min_lon;
-    let lat_range = max_lat - min_lat;
-
-    let scale_x = if lon_range.abs() > 1e-9 { map_width / lon_range } else { 0.0 };
-    let scale_y = if lat_range.abs() > 1e-9 { map_height / lat_range } else { 0.0 };
-    let scale = scale_x.min(scale_y);
 
     let get_pixel_coords = |lon: f64, lat: f64| -> (f32, f32) {
-        let x = padding + (lon - min_lon) * scale;
-        let y = padding + (max_lat - lat) * scale;
+        let (x, y) = projection.project(lon, lat);
         (x as f32, y as f32)
     };
 
@@ -398,11 +778,191 @@ pub fn generate_track_map_image(
         }
     }
 
-    img.save(path)?;
-    Ok(())
+    Ok(img)
 }
 
 
+/// Eixos do perfil de elevação numa imagem de `width` x `height`: distância na horizontal e
+/// altitude na vertical, partilhados pela imagem base e pelo marcador de cada frame
+#[derive(Debug, Clone, Copy)]
+pub struct ProfileProjection {
+    start_km: f64,
+    total_km: f64,
+    low_m: f64,
+    high_m: f64,
+    /// Área do gráfico: esquerda, topo, direita e base, em pixels
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+}
+
+impl ProfileProjection {
+    /// Altitudes com menos de `MIN_RANGE_M` de variação não são esticadas até à altura toda,
+    /// e a mais baixa fica um pouco acima da base para o preenchimento continuar visível
+    pub fn new(profile: &ElevationProfile, width: u32, height: u32, scale: f32) -> Self {
+        const MIN_RANGE_M: f64 = 20.0;
+        let scale = scale as f64;
+        let (min, max) = (profile.min_elevation(), profile.max_elevation());
+        let range = (max - min).max(MIN_RANGE_M);
+        let middle = (min + max) / 2.0;
+        let (low, high) = if max - min < MIN_RANGE_M { (middle - range / 2.0, middle + range / 2.0) } else { (min, max) };
+        ProfileProjection {
+            start_km: profile.start_km(),
+            total_km: profile.total_km(),
+            low_m: low - range * 0.1,
+            high_m: high,
+            left: PROFILE_PLOT_LEFT * scale,
+            top: PROFILE_PLOT_TOP * scale,
+            right: width as f64 - PROFILE_PADDING * scale,
+            bottom: height as f64 - PROFILE_PADDING * scale,
+        }
+    }
+
+    /// Coordenadas em pixels de uma altitude a `km` do início da trilha
+    pub fn project(&self, km: f64, elevation_m: f64) -> (f64, f64) {
+        let x_ratio = ((km - self.start_km) / self.total_km).clamp(0.0, 1.0);
+        let y_ratio = ((elevation_m - self.low_m) / (self.high_m - self.low_m)).clamp(0.0, 1.0);
+        (
+            self.left + x_ratio * (self.right - self.left),
+            self.bottom - y_ratio * (self.bottom - self.top),
+        )
+    }
+
+    /// Distância do início da trilha que corresponde à coluna `x`
+    pub fn km_at(&self, x: f64) -> f64 {
+        let ratio = ((x - self.left) / (self.right - self.left)).clamp(0.0, 1.0);
+        self.start_km + ratio * self.total_km
+    }
+
+    /// Colunas de pixels do gráfico
+    fn columns(&self) -> std::ops::Range<u32> {
+        self.left.floor() as u32..self.right.ceil() as u32
+    }
+}
+
+/// Medidas do perfil no tamanho natural (480x160): cabeçalho com os valores atuais e, por
+/// baixo, o gráfico com as altitudes máxima e mínima à esquerda
+const PROFILE_PLOT_LEFT: f64 = 60.0;
+const PROFILE_PLOT_TOP: f64 = 40.0;
+const PROFILE_PADDING: f64 = 10.0;
+
+/// Perfil de elevação completo, desenhado uma só vez no tamanho final. Com `done` é a versão
+/// do trecho já percorrido: preenchida com a cor de destaque ou, com `color_by_gradient`, com a
+/// cor da inclinação; sem ele o preenchimento fica esbatido e leva os rótulos das altitudes.
+#[allow(clippy::too_many_arguments)]
+pub fn render_elevation_profile_image(
+    profile: &ElevationProfile,
+    projection: &ProfileProjection,
+    width: u32,
+    height: u32,
+    theme: &Theme,
+    units: UnitSystem,
+    color_by_gradient: bool,
+    done: bool,
+    scale: f32,
+) -> RgbaImage {
+    let background = if done { Rgba([0, 0, 0, 0]) } else { theme.background_color };
+    let mut img = RgbaImage::from_pixel(width, height, background);
+    let pending_fill = blend_over(theme.text_color, 0.3, theme.background_color);
+
+    let mut previous_top: Option<(f32, f32)> = None;
+    for x in projection.columns() {
+        let km = projection.km_at(x as f64 + 0.5);
+        let (_, y) = projection.project(km, profile.elevation_at(km));
+        let fill = match (done, color_by_gradient) {
+            (false, _) => pending_fill,
+            (true, true) => gradient_color(profile.gradient_at(km)),
+            (true, false) => theme.accent_color,
+        };
+        let column_height = (projection.bottom - y).round().max(1.0) as u32;
+        draw_filled_rect_mut(&mut img, imageproc::rect::Rect::at(x as i32, y.round() as i32).of_size(1, column_height), fill);
+        let top = (x as f32 + 0.5, y as f32);
+        if let Some(previous) = previous_top {
+            draw_thick_line_segment_mut(&mut img, previous, top, (2.0 * scale).max(1.0), theme.text_color);
+        }
+        previous_top = Some(top);
+    }
+
+    if !done {
+        let label_scale = Scale::uniform(13.0 * scale);
+        let label = |elevation: f64| format!("{:.0} {}", units.elevation(elevation), units.elevation_unit());
+        let (_, max_y) = projection.project(profile.start_km(), profile.max_elevation());
+        let (_, min_y) = projection.project(profile.start_km(), profile.min_elevation());
+        let x = (PROFILE_PADDING as f32 * scale) as i32;
+        draw_text_mut(&mut img, theme.text_color, x, max_y as i32, label_scale, &theme.font, &label(profile.max_elevation()));
+        draw_text_mut(&mut img, theme.text_color, x, (min_y - 13.0 * scale as f64) as i32, label_scale, &theme.font, &label(profile.min_elevation()));
+    }
+    img
+}
+
+/// Frame do perfil a `km` do início: as colunas já percorridas vêm de `done`, com um marcador
+/// na posição atual e, no cabeçalho, a altitude, a inclinação e a distância percorrida
+#[allow(clippy::too_many_arguments)]
+pub fn render_elevation_progress_image(
+    base: &RgbaImage,
+    done: &RgbaImage,
+    profile: &ElevationProfile,
+    projection: &ProfileProjection,
+    km: f64,
+    theme: &Theme,
+    units: UnitSystem,
+    scale: f32,
+) -> RgbaImage {
+    let mut img = base.clone();
+    let elevation = profile.elevation_at(km);
+    let (marker_x, marker_y) = projection.project(km, elevation);
+    let left = projection.columns().start;
+    let done_width = (marker_x.round() as u32).saturating_sub(left).min(done.width().saturating_sub(left));
+    if done_width > 0 {
+        let done_part = image::imageops::crop_imm(done, left, 0, done_width, done.height()).to_image();
+        image::imageops::overlay(&mut img, &done_part, left as i64, 0);
+    }
+
+    let line_width = (2.0 * scale).max(1.0);
+    draw_thick_line_segment_mut(&mut img, (marker_x as f32, marker_y as f32), (marker_x as f32, projection.bottom as f32), line_width, theme.text_color);
+    let center = (marker_x.round() as i32, marker_y.round() as i32);
+    draw_filled_circle_mut(&mut img, center, ((6.0 * scale).round() as i32).max(2), theme.text_color);
+    draw_filled_circle_mut(&mut img, center, ((3.5 * scale).round() as i32).max(1), theme.accent_color);
+
+    let header_scale = Scale::uniform(20.0 * scale);
+    let margin = (PROFILE_PADDING as f32 * scale) as i32;
+    let current = format!(
+        "{:.0} {}  {:+.1}%",
+        units.elevation(elevation),
+        units.elevation_unit(),
+        profile.gradient_at(km),
+    );
+    draw_text_mut(&mut img, theme.text_color, margin, margin, header_scale, &theme.bold_font, &current);
+    let distance = format!(
+        "{:.1} / {:.1} {}",
+        units.distance(km - profile.start_km()),
+        units.distance(profile.total_km()),
+        units.distance_unit(),
+    );
+    let distance_x = img.width() as i32 - margin - text_width(&theme.font, header_scale, &distance).ceil() as i32;
+    draw_text_mut(&mut img, theme.text_color, distance_x, margin, header_scale, &theme.font, &distance);
+    img
+}
+
+/// `color` com opacidade `alpha` sobre `background`, já composta (o desenho substitui pixels)
+fn blend_over(color: Rgba<u8>, alpha: f32, background: Rgba<u8>) -> Rgba<u8> {
+    let background_alpha = background.0[3] as f32 / 255.0;
+    let out_alpha = alpha + background_alpha * (1.0 - alpha);
+    if out_alpha <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let channel = |i: usize| ((color.0[i] as f32 * alpha + background.0[i] as f32 * background_alpha * (1.0 - alpha)) / out_alpha).round() as u8;
+    Rgba([channel(0), channel(1), channel(2), (out_alpha * 255.0).round() as u8])
+}
+
+/// Largura de uma linha de texto em pixels
+fn text_width(font: &Font, scale: Scale, text: &str) -> f32 {
+    font.layout(text, scale, rusttype::point(0.0, 0.0))
+        .map(|g| g.unpositioned().h_metrics().advance_width)
+        .sum()
+}
+
 fn draw_arc_mut(
     image: &mut RgbaImage,
     center: (i32, i32),
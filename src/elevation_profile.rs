@@ -0,0 +1,147 @@
+// src/elevation_profile.rs - Perfil de elevação da trilha: altitude por distância percorrida
+
+use chrono::{DateTime, Utc};
+use gpx::{Gpx, Waypoint};
+use image::Rgba;
+use crate::utils::distance_2d;
+
+/// Meia janela, em km, sobre a qual a inclinação é medida; evita que o ruído da altitude entre
+/// pontos próximos apareça como rampas de 30%
+const GRADIENT_HALF_WINDOW_KM: f64 = 0.05;
+
+/// Limites superiores (em %) das faixas de inclinação de `gradient_color`
+const GRADIENT_BANDS: [(f64, Rgba<u8>); 5] = [
+    (-1.0, Rgba([33, 150, 243, 255])),  // Descida: azul
+    (2.0, Rgba([76, 175, 80, 255])),    // Plano: verde
+    (5.0, Rgba([255, 235, 59, 255])),   // Amarelo
+    (8.0, Rgba([255, 152, 0, 255])),    // Laranja
+    (12.0, Rgba([244, 67, 54, 255])),   // Vermelho
+];
+/// Acima de 12%
+const STEEPEST_COLOR: Rgba<u8> = Rgba([136, 14, 79, 255]);
+
+/// Altitude de cada ponto da trilha em função da distância acumulada desde o primeiro ponto
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElevationProfile {
+    distances_km: Vec<f64>,
+    elevations_m: Vec<f64>,
+    /// Distância acumulada dos pontos com hora, por ordem, para posicionar o marcador
+    timeline: Vec<(DateTime<Utc>, f64)>,
+    min_m: f64,
+    max_m: f64,
+}
+
+impl ElevationProfile {
+    /// Perfil dos pontos de `gpx` (a mesma trilha interpolada do mini-mapa). Pontos sem altitude
+    /// repetem a anterior; os saltos entre segmentos não contam na distância. `None` se nenhum
+    /// ponto tiver altitude ou a trilha não sair do lugar.
+    pub fn new(gpx: &Gpx) -> Option<Self> {
+        let mut profile = ElevationProfile {
+            distances_km: Vec::new(),
+            elevations_m: Vec::new(),
+            timeline: Vec::new(),
+            min_m: f64::INFINITY,
+            max_m: f64::NEG_INFINITY,
+        };
+        let mut distance_km = 0.0;
+        let mut last_elevation = None;
+        for segment in gpx.tracks.iter().flat_map(|t| t.segments.iter()) {
+            let mut previous: Option<&Waypoint> = None;
+            for point in &segment.points {
+                if let Some(previous) = previous {
+                    distance_km += distance_2d(previous, point) / 1000.0;
+                }
+                previous = Some(point);
+                let Some(elevation) = point.elevation.or(last_elevation) else { continue };
+                last_elevation = Some(elevation);
+                profile.distances_km.push(distance_km);
+                profile.elevations_m.push(elevation);
+                profile.min_m = profile.min_m.min(elevation);
+                profile.max_m = profile.max_m.max(elevation);
+                if let Some(time) = point_time(point) {
+                    if profile.timeline.last().is_none_or(|(last, _)| time >= *last) {
+                        profile.timeline.push((time, distance_km));
+                    }
+                }
+            }
+        }
+        (profile.total_km() > 0.0).then_some(profile)
+    }
+
+    /// Distância do primeiro ao último ponto com altitude
+    pub fn total_km(&self) -> f64 {
+        match (self.distances_km.first(), self.distances_km.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+
+    /// Distância acumulada no início do perfil (pontos iniciais sem altitude ficam de fora)
+    pub fn start_km(&self) -> f64 {
+        self.distances_km.first().copied().unwrap_or(0.0)
+    }
+
+    pub fn min_elevation(&self) -> f64 {
+        self.min_m
+    }
+
+    pub fn max_elevation(&self) -> f64 {
+        self.max_m
+    }
+
+    /// Altitude interpolada a `km` do início da trilha, limitada às pontas do perfil
+    pub fn elevation_at(&self, km: f64) -> f64 {
+        let index = self.distances_km.partition_point(|&d| d < km);
+        if index == 0 {
+            return self.elevations_m[0];
+        }
+        if index == self.distances_km.len() {
+            return self.elevations_m[index - 1];
+        }
+        let (d1, d2) = (self.distances_km[index - 1], self.distances_km[index]);
+        let (e1, e2) = (self.elevations_m[index - 1], self.elevations_m[index]);
+        if d2 - d1 <= f64::EPSILON { e2 } else { e1 + (e2 - e1) * (km - d1) / (d2 - d1) }
+    }
+
+    /// Inclinação em % a `km` do início, medida em `GRADIENT_HALF_WINDOW_KM` para cada lado
+    pub fn gradient_at(&self, km: f64) -> f64 {
+        let from = (km - GRADIENT_HALF_WINDOW_KM).max(self.start_km());
+        let to = (km + GRADIENT_HALF_WINDOW_KM).min(self.start_km() + self.total_km());
+        if to - from <= f64::EPSILON {
+            return 0.0;
+        }
+        (self.elevation_at(to) - self.elevation_at(from)) / ((to - from) * 1000.0) * 100.0
+    }
+
+    /// Distância percorrida no instante `time`, interpolada entre os pontos com hora; `None`
+    /// se a trilha não tiver horas
+    pub fn distance_at(&self, time: DateTime<Utc>) -> Option<f64> {
+        let index = self.timeline.partition_point(|(t, _)| *t < time);
+        if index == 0 {
+            return self.timeline.first().map(|(_, km)| *km);
+        }
+        let Some(&(t2, d2)) = self.timeline.get(index) else {
+            return self.timeline.last().map(|(_, km)| *km);
+        };
+        let (t1, d1) = self.timeline[index - 1];
+        let span = (t2 - t1).num_milliseconds() as f64;
+        if span <= 0.0 {
+            return Some(d2);
+        }
+        Some(d1 + (d2 - d1) * (time - t1).num_milliseconds() as f64 / span)
+    }
+}
+
+/// Cor da faixa de inclinação: azul a descer, verde no plano, depois amarelo (2%), laranja
+/// (5%), vermelho (8%) e bordô acima de 12%
+pub fn gradient_color(gradient_percent: f64) -> Rgba<u8> {
+    GRADIENT_BANDS.iter()
+        .find(|(limit, _)| gradient_percent < *limit)
+        .map_or(STEEPEST_COLOR, |(_, color)| *color)
+}
+
+fn point_time(point: &Waypoint) -> Option<DateTime<Utc>> {
+    point.time.as_ref()
+        .and_then(|t| t.format().ok())
+        .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+}
@@ -0,0 +1,224 @@
+// src/fit_adapter.rs - Módulo para leitura de arquivos FIT (Garmin, Wahoo, Coros) e conversão para GPX
+
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use chrono::Utc;
+use fitparser::profile::MesgNum;
+use fitparser::{FitDataRecord, Value};
+use gpx::{Gpx, Track, TrackSegment, Waypoint, GpxVersion};
+use geo_types::Point;
+use crate::tcx_adapter::{map_sport_to_track_type, TcxExtraData};
+use crate::telemetry::{AltitudeSource, TelemetrySample};
+
+/// Fator de conversão de semicírculos (unidade de posição do FIT) para graus
+const SEMICIRCLES_TO_DEGREES: f64 = 180.0 / 2_147_483_648.0;
+
+/// Estrutura para o resultado do processamento de um arquivo FIT
+pub struct FitProcessResult {
+    pub gpx: Gpx,
+    pub extra_data: TcxExtraData,
+    /// Telemetria por ponto, paralela aos pontos de `gpx`
+    pub telemetry: Vec<TelemetrySample>,
+}
+
+/// Lê e processa um arquivo FIT, retornando a estrutura GPX e os dados extras no mesmo
+/// formato produzido pelo adaptador TCX.
+pub fn read_and_process_fit(path: &Path) -> Result<FitProcessResult, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let records = fitparser::from_reader(&mut reader)?;
+
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        creator: Some("GPX Video Sync - FIT Adapter".to_string()),
+        ..Default::default()
+    };
+    let mut extra_data = TcxExtraData::default();
+    let mut telemetry = Vec::new();
+    let mut has_barometer = false;
+
+    let mut track = Track::new();
+    let mut segment = TrackSegment::new();
+    let mut has_session = false;
+    let mut lap_totals = TcxExtraData::default();
+
+    for record in &records {
+        match record.kind() {
+            MesgNum::FileId if extra_data.device_name.is_none() => {
+                extra_data.device_name = device_name_from_record(record);
+            },
+            MesgNum::DeviceInfo if field_string(record, "local_device_type").as_deref() == Some("barometer") => {
+                has_barometer = true;
+            },
+            MesgNum::DeviceInfo if is_creator_device(record) => {
+                // O dispositivo "creator" é o gravador; sensores (cinta, medidor de potência) são ignorados
+                if let Some(name) = field_string(record, "product_name") {
+                    extra_data.device_name = Some(name);
+                } else if extra_data.device_name.is_none() {
+                    extra_data.device_name = device_name_from_record(record);
+                }
+            },
+            MesgNum::Record => {
+                if let Some((waypoint, sample)) = record_to_waypoint(record, &mut extra_data) {
+                    segment.points.push(waypoint);
+                    telemetry.push(sample);
+                }
+            },
+            MesgNum::Lap => {
+                // Fecha o segmento atual a cada volta, tal como o adaptador TCX
+                if !segment.points.is_empty() {
+                    track.segments.push(std::mem::replace(&mut segment, TrackSegment::new()));
+                }
+                accumulate_totals(record, &mut lap_totals);
+            },
+            MesgNum::Session => {
+                has_session = true;
+                accumulate_totals(record, &mut extra_data);
+                if let Some(sport) = field_string(record, "sport") {
+                    extra_data.sport = Some(map_fit_sport(&sport));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    if !segment.points.is_empty() {
+        track.segments.push(segment);
+    }
+
+    // Arquivos sem mensagem de sessão (ex.: gravações interrompidas) usam os totais das voltas
+    if !has_session {
+        extra_data.total_time_seconds = lap_totals.total_time_seconds;
+        extra_data.total_distance_meters = lap_totals.total_distance_meters;
+        extra_data.total_calories = lap_totals.total_calories;
+        extra_data.max_speed = lap_totals.max_speed;
+    }
+
+    if !track.segments.is_empty() {
+        track.name = extra_data.device_name.clone();
+        track.type_ = extra_data.sport.as_deref().map(map_sport_to_track_type);
+        gpx.tracks.push(track);
+    }
+
+    // A mensagem de barómetro pode vir depois dos registos, por isso a origem é aplicada no fim
+    let altitude_source = if has_barometer { AltitudeSource::Barometric } else { AltitudeSource::Gps };
+    for sample in telemetry.iter_mut() {
+        sample.altitude_source = Some(altitude_source);
+    }
+
+    Ok(FitProcessResult { gpx, extra_data, telemetry })
+}
+
+/// Converte uma mensagem `record` em Waypoint e na respetiva amostra de telemetria.
+/// Registros sem posição GPS (ex.: antes do fix) contribuem apenas para as estatísticas.
+fn record_to_waypoint(record: &FitDataRecord, extra_data: &mut TcxExtraData) -> Option<(Waypoint, TelemetrySample)> {
+    let mut sample = TelemetrySample::default();
+
+    if let Some(heart_rate) = field_f64(record, "heart_rate") {
+        extra_data.heart_rate_data.push(heart_rate);
+        sample.heart_rate = Some(heart_rate);
+    }
+
+    if let Some(cadence) = field_f64(record, "cadence") {
+        extra_data.cadence_data.push(cadence);
+        sample.cadence = Some(cadence);
+    }
+
+    if let Some(speed) = field_f64(record, "enhanced_speed").or_else(|| field_f64(record, "speed")) {
+        extra_data.speed_data.push(speed);
+        sample.speed_mps = Some(speed);
+    }
+
+    if let Some(power) = field_f64(record, "power") {
+        extra_data.power_data.push(power);
+        sample.power = Some(power);
+    }
+
+    if let Some(temperature) = field_f64(record, "temperature") {
+        extra_data.temperature_data.push(temperature);
+        sample.temperature = Some(temperature);
+    }
+
+    let lat = field_f64(record, "position_lat")? * SEMICIRCLES_TO_DEGREES;
+    let lon = field_f64(record, "position_long")? * SEMICIRCLES_TO_DEGREES;
+
+    let mut waypoint = Waypoint::new(Point::new(lon, lat));
+    waypoint.elevation = field_f64(record, "enhanced_altitude").or_else(|| field_f64(record, "altitude"));
+
+    if let Some(Value::Timestamp(timestamp)) = field_value(record, "timestamp") {
+        let utc_time = timestamp.with_timezone(&Utc);
+        if let Ok(offset_dt) = time::OffsetDateTime::from_unix_timestamp(utc_time.timestamp()) {
+            waypoint.time = Some(gpx::Time::from(offset_dt));
+        }
+    }
+
+    Some((waypoint, sample))
+}
+
+/// Soma os totais de uma mensagem `lap` ou `session` e atualiza a velocidade máxima.
+fn accumulate_totals(record: &FitDataRecord, totals: &mut TcxExtraData) {
+    totals.total_time_seconds += field_f64(record, "total_timer_time")
+        .or_else(|| field_f64(record, "total_elapsed_time"))
+        .unwrap_or(0.0);
+    totals.total_distance_meters += field_f64(record, "total_distance").unwrap_or(0.0);
+    totals.total_calories += field_f64(record, "total_calories").unwrap_or(0.0);
+
+    if let Some(max_speed) = field_f64(record, "enhanced_max_speed").or_else(|| field_f64(record, "max_speed")) {
+        if max_speed > totals.max_speed {
+            totals.max_speed = max_speed;
+        }
+    }
+}
+
+/// Mensagens `device_info` sem índice ou com índice "creator" descrevem o próprio gravador.
+fn is_creator_device(record: &FitDataRecord) -> bool {
+    match field_value(record, "device_index") {
+        None => true,
+        Some(Value::String(index)) => index == "creator",
+        Some(value) => matches!(value.clone().try_into(), Ok(0i64)),
+    }
+}
+
+/// Monta um nome legível do dispositivo a partir do fabricante e do produto.
+fn device_name_from_record(record: &FitDataRecord) -> Option<String> {
+    let manufacturer = field_string(record, "manufacturer")?;
+    let product = field_string(record, "garmin_product")
+        .or_else(|| field_string(record, "product"));
+
+    Some(match product {
+        Some(product) => format!("{} {}", manufacturer, product),
+        None => manufacturer,
+    })
+}
+
+/// Mapeia os tipos de esporte do perfil FIT para os nomes usados pelo TCX
+fn map_fit_sport(sport: &str) -> String {
+    match sport {
+        "running" => "Running".to_string(),
+        "cycling" | "e_biking" => "Biking".to_string(),
+        "walking" => "Walking".to_string(),
+        "hiking" => "Hiking".to_string(),
+        "swimming" => "Swimming".to_string(),
+        _ => sport.to_string(),
+    }
+}
+
+fn field_value<'a>(record: &'a FitDataRecord, name: &str) -> Option<&'a Value> {
+    record.fields().iter().find(|f| f.name() == name).map(|f| f.value())
+}
+
+fn field_f64(record: &FitDataRecord, name: &str) -> Option<f64> {
+    match field_value(record, name)? {
+        Value::Invalid | Value::String(_) | Value::Array(_) | Value::Timestamp(_) => None,
+        value => value.clone().try_into().ok(),
+    }
+}
+
+fn field_string(record: &FitDataRecord, name: &str) -> Option<String> {
+    match field_value(record, name)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Invalid | Value::String(_) => None,
+        value => Some(value.to_string()),
+    }
+}
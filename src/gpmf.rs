@@ -0,0 +1,404 @@
+// src/gpmf.rs - Leitura da telemetria GPMF (GoPro) embutida na faixa `gpmd` do MP4
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::Command as StdCommand;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use gpx::{Gpx, Track, TrackSegment, Waypoint, GpxVersion};
+use geo_types::Point;
+use crate::tcx_adapter::TcxExtraData;
+use crate::telemetry::{AltitudeSource, TelemetrySample};
+use crate::utils::distance_2d;
+
+/// O GPS da GoPro grava a 10-18 Hz; o pipeline gera um frame de overlay por ponto,
+/// por isso a trilha é reduzida para no máximo um ponto por segundo de vídeo.
+const MIN_POINT_INTERVAL_SECS: f64 = 1.0;
+
+/// Aceleração da gravidade padrão (m/s²), usada para converter o ACCL em g
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// Um pacote da faixa `gpmd`, com a sua posição na linha do tempo do vídeo
+pub struct GpmfPayload {
+    pub video_time_secs: f64,
+    pub duration_secs: f64,
+    pub data: Vec<u8>,
+}
+
+/// Leitura do acelerómetro (m/s²) nos três eixos da câmara
+struct AccelSample {
+    video_time_secs: f64,
+    values: [f64; 3],
+}
+
+/// Estrutura para o resultado do processamento da telemetria GPMF
+pub struct GpmfProcessResult {
+    pub gpx: Gpx,
+    pub extra_data: TcxExtraData,
+    /// Telemetria por ponto, paralela aos pontos de `gpx`; inclui a força G do acelerómetro
+    pub telemetry: Vec<TelemetrySample>,
+    /// Início do vídeo em UTC, derivado do relógio do GPS (mais preciso que `creation_time`)
+    pub video_start_time: Option<DateTime<Utc>>,
+}
+
+/// Posição do GPS já convertida, com o instante UTC e o instante no vídeo
+struct GpsFix {
+    lat: f64,
+    lon: f64,
+    altitude: f64,
+    speed_mps: f64,
+    utc: DateTime<Utc>,
+    video_time_secs: f64,
+}
+
+/// Um item KLV do GPMF: FourCC, tipo, tamanho da estrutura e número de repetições
+struct Klv<'a> {
+    key: &'a [u8],
+    value_type: u8,
+    struct_size: usize,
+    repeat: usize,
+    data: &'a [u8],
+}
+
+/// Estado acumulado dentro de um `STRM`: os modificadores valem para os dados seguintes
+#[derive(Default)]
+struct StreamState {
+    scale: Vec<f64>,
+    complex_type: Vec<u8>,
+    gps_fix: Option<u32>,
+    gps_time: Option<DateTime<Utc>>,
+}
+
+/// Lê a telemetria GPMF de um vídeo GoPro e a converte para o mesmo modelo
+/// produzido pelos adaptadores de trilha.
+pub fn read_and_process_gpmf(video_path: &Path) -> Result<GpmfProcessResult, Box<dyn Error>> {
+    let payloads = read_gpmf_payloads(video_path)?;
+    decode_gpmf(&payloads)
+}
+
+/// Localiza a faixa `gpmd` com o ffprobe e lê do arquivo os bytes de cada pacote.
+pub fn read_gpmf_payloads(video_path: &Path) -> Result<Vec<GpmfPayload>, Box<dyn Error>> {
+    let metadata = ffprobe::ffprobe(video_path)
+        .map_err(|e| format!("Erro ao executar o ffprobe: {}", e))?;
+
+    let stream = metadata.streams.iter()
+        .find(|s| {
+            s.codec_tag_string == "gpmd"
+                || s.tags.as_ref().and_then(|t| t.handler_name.as_deref()).is_some_and(|h| h.contains("GoPro MET"))
+        })
+        .ok_or("Faixa de telemetria GPMF (gpmd) não encontrada no vídeo.")?;
+
+    let output = StdCommand::new("ffprobe")
+        .args(["-v", "error", "-select_streams"])
+        .arg(stream.index.to_string())
+        .args(["-show_entries", "packet=pts_time,duration_time,size,pos", "-of", "compact=p=0"])
+        .arg(video_path)
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("Erro ao listar os pacotes GPMF: {}", String::from_utf8_lossy(&output.stderr)).into());
+    }
+
+    let mut file = File::open(video_path)?;
+    let mut payloads = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let entry = |key: &str| line.split('|')
+            .filter_map(|kv| kv.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.to_string());
+
+        let (Some(pos), Some(size)) = (
+            entry("pos").and_then(|v| v.parse::<u64>().ok()),
+            entry("size").and_then(|v| v.parse::<usize>().ok()),
+        ) else { continue };
+
+        let mut data = vec![0u8; size];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut data)?;
+
+        payloads.push(GpmfPayload {
+            video_time_secs: entry("pts_time").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            duration_secs: entry("duration_time").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+            data,
+        });
+    }
+
+    Ok(payloads)
+}
+
+/// Decodifica os pacotes GPMF (GPS5/GPS9, ACCL) numa trilha GPX com telemetria.
+/// As amostras de cada pacote são distribuídas uniformemente pela duração do pacote.
+pub fn decode_gpmf(payloads: &[GpmfPayload]) -> Result<GpmfProcessResult, Box<dyn Error>> {
+    let mut device_name = None;
+    let mut fixes = Vec::new();
+    let mut accelerometer = Vec::new();
+
+    for payload in payloads {
+        for devc in parse_klv(&payload.data)?.into_iter().filter(|k| k.key == b"DEVC" && k.value_type == 0) {
+            for item in parse_klv(devc.data)? {
+                match item.key {
+                    b"DVNM" if device_name.is_none() => device_name = Some(read_string(item.data)),
+                    b"STRM" if item.value_type == 0 => {
+                        decode_stream(item.data, payload, &mut fixes, &mut accelerometer)?;
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fixes.sort_by(|a, b| a.video_time_secs.total_cmp(&b.video_time_secs));
+    accelerometer.sort_by(|a, b| a.video_time_secs.total_cmp(&b.video_time_secs));
+    let gravity = mean_vector(&accelerometer);
+
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        creator: Some("GPX Video Sync - GPMF Adapter".to_string()),
+        ..Default::default()
+    };
+    let mut extra_data = TcxExtraData { device_name: device_name.clone(), ..Default::default() };
+    let mut telemetry = Vec::new();
+    let mut segment = TrackSegment::new();
+    let mut video_start_time = None;
+    let mut last_kept_time: Option<f64> = None;
+
+    for fix in &fixes {
+        if last_kept_time.is_some_and(|t| fix.video_time_secs - t < MIN_POINT_INTERVAL_SECS - 1e-6) {
+            continue;
+        }
+        last_kept_time = Some(fix.video_time_secs);
+
+        if video_start_time.is_none() {
+            video_start_time = Some(fix.utc - Duration::microseconds((fix.video_time_secs * 1_000_000.0) as i64));
+        }
+
+        let mut waypoint = Waypoint::new(Point::new(fix.lon, fix.lat));
+        waypoint.elevation = Some(fix.altitude);
+        if let Some(nanos) = fix.utc.timestamp_nanos_opt() {
+            if let Ok(offset_dt) = time::OffsetDateTime::from_unix_timestamp_nanos(nanos as i128) {
+                waypoint.time = Some(gpx::Time::from(offset_dt));
+            }
+        }
+
+        if let Some(previous) = segment.points.last() {
+            extra_data.total_distance_meters += distance_2d(previous, &waypoint);
+        }
+        extra_data.speed_data.push(fix.speed_mps);
+        extra_data.max_speed = extra_data.max_speed.max(fix.speed_mps);
+
+        segment.points.push(waypoint);
+        telemetry.push(TelemetrySample {
+            speed_mps: Some(fix.speed_mps),
+            g_force: g_force_at(&accelerometer, gravity, fix.video_time_secs),
+            altitude_source: Some(AltitudeSource::Gps),
+            ..Default::default()
+        });
+    }
+
+    if let (Some(first), Some(last)) = (fixes.first(), fixes.last()) {
+        extra_data.total_time_seconds = last.video_time_secs - first.video_time_secs;
+    }
+
+    if !segment.points.is_empty() {
+        let mut track = Track::new();
+        track.name = device_name;
+        track.segments.push(segment);
+        gpx.tracks.push(track);
+    }
+
+    Ok(GpmfProcessResult { gpx, extra_data, telemetry, video_start_time })
+}
+
+/// Média dos vetores do acelerómetro; com a câmara presa ao veículo aproxima a gravidade
+/// no referencial da câmara, qualquer que seja a sua orientação.
+fn mean_vector(samples: &[AccelSample]) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for sample in samples {
+        for (total, value) in sum.iter_mut().zip(sample.values) {
+            *total += value;
+        }
+    }
+    sum.map(|total| total / samples.len().max(1) as f64)
+}
+
+/// Força G média no segundo de vídeo que começa em `start_secs`, já sem a gravidade.
+/// `None` quando o vídeo não tem leituras do acelerómetro nesse intervalo.
+fn g_force_at(samples: &[AccelSample], gravity: [f64; 3], start_secs: f64) -> Option<f64> {
+    let first = samples.partition_point(|s| s.video_time_secs < start_secs - 1e-6);
+    let window: Vec<f64> = samples[first..].iter()
+        .take_while(|s| s.video_time_secs < start_secs + MIN_POINT_INTERVAL_SECS - 1e-6)
+        .map(|s| {
+            let [x, y, z] = [0, 1, 2].map(|i| s.values[i] - gravity[i]);
+            (x * x + y * y + z * z).sqrt() / STANDARD_GRAVITY
+        })
+        .collect();
+
+    (!window.is_empty()).then(|| window.iter().sum::<f64>() / window.len() as f64)
+}
+
+/// Processa um `STRM`, aplicando os modificadores (SCAL, TYPE, GPSF, GPSU) aos dados que os seguem.
+fn decode_stream(
+    data: &[u8],
+    payload: &GpmfPayload,
+    fixes: &mut Vec<GpsFix>,
+    accelerometer: &mut Vec<AccelSample>,
+) -> Result<(), Box<dyn Error>> {
+    let mut state = StreamState::default();
+
+    for item in parse_klv(data)? {
+        match item.key {
+            b"SCAL" => state.scale = read_numbers(&item, &[]),
+            b"TYPE" => state.complex_type = item.data[..item.struct_size * item.repeat].to_vec(),
+            b"GPSF" => state.gps_fix = read_numbers(&item, &[]).first().map(|v| *v as u32),
+            b"GPSU" => state.gps_time = parse_gps_time(&read_string(item.data)),
+            b"GPS5" | b"GPS9" | b"ACCL" => {
+                let values = read_numbers(&item, &state.complex_type);
+                let per_sample = values.len() / item.repeat.max(1);
+                if per_sample == 0 { continue; }
+
+                for (k, raw) in values.chunks(per_sample).enumerate() {
+                    let sample: Vec<f64> = raw.iter().enumerate()
+                        .map(|(j, v)| v / scale_for(&state.scale, j))
+                        .collect();
+                    let offset = k as f64 * payload.duration_secs / item.repeat as f64;
+                    let video_time_secs = payload.video_time_secs + offset;
+
+                    match item.key {
+                        b"GPS5" if sample.len() >= 5 => {
+                            // GPS5 só traz a hora UTC do pacote (GPSU); sem fix 2D/3D as coordenadas são lixo
+                            if state.gps_fix.is_some_and(|f| f < 2) { continue; }
+                            let Some(gps_time) = state.gps_time else { continue };
+                            let utc = gps_time + Duration::microseconds((offset * 1_000_000.0) as i64);
+                            push_fix(fixes, &sample, utc, video_time_secs);
+                        },
+                        b"GPS9" if sample.len() >= 9 => {
+                            // GPS9 traz dias desde 2000-01-01, segundos do dia e o tipo de fix por amostra
+                            if sample[8] < 2.0 { continue; }
+                            let epoch = NaiveDate::from_ymd_opt(2000, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0));
+                            let Some(epoch) = epoch else { continue };
+                            // Dias fora do intervalo do chrono (pacote corrompido) descartam a amostra
+                            let utc = Duration::try_days(sample[5] as i64)
+                                .and_then(|days| epoch.and_utc().checked_add_signed(days))
+                                .and_then(|day| day.checked_add_signed(Duration::microseconds((sample[6] * 1_000_000.0) as i64)));
+                            let Some(utc) = utc else { continue };
+                            push_fix(fixes, &sample, utc, video_time_secs);
+                        },
+                        b"ACCL" if sample.len() >= 3 => {
+                            accelerometer.push(AccelSample { video_time_secs, values: [sample[0], sample[1], sample[2]] });
+                        },
+                        _ => {}
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn push_fix(fixes: &mut Vec<GpsFix>, sample: &[f64], utc: DateTime<Utc>, video_time_secs: f64) {
+    // Coordenadas (0, 0) indicam que o receptor ainda não tinha posição
+    if sample[0] == 0.0 && sample[1] == 0.0 { return; }
+    fixes.push(GpsFix {
+        lat: sample[0],
+        lon: sample[1],
+        altitude: sample[2],
+        speed_mps: sample[3],
+        utc,
+        video_time_secs,
+    });
+}
+
+/// Separa um bloco GPMF nos seus itens KLV do mesmo nível.
+fn parse_klv(mut data: &[u8]) -> Result<Vec<Klv<'_>>, Box<dyn Error>> {
+    let mut items = Vec::new();
+
+    while data.len() >= 8 {
+        let key = &data[0..4];
+        // Preenchimento no fim do pacote
+        if key == [0, 0, 0, 0] { break; }
+
+        let value_type = data[4];
+        let struct_size = data[5] as usize;
+        let repeat = u16::from_be_bytes([data[6], data[7]]) as usize;
+        let length = struct_size * repeat;
+        let padded = length.div_ceil(4) * 4;
+
+        if data.len() < 8 + padded {
+            return Err(format!("Bloco GPMF truncado em '{}'", String::from_utf8_lossy(key)).into());
+        }
+        items.push(Klv { key, value_type, struct_size, repeat, data: &data[8..8 + length] });
+        data = &data[8 + padded..];
+    }
+
+    Ok(items)
+}
+
+/// Lê os valores numéricos de um item. Tipos simples repetem o mesmo tipo por toda a
+/// estrutura; o tipo complexo `?` usa a descrição do modificador `TYPE` (ex.: "lllllllSS").
+fn read_numbers(item: &Klv, complex_type: &[u8]) -> Vec<f64> {
+    let field_types: Vec<u8> = if item.value_type == b'?' {
+        complex_type.to_vec()
+    } else {
+        match type_size(item.value_type) {
+            Some(size) if size > 0 => vec![item.value_type; item.struct_size / size],
+            _ => return Vec::new(),
+        }
+    };
+
+    let mut values = Vec::new();
+    for chunk in item.data.chunks_exact(item.struct_size.max(1)) {
+        let mut offset = 0;
+        for &field_type in &field_types {
+            let Some(size) = type_size(field_type) else { return values };
+            let Some(bytes) = chunk.get(offset..offset + size) else { break };
+            values.push(read_value(field_type, bytes));
+            offset += size;
+        }
+    }
+    values
+}
+
+fn type_size(value_type: u8) -> Option<usize> {
+    match value_type {
+        b'b' | b'B' | b'c' => Some(1),
+        b's' | b'S' => Some(2),
+        b'l' | b'L' | b'f' | b'F' => Some(4),
+        b'd' | b'j' | b'J' => Some(8),
+        _ => None,
+    }
+}
+
+fn read_value(value_type: u8, bytes: &[u8]) -> f64 {
+    match value_type {
+        b'b' => bytes[0] as i8 as f64,
+        b'B' | b'c' => bytes[0] as f64,
+        b's' => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+        b'S' => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+        b'l' => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        b'L' | b'F' => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        b'f' => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        b'd' => f64::from_be_bytes(bytes[..8].try_into().unwrap_or_default()),
+        b'j' => i64::from_be_bytes(bytes[..8].try_into().unwrap_or_default()) as f64,
+        b'J' => u64::from_be_bytes(bytes[..8].try_into().unwrap_or_default()) as f64,
+        _ => 0.0,
+    }
+}
+
+/// SCAL pode ter um único divisor para todos os campos ou um por campo
+fn scale_for(scale: &[f64], index: usize) -> f64 {
+    let divisor = if scale.len() > 1 { scale.get(index) } else { scale.first() };
+    divisor.copied().filter(|d| *d != 0.0).unwrap_or(1.0)
+}
+
+fn read_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).trim_end_matches('\0').trim().to_string()
+}
+
+/// Converte a hora do GPSU ("yymmddhhmmss.sss") para UTC
+fn parse_gps_time(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(&format!("20{}", text), "%Y%m%d%H%M%S%.f")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
@@ -0,0 +1,153 @@
+// src/gpx_adapter.rs - Leitura de GPX com extensões de telemetria (Garmin TrackPointExtension)
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use gpx::Gpx;
+use xml::reader::{EventReader, XmlEvent};
+use crate::tcx_adapter::TcxExtraData;
+use crate::telemetry::TelemetrySample;
+
+/// Estrutura para o resultado do processamento de um arquivo GPX
+pub struct GpxProcessResult {
+    pub gpx: Gpx,
+    /// Presente apenas quando o arquivo traz telemetria nas extensões
+    pub extra_data: Option<TcxExtraData>,
+    /// Telemetria por ponto, paralela aos pontos de `gpx` (vazia se não houver extensões)
+    pub telemetry: Vec<TelemetrySample>,
+}
+
+/// Lê um arquivo GPX e extrai a telemetria de `gpxtpx:TrackPointExtension`
+/// (Garmin Connect, Strava, Wahoo) para o mesmo modelo produzido pelo adaptador TCX.
+pub fn read_and_process_gpx(path: &Path) -> Result<GpxProcessResult, Box<dyn Error>> {
+    let gpx = gpx::read(BufReader::new(File::open(path)?))?;
+    let extensions = read_track_point_extensions(path)?;
+
+    // Cada ponto recebe a amostra seguinte com as mesmas coordenadas, por isso um `<trkpt>`
+    // a mais ou a menos numa das leituras não desalinha os restantes
+    let mut by_position: HashMap<PositionKey, VecDeque<usize>> = HashMap::new();
+    for (index, (position, _)) in extensions.iter().enumerate() {
+        by_position.entry(*position).or_default().push_back(index);
+    }
+    let mut samples = Vec::new();
+    let mut unmatched = 0;
+    let mut next = 0;
+    for point in gpx.tracks.iter().flat_map(|t| &t.segments).flat_map(|s| &s.points) {
+        // Só as amostras depois da última usada, para manter a ordem do documento
+        let index = by_position.get_mut(&position_key(point.point().y(), point.point().x())).and_then(|candidates| {
+            while candidates.front().is_some_and(|&index| index < next) {
+                candidates.pop_front();
+            }
+            candidates.pop_front()
+        });
+        match index {
+            Some(index) => {
+                next = index + 1;
+                samples.push(extensions[index].1);
+            },
+            None => {
+                unmatched += 1;
+                samples.push(TelemetrySample::default());
+            },
+        }
+    }
+    if unmatched > 0 || extensions.len() != samples.len() {
+        tracing::warn!(
+            "GPX {}: {} pontos lidos e {} <trkpt> no XML; {} pontos ficaram sem telemetria",
+            path.display(), samples.len(), extensions.len(), unmatched
+        );
+    }
+
+    if samples.iter().all(|s| *s == TelemetrySample::default()) {
+        return Ok(GpxProcessResult { gpx, extra_data: None, telemetry: Vec::new() });
+    }
+
+    let mut extra_data = TcxExtraData {
+        sport: gpx.tracks.iter().find_map(|t| t.type_.clone()),
+        device_name: gpx.creator.clone(),
+        ..Default::default()
+    };
+    for sample in &samples {
+        if let Some(hr) = sample.heart_rate { extra_data.heart_rate_data.push(hr); }
+        if let Some(cad) = sample.cadence { extra_data.cadence_data.push(cad); }
+        if let Some(speed) = sample.speed_mps {
+            extra_data.speed_data.push(speed);
+            extra_data.max_speed = extra_data.max_speed.max(speed);
+        }
+        if let Some(power) = sample.power { extra_data.power_data.push(power); }
+        if let Some(temp) = sample.temperature { extra_data.temperature_data.push(temp); }
+    }
+
+    Ok(GpxProcessResult { gpx, extra_data: Some(extra_data), telemetry: samples })
+}
+
+/// Bits da latitude e da longitude de um ponto
+type PositionKey = (u64, u64);
+
+/// Coordenadas exatas de um ponto, para comparar as duas leituras do arquivo
+fn position_key(lat: f64, lon: f64) -> PositionKey {
+    (lat.to_bits(), lon.to_bits())
+}
+
+/// Percorre o XML e devolve as coordenadas e uma amostra por `<trkpt>`, na ordem do
+/// documento. Os elementos são identificados pelo nome local, por isso funcionam com
+/// qualquer prefixo de namespace (`gpxtpx:`, `ns3:`, `pwr:`, ...).
+fn read_track_point_extensions(path: &Path) -> Result<Vec<(PositionKey, TelemetrySample)>, Box<dyn Error>> {
+    let parser = EventReader::new(BufReader::new(File::open(path)?));
+
+    let mut samples = Vec::new();
+    let mut current: Option<(PositionKey, TelemetrySample)> = None;
+    let mut in_extensions = false;
+    let mut element_stack: Vec<String> = Vec::new();
+
+    for event in parser {
+        match event? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                match name.local_name.as_str() {
+                    "trkpt" => {
+                        let coordinate = |key: &str| attributes.iter()
+                            .find(|attribute| attribute.name.local_name == key)
+                            .and_then(|attribute| attribute.value.trim().parse::<f64>().ok())
+                            .unwrap_or(f64::NAN);
+                        current = Some((position_key(coordinate("lat"), coordinate("lon")), TelemetrySample::default()));
+                    },
+                    "extensions" if current.is_some() => in_extensions = true,
+                    _ => {}
+                }
+                element_stack.push(name.local_name);
+            },
+            XmlEvent::Characters(text) if in_extensions => {
+                if let (Some((_, sample)), Some(element)) = (current.as_mut(), element_stack.last()) {
+                    if let Ok(value) = text.trim().parse::<f64>() {
+                        apply_extension_value(sample, element, value);
+                    }
+                }
+            },
+            XmlEvent::EndElement { name } => {
+                element_stack.pop();
+                match name.local_name.as_str() {
+                    "extensions" => in_extensions = false,
+                    "trkpt" => samples.extend(current.take()),
+                    _ => {}
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(samples)
+}
+
+/// Mapeia os nomes de elemento das extensões mais comuns para os canais de telemetria
+fn apply_extension_value(sample: &mut TelemetrySample, element: &str, value: f64) {
+    match element {
+        "hr" | "heartrate" => sample.heart_rate = Some(value),
+        "cad" | "cadence" => sample.cadence = Some(value),
+        "atemp" | "temp" | "temperature" => sample.temperature = Some(value),
+        "power" | "PowerInWatts" => sample.power = Some(value),
+        "speed" => sample.speed_mps = Some(value),
+        _ => {}
+    }
+}
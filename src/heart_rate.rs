@@ -0,0 +1,173 @@
+// src/heart_rate.rs - Zonas de frequência cardíaca (Z1–Z5) e tempo passado em cada uma
+
+use std::fmt;
+use std::str::FromStr;
+use image::Rgba;
+use serde::{Deserialize, Serialize};
+
+/// Número de zonas de treino
+pub const ZONE_COUNT: usize = 5;
+/// FC máxima usada quando o atleta não configurou as zonas
+pub const DEFAULT_MAX_HR: f64 = 190.0;
+
+/// Z1 cinza, Z2 azul, Z3 verde, Z4 laranja e Z5 vermelho
+pub const ZONE_COLORS: [Rgba<u8>; ZONE_COUNT] = [
+    Rgba([158, 158, 158, 255]),
+    Rgba([33, 150, 243, 255]),
+    Rgba([76, 175, 80, 255]),
+    Rgba([255, 152, 0, 255]),
+    Rgba([244, 67, 54, 255]),
+];
+
+/// Referência a partir da qual as zonas são calculadas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ZoneMethod {
+    /// Percentagens da FC máxima: 50, 60, 70, 80 e 90%
+    MaxHr,
+    /// Percentagens da FC do limiar de lactato (LTHR): 68, 85, 90, 95 e 100%
+    Lthr,
+    /// Karvonen: repouso + 50, 60, 70, 80 e 90% da reserva (máxima - repouso)
+    Reserve,
+}
+
+impl ZoneMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZoneMethod::MaxHr => "max-hr",
+            ZoneMethod::Lthr => "lthr",
+            ZoneMethod::Reserve => "reserve",
+        }
+    }
+}
+
+impl fmt::Display for ZoneMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ZoneMethod {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "max-hr" => Ok(ZoneMethod::MaxHr),
+            "lthr" => Ok(ZoneMethod::Lthr),
+            "reserve" => Ok(ZoneMethod::Reserve),
+            other => Err(format!("Método de zonas desconhecido: {} (disponíveis: max-hr, lthr, reserve)", other)),
+        }
+    }
+}
+
+/// Limites das zonas em bpm: `bounds[i]` é o início da zona i+1 e `bounds[5]` o topo da escala
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HeartRateZones {
+    pub method: ZoneMethod,
+    pub bounds: [f64; ZONE_COUNT + 1],
+}
+
+impl Default for HeartRateZones {
+    fn default() -> Self {
+        HeartRateZones::from_max_hr(DEFAULT_MAX_HR)
+    }
+}
+
+impl HeartRateZones {
+    pub fn from_max_hr(max_hr: f64) -> Self {
+        HeartRateZones { method: ZoneMethod::MaxHr, bounds: [0.5, 0.6, 0.7, 0.8, 0.9, 1.0].map(|pct| pct * max_hr) }
+    }
+
+    /// A escala vai até 110% do limiar, pouco acima da FC máxima típica
+    pub fn from_lthr(lthr: f64) -> Self {
+        HeartRateZones { method: ZoneMethod::Lthr, bounds: [0.68, 0.85, 0.9, 0.95, 1.0, 1.1].map(|pct| pct * lthr) }
+    }
+
+    pub fn from_reserve(max_hr: f64, resting_hr: f64) -> Self {
+        let reserve = max_hr - resting_hr;
+        HeartRateZones { method: ZoneMethod::Reserve, bounds: [0.5, 0.6, 0.7, 0.8, 0.9, 1.0].map(|pct| resting_hr + pct * reserve) }
+    }
+
+    /// Zonas configuradas pelo atleta; `None` sem nenhum valor. Sem `method`, usa o LTHR se
+    /// indicado, senão a reserva (com máxima e repouso) ou a FC máxima.
+    pub fn from_settings(
+        method: Option<ZoneMethod>,
+        max_hr: Option<f64>,
+        resting_hr: Option<f64>,
+        lthr: Option<f64>,
+    ) -> Result<Option<Self>, String> {
+        let check = |name: &str, value: Option<f64>, range: std::ops::RangeInclusive<f64>| match value {
+            Some(bpm) if !range.contains(&bpm) => Err(format!("{} fora do intervalo {}–{} bpm: {}", name, range.start(), range.end(), bpm)),
+            _ => Ok(value),
+        };
+        let max_hr = check("FC máxima", max_hr, 100.0..=250.0)?;
+        let resting_hr = check("FC de repouso", resting_hr, 25.0..=120.0)?;
+        let lthr = check("LTHR", lthr, 80.0..=230.0)?;
+
+        let method = match method {
+            Some(method) => method,
+            None if lthr.is_some() => ZoneMethod::Lthr,
+            None if max_hr.is_some() && resting_hr.is_some() => ZoneMethod::Reserve,
+            None if max_hr.is_some() => ZoneMethod::MaxHr,
+            None => return Ok(None),
+        };
+        let zones = match method {
+            ZoneMethod::MaxHr => HeartRateZones::from_max_hr(max_hr.ok_or("As zonas por FC máxima precisam da FC máxima.")?),
+            ZoneMethod::Lthr => HeartRateZones::from_lthr(lthr.ok_or("As zonas por limiar precisam do LTHR.")?),
+            ZoneMethod::Reserve => {
+                let (Some(max_hr), Some(resting_hr)) = (max_hr, resting_hr) else {
+                    return Err("As zonas por reserva precisam da FC máxima e da FC de repouso.".to_string());
+                };
+                if resting_hr >= max_hr {
+                    return Err("A FC de repouso tem de ser menor que a FC máxima.".to_string());
+                }
+                HeartRateZones::from_reserve(max_hr, resting_hr)
+            },
+        };
+        Ok(Some(zones))
+    }
+
+    /// Índice (0 = Z1) da zona de `heart_rate`; abaixo de Z1 conta como Z1 e acima da escala como Z5
+    pub fn zone(&self, heart_rate: f64) -> usize {
+        self.bounds[1..ZONE_COUNT].iter().take_while(|&&bound| heart_rate >= bound).count()
+    }
+
+    /// Posição de `heart_rate` na escala, de 0 (início de Z1) a 1 (topo)
+    pub fn ratio(&self, heart_rate: f64) -> f64 {
+        let (low, high) = (self.bounds[0], self.bounds[ZONE_COUNT]);
+        ((heart_rate - low) / (high - low)).clamp(0.0, 1.0)
+    }
+
+    pub fn describe(&self) -> String {
+        let bounds: Vec<String> = self.bounds.iter().map(|bpm| format!("{:.0}", bpm)).collect();
+        format!("{} ({} bpm)", self.method, bounds.join("/"))
+    }
+}
+
+/// Segundos acumulados em cada zona
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeInZones(pub [f64; ZONE_COUNT]);
+
+impl TimeInZones {
+    /// Soma `secs` à zona de `heart_rate`
+    pub fn add(&mut self, zones: &HeartRateZones, heart_rate: f64, secs: f64) {
+        if secs > 0.0 {
+            self.0[zones.zone(heart_rate)] += secs;
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.0.iter().sum()
+    }
+}
+
+/// Duração em "m:ss", ou "h:mm:ss" a partir de uma hora
+pub fn format_duration(secs: f64) -> String {
+    let total = secs.max(0.0).round() as u64;
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
@@ -0,0 +1,390 @@
+// src/jobs.rs - Fila de processamentos em segundo plano, com diretório e saída próprios por job
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::{broadcast, Semaphore};
+use uuid::Uuid;
+use crate::cancellation::CancellationToken;
+use crate::progress::{EncodeProgress, FrameProgress, ProgressEvent, ProgressReporter};
+
+/// Jobs processados em simultâneo quando `MAX_CONCURRENT_JOBS` não está definido.
+/// Cada job mantém um FFmpeg ocupado, por isso poucos em paralelo já saturam a máquina.
+pub const DEFAULT_MAX_CONCURRENT_JOBS: usize = 2;
+const MAX_CONCURRENT_JOBS_ENV: &str = "MAX_CONCURRENT_JOBS";
+/// Tempo durante o qual um job terminado (e o seu vídeo) é mantido, quando `JOB_TTL_MINUTES`
+/// não está definido
+pub const DEFAULT_JOB_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const JOB_TTL_ENV: &str = "JOB_TTL_MINUTES";
+/// Intervalo entre passagens do janitor
+const JANITOR_INTERVAL: Duration = Duration::from_secs(60);
+/// Diretório de trabalho dos jobs (uploads e arquivos temporários)
+pub const JOBS_DIR: &str = "jobs";
+/// Diretório servido em `/output` com os vídeos finais
+pub const OUTPUT_DIR: &str = "output";
+/// Eventos guardados para cada ouvinte SSE; um ouvinte mais lento perde os mais antigos
+const EVENT_BUFFER: usize = 256;
+
+/// Resultado de um processamento: logs em caso de sucesso, mensagem e logs em caso de erro
+pub type JobResult = Result<Vec<String>, (String, Vec<String>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Completed | JobState::Failed | JobState::Cancelled)
+    }
+}
+
+/// Resultado de `JobQueue::cancel`
+#[derive(Debug, Clone)]
+pub enum CancelOutcome {
+    /// O job ainda estava na fila e foi cancelado de imediato
+    Cancelled(JobStatus),
+    /// O job está a correr; passa a `cancelled` assim que o processamento parar
+    Cancelling(JobStatus),
+    /// O job já tinha terminado e nada mudou
+    AlreadyFinished(JobStatus),
+}
+
+/// Estado de um job devolvido por `GET /jobs/{id}`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub id: Uuid,
+    pub state: JobState,
+    pub logs: Vec<String>,
+    /// Só presente quando o job terminou com sucesso
+    pub download_url: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Último progresso conhecido da geração dos frames de overlay
+    pub frames: Option<FrameProgress>,
+    /// Último progresso conhecido da codificação do FFmpeg
+    pub encode: Option<EncodeProgress>,
+}
+
+impl JobStatus {
+    fn state_change(&self) -> StateChange {
+        StateChange {
+            state: self.state,
+            download_url: self.download_url.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+/// Mudança de estado de um job, publicada no stream de eventos
+#[derive(Debug, Clone, Serialize)]
+pub struct StateChange {
+    pub state: JobState,
+    pub download_url: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Evento do stream `/jobs/{id}/events`
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    State(StateChange),
+    Progress(ProgressEvent),
+}
+
+/// Eventos já ocorridos de um job (para quem se liga a meio) e o canal dos seguintes
+pub struct JobSubscription {
+    pub history: Vec<JobEvent>,
+    /// `None` se o job já terminou: não haverá mais eventos
+    pub receiver: Option<broadcast::Receiver<JobEvent>>,
+}
+
+struct JobEntry {
+    status: JobStatus,
+    events: broadcast::Sender<JobEvent>,
+    cancel: CancellationToken,
+    /// Apagado junto com o job quando expira
+    output_file: PathBuf,
+}
+
+/// Caminhos reservados a um job
+#[derive(Debug, Clone)]
+pub struct JobPaths {
+    pub id: Uuid,
+    /// Recebe os uploads; é apagado quando o job termina
+    pub work_dir: PathBuf,
+    /// Vídeo final, com o ID do job no nome
+    pub output_file: PathBuf,
+}
+
+impl JobPaths {
+    pub fn download_url(&self) -> String {
+        let file_name = self.output_file.file_name().unwrap_or_default().to_string_lossy();
+        format!("/{}/{}", OUTPUT_DIR, file_name)
+    }
+}
+
+/// Fila de jobs: guarda o estado de cada um e limita quantos correm ao mesmo tempo
+pub struct JobQueue {
+    jobs: Mutex<HashMap<Uuid, JobEntry>>,
+    workers: Arc<Semaphore>,
+    jobs_dir: PathBuf,
+    output_dir: PathBuf,
+    /// Tempo após o fim de um job até ser esquecido e o seu vídeo apagado
+    ttl: Duration,
+}
+
+impl JobQueue {
+    pub fn new(jobs_dir: impl Into<PathBuf>, output_dir: impl Into<PathBuf>, max_concurrent_jobs: usize, ttl: Duration) -> Self {
+        JobQueue {
+            jobs: Mutex::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(max_concurrent_jobs.max(1))),
+            jobs_dir: jobs_dir.into(),
+            output_dir: output_dir.into(),
+            ttl,
+        }
+    }
+
+    /// Fila nos diretórios padrão, com o limite de `MAX_CONCURRENT_JOBS` e o TTL de
+    /// `JOB_TTL_MINUTES`
+    pub fn from_env() -> Self {
+        let max_concurrent_jobs = std::env::var(MAX_CONCURRENT_JOBS_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|&max: &usize| max > 0)
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_JOBS);
+        let ttl = std::env::var(JOB_TTL_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|&minutes| minutes > 0)
+            .map(|minutes| Duration::from_secs(minutes * 60))
+            .unwrap_or(DEFAULT_JOB_TTL);
+        tracing::debug!("Até {} jobs em simultâneo, mantidos {} min depois de terminarem", max_concurrent_jobs, ttl.as_secs() / 60);
+        JobQueue::new(JOBS_DIR, OUTPUT_DIR, max_concurrent_jobs, ttl)
+    }
+
+    /// Reserva um ID e cria o diretório de trabalho, antes de receber os uploads
+    pub fn prepare(&self) -> std::io::Result<JobPaths> {
+        let id = Uuid::new_v4();
+        let work_dir = self.jobs_dir.join(id.to_string());
+        std::fs::create_dir_all(&work_dir)?;
+        std::fs::create_dir_all(&self.output_dir)?;
+        Ok(JobPaths {
+            id,
+            work_dir: std::fs::canonicalize(&work_dir)?,
+            output_file: self.output_dir.join(format!("{}.mp4", id)),
+        })
+    }
+
+    /// Descarta um job preparado que não chegou a ser submetido (pedido inválido)
+    pub fn discard(&self, paths: &JobPaths) {
+        cleanup_files(paths, false);
+    }
+
+    /// Põe o job na fila e devolve logo; `task` corre numa thread de bloqueio assim que
+    /// houver um worker livre, publica o progresso no `ProgressReporter` recebido e deve
+    /// parar quando o `CancellationToken` for acionado
+    pub fn submit<F>(self: &Arc<Self>, paths: JobPaths, task: F) -> Uuid
+    where
+        F: FnOnce(&JobPaths, ProgressReporter, CancellationToken) -> JobResult + Send + 'static,
+    {
+        let id = paths.id;
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        let cancel = CancellationToken::new();
+        self.jobs.lock().unwrap().insert(id, JobEntry {
+            status: JobStatus {
+                id,
+                state: JobState::Queued,
+                logs: Vec::new(),
+                download_url: None,
+                error: None,
+                created_at: Utc::now(),
+                finished_at: None,
+                frames: None,
+                encode: None,
+            },
+            events,
+            cancel: cancel.clone(),
+            output_file: paths.output_file.clone(),
+        });
+
+        let queue = Arc::clone(self);
+        tokio::spawn(async move {
+            let _permit = queue.workers.clone().acquire_owned().await;
+            if !queue.start(id) {
+                // Cancelado enquanto esperava na fila
+                tokio::task::spawn_blocking(move || cleanup_files(&paths, true)).await.ok();
+                return;
+            }
+
+            let reporter_queue = Arc::clone(&queue);
+            let reporter = ProgressReporter::new(move |event| reporter_queue.record_progress(id, event));
+            let result = tokio::task::spawn_blocking(move || {
+                let result = task(&paths, reporter, cancel.clone());
+                // Um vídeo cancelado ou com erro fica incompleto: a saída também é apagada
+                let cancelled = cancel.is_cancelled();
+                cleanup_files(&paths, cancelled || result.is_err());
+                (result, paths, cancelled)
+            }).await;
+
+            queue.update(id, |status| {
+                status.finished_at = Some(Utc::now());
+                match result {
+                    Ok((Ok(logs), _, true)) | Ok((Err((_, logs)), _, true)) => {
+                        status.state = JobState::Cancelled;
+                        status.logs = logs;
+                    },
+                    Ok((Ok(logs), paths, false)) => {
+                        status.state = JobState::Completed;
+                        status.logs = logs;
+                        status.download_url = Some(paths.download_url());
+                    },
+                    Ok((Err((message, logs)), _, false)) => {
+                        status.state = JobState::Failed;
+                        status.logs = logs;
+                        status.error = Some(message);
+                    },
+                    Err(e) => {
+                        status.state = JobState::Failed;
+                        status.error = Some(format!("O processamento foi interrompido: {}", e));
+                    },
+                }
+            });
+        });
+        id
+    }
+
+    pub fn status(&self, id: Uuid) -> Option<JobStatus> {
+        self.jobs.lock().unwrap().get(&id).map(|entry| entry.status.clone())
+    }
+
+    /// Pede o cancelamento de um job: na fila é cancelado logo; a correr, o processamento
+    /// é avisado e o job passa a `cancelled` quando parar
+    pub fn cancel(&self, id: Uuid) -> Option<CancelOutcome> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get_mut(&id)?;
+        let outcome = match entry.status.state {
+            JobState::Queued => {
+                entry.cancel.cancel();
+                entry.status.state = JobState::Cancelled;
+                entry.status.finished_at = Some(Utc::now());
+                entry.events.send(JobEvent::State(entry.status.state_change())).ok();
+                CancelOutcome::Cancelled(entry.status.clone())
+            },
+            JobState::Running => {
+                entry.cancel.cancel();
+                CancelOutcome::Cancelling(entry.status.clone())
+            },
+            _ => CancelOutcome::AlreadyFinished(entry.status.clone()),
+        };
+        Some(outcome)
+    }
+
+    /// Passa o job a `running` quando um worker o pega; `false` se foi cancelado na fila
+    fn start(&self, id: Uuid) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(entry) = jobs.get_mut(&id) else { return false };
+        if entry.status.state != JobState::Queued {
+            return false;
+        }
+        entry.status.state = JobState::Running;
+        entry.events.send(JobEvent::State(entry.status.state_change())).ok();
+        true
+    }
+
+    /// Estado atual e logs já escritos, seguidos dos eventos futuros. O histórico e a
+    /// inscrição são obtidos sob o mesmo lock, por isso nenhum evento se perde ou repete.
+    pub fn subscribe(&self, id: Uuid) -> Option<JobSubscription> {
+        let jobs = self.jobs.lock().unwrap();
+        let entry = jobs.get(&id)?;
+        let status = &entry.status;
+
+        let mut history = vec![JobEvent::State(status.state_change())];
+        history.extend(status.logs.iter().map(|line| JobEvent::Progress(ProgressEvent::Log { message: line.clone() })));
+        history.extend(status.frames.map(|frames| JobEvent::Progress(ProgressEvent::Frames(frames))));
+        history.extend(status.encode.map(|encode| JobEvent::Progress(ProgressEvent::Encode(encode))));
+
+        let receiver = (!status.state.is_finished()).then(|| entry.events.subscribe());
+        Some(JobSubscription { history, receiver })
+    }
+
+    fn record_progress(&self, id: Uuid, event: ProgressEvent) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(entry) = jobs.get_mut(&id) else { return };
+        match &event {
+            ProgressEvent::Log { message } => entry.status.logs.push(message.clone()),
+            ProgressEvent::Frames(frames) => entry.status.frames = Some(*frames),
+            ProgressEvent::Encode(encode) => entry.status.encode = Some(*encode),
+        }
+        // Sem ouvintes o envio falha, o que não é um erro
+        entry.events.send(JobEvent::Progress(event)).ok();
+    }
+
+    /// Esquece os jobs terminados há mais de um TTL em `now`, apaga os seus vídeos e devolve
+    /// os IDs removidos
+    pub fn remove_expired(&self, now: DateTime<Utc>) -> Vec<Uuid> {
+        let ttl = chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::days(1));
+        let expired: Vec<(Uuid, PathBuf)> = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let ids: Vec<Uuid> = jobs.values()
+                .filter(|entry| entry.status.state.is_finished() && entry.status.finished_at.is_some_and(|at| at + ttl <= now))
+                .map(|entry| entry.status.id)
+                .collect();
+            ids.iter().filter_map(|id| jobs.remove(id).map(|entry| (*id, entry.output_file))).collect()
+        };
+        for (_, output_file) in &expired {
+            if output_file.exists() {
+                if let Err(e) = std::fs::remove_file(output_file) {
+                    tracing::debug!("Não foi possível apagar a saída do job {:?}: {}", output_file, e);
+                }
+            }
+        }
+        expired.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Altera o estado do job e publica a mudança
+    fn update(&self, id: Uuid, change: impl FnOnce(&mut JobStatus)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(entry) = jobs.get_mut(&id) {
+            change(&mut entry.status);
+            entry.events.send(JobEvent::State(entry.status.state_change())).ok();
+        }
+    }
+}
+
+/// Tarefa que esquece periodicamente os jobs expirados
+pub fn spawn_janitor(queue: Arc<JobQueue>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(JANITOR_INTERVAL);
+        loop {
+            interval.tick().await;
+            let queue = Arc::clone(&queue);
+            let removed = tokio::task::spawn_blocking(move || queue.remove_expired(Utc::now())).await;
+            if let Ok(removed) = removed {
+                if !removed.is_empty() {
+                    tracing::debug!("Janitor: {} job(s) expirado(s) removido(s)", removed.len());
+                }
+            }
+        }
+    })
+}
+
+/// Apaga o diretório de trabalho do job e, se `remove_output`, o vídeo final (incompleto)
+fn cleanup_files(paths: &JobPaths, remove_output: bool) {
+    if let Err(e) = std::fs::remove_dir_all(&paths.work_dir) {
+        tracing::debug!("Não foi possível apagar o diretório do job {:?}: {}", paths.work_dir, e);
+    }
+    if remove_output && paths.output_file.exists() {
+        if let Err(e) = std::fs::remove_file(&paths.output_file) {
+            tracing::debug!("Não foi possível apagar a saída do job {:?}: {}", paths.output_file, e);
+        }
+    }
+}
@@ -0,0 +1,544 @@
+// src/layout.rs - Documento de layout dos overlays (JSON ou TOML): posição, tamanho, opacidade,
+// ordem e estilo de cada widget
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::drawing::{load_font, Theme, BUILTIN_FONTS};
+use crate::widget::WidgetRegistry;
+
+/// Distância padrão dos widgets às bordas do vídeo, em pixels de referência
+pub const DEFAULT_MARGIN: f64 = 10.0;
+
+/// Lado menor do vídeo (1080p, horizontal ou vertical) em que os tamanhos naturais dos widgets
+/// e as medidas em pixels do layout valem 1:1; noutras resoluções são escalados na proporção
+pub const REFERENCE_SHORT_SIDE: f64 = 1080.0;
+
+/// Lado maior do vídeo de referência (1920 px em 1080p); nenhuma medida em pixels o excede
+pub const REFERENCE_LONG_SIDE: f64 = 1920.0;
+
+/// Maior `scale` aceite num widget
+pub const MAX_SCALE: f64 = 10.0;
+
+/// Fator aplicado aos pixels de referência num vídeo `canvas`: 2 em 4K, 2/3 em 720p
+pub fn resolution_scale(canvas: (u32, u32)) -> f64 {
+    canvas.0.min(canvas.1).max(1) as f64 / REFERENCE_SHORT_SIDE
+}
+
+/// Diretório das fontes que os layouts enviados ao servidor podem usar
+const FONTS_DIR_ENV: &str = "LAYOUT_FONTS_DIR";
+/// Diretório dos mapas base (MBTiles ou XYZ) que os layouts enviados ao servidor podem usar
+const BASEMAPS_DIR_ENV: &str = "LAYOUT_BASEMAPS_DIR";
+
+/// Diretórios de onde um layout enviado ao servidor pode ler arquivos; sem diretório, só os
+/// recursos incluídos são aceitos
+#[derive(Debug, Clone, Default)]
+pub struct LayoutResources {
+    pub fonts_dir: Option<PathBuf>,
+    /// Diretório da opção `basemap` do `track_map`
+    pub basemaps_dir: Option<PathBuf>,
+}
+
+impl LayoutResources {
+    /// Diretórios das variáveis de ambiente (`LAYOUT_FONTS_DIR`, `LAYOUT_BASEMAPS_DIR`)
+    pub fn from_env() -> Self {
+        let dir = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+        LayoutResources { fonts_dir: dir(FONTS_DIR_ENV), basemaps_dir: dir(BASEMAPS_DIR_ENV) }
+    }
+}
+
+/// Caminho de `name` dentro de `dir`: só componentes normais, sem caminhos absolutos nem `..`,
+/// e ainda dentro de `dir` depois de seguir as ligações simbólicas
+pub fn resolve_in_dir(dir: Option<&Path>, name: &str, kind: &str) -> Result<PathBuf, String> {
+    let dir = dir.ok_or_else(|| format!("{} não permitido: {} (o servidor não tem um diretório configurado)", kind, name))?;
+    let relative = Path::new(name);
+    if name.is_empty() || !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+        return Err(format!("{} não permitido: {} (use um nome relativo ao diretório configurado)", kind, name));
+    }
+    let root = dir.canonicalize().map_err(|e| format!("Diretório {} inválido: {}", dir.display(), e))?;
+    let path = root.join(relative).canonicalize().map_err(|_| format!("{} não encontrado: {}", kind, name))?;
+    if !path.starts_with(&root) {
+        return Err(format!("{} não permitido: {} (fora do diretório configurado)", kind, name));
+    }
+    Ok(path)
+}
+
+/// Layouts incluídos, por nome
+pub const PRESETS: &[(&str, &str)] = &[
+    ("classic", include_str!("../layouts/classic.toml")),
+    ("minimal", include_str!("../layouts/minimal.toml")),
+    ("dashboard", include_str!("../layouts/dashboard.toml")),
+    ("training", include_str!("../layouts/training.toml")),
+    ("cycling", include_str!("../layouts/cycling.toml")),
+];
+
+/// Widgets a desenhar, por ordem de `z` (e, com o mesmo `z`, pela ordem do documento)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Layout {
+    #[serde(default)]
+    pub widgets: Vec<WidgetLayout>,
+    /// Widgets usados nos vídeos verticais (mais altos que largos); vazio usa `widgets`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub portrait: Vec<WidgetLayout>,
+}
+
+/// Posição e aparência de um widget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetLayout {
+    /// Nome do widget no `WidgetRegistry` ("speedometer", "track_map", "stats", ...)
+    pub widget: String,
+    #[serde(default)]
+    pub anchor: Anchor,
+    /// Distância à âncora, para dentro do vídeo; no eixo centrado, positivo desloca para a
+    /// direita ou para baixo
+    #[serde(default)]
+    pub offset: Offset,
+    /// Tamanho final; só com uma das medidas a proporção do widget é mantida
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<Size>,
+    /// Multiplica o tamanho (o natural ou o de `size`)
+    #[serde(default = "default_one")]
+    pub scale: f64,
+    /// 0 (invisível) a 1 (opaco)
+    #[serde(default = "default_one")]
+    pub opacity: f64,
+    /// Ordem de desenho: valores maiores ficam por cima
+    #[serde(default)]
+    pub z: i32,
+    #[serde(default)]
+    pub style: WidgetStyle,
+    /// Opções próprias do widget (por exemplo `color_by_gradient` do `elevation_profile`);
+    /// cada widget lê as que conhece
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, serde_json::Value>,
+}
+
+fn default_one() -> f64 {
+    1.0
+}
+
+/// Ponto do vídeo a que o widget se prende
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    #[default]
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// Posição do widget num eixo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Anchor {
+    /// Canto escolhido no formulário ("top-left", "top-right", "bottom-left", "bottom-right");
+    /// outros valores caem no canto inferior esquerdo
+    pub fn from_corner(position: &str) -> Self {
+        match position {
+            "top-left" => Anchor::TopLeft,
+            "top-right" => Anchor::TopRight,
+            "bottom-right" => Anchor::BottomRight,
+            _ => Anchor::BottomLeft,
+        }
+    }
+
+    fn aligns(&self) -> (Align, Align) {
+        match self {
+            Anchor::TopLeft => (Align::Start, Align::Start),
+            Anchor::TopCenter => (Align::Center, Align::Start),
+            Anchor::TopRight => (Align::End, Align::Start),
+            Anchor::CenterLeft => (Align::Start, Align::Center),
+            Anchor::Center => (Align::Center, Align::Center),
+            Anchor::CenterRight => (Align::End, Align::Center),
+            Anchor::BottomLeft => (Align::Start, Align::End),
+            Anchor::BottomCenter => (Align::Center, Align::End),
+            Anchor::BottomRight => (Align::End, Align::End),
+        }
+    }
+
+    /// Canto superior esquerdo de um widget `widget` (largura, altura) em `canvas`
+    pub fn origin(&self, canvas: (u32, u32), widget: (u32, u32), offset: &Offset) -> (i64, i64) {
+        let (horizontal, vertical) = self.aligns();
+        let scale = resolution_scale(canvas);
+        (
+            place(horizontal, canvas.0, widget.0, offset.x, scale),
+            place(vertical, canvas.1, widget.1, offset.y, scale),
+        )
+    }
+}
+
+fn place(align: Align, canvas: u32, widget: u32, offset: Option<Length>, scale: f64) -> i64 {
+    let free = canvas as f64 - widget as f64;
+    let margin = DEFAULT_MARGIN * scale;
+    let position = match align {
+        Align::Start => offset.map_or(margin, |o| o.pixels(canvas, scale)),
+        Align::Center => free / 2.0 + offset.map_or(0.0, |o| o.pixels(canvas, scale)),
+        Align::End => free - offset.map_or(margin, |o| o.pixels(canvas, scale)),
+    };
+    position.round() as i64
+}
+
+/// Distância à âncora em cada eixo; sem valor usa-se `DEFAULT_MARGIN` nas bordas e 0 no centro
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Offset {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<Length>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<Length>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Size {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<Length>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<Length>,
+}
+
+/// Medida em pixels de referência (`120`, `"120px"`, ver `REFERENCE_SHORT_SIDE`) ou em
+/// percentagem da dimensão do vídeo no mesmo eixo (`"12.5%"`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Pixels(f64),
+    Percent(f64),
+}
+
+impl Length {
+    /// Pixels num eixo de `canvas` pixels, com `scale` vindo de `resolution_scale`
+    pub fn pixels(&self, canvas: u32, scale: f64) -> f64 {
+        match *self {
+            Length::Pixels(px) => px * scale,
+            Length::Percent(percent) => canvas as f64 * percent / 100.0,
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match *self {
+            Length::Pixels(value) | Length::Percent(value) => value,
+        }
+    }
+}
+
+impl std::str::FromStr for Length {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let text = value.trim();
+        let (number, percent) = match text.strip_suffix('%') {
+            Some(number) => (number, true),
+            None => (text.strip_suffix("px").unwrap_or(text), false),
+        };
+        let number: f64 = number.trim().parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite())
+            .ok_or_else(|| format!("Medida inválida: '{}'. Use pixels (120) ou percentagem (\"10%\")", value))?;
+        Ok(if percent { Length::Percent(number) } else { Length::Pixels(number) })
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Pixels(px) => write!(f, "{}px", px),
+            Length::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Length::Pixels(px) => serializer.serialize_f64(*px),
+            Length::Percent(_) => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(px) => Ok(Length::Pixels(px)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Cores e fontes de um widget; campos em falta mantêm as do widget
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetStyle {
+    /// Textos e marcações
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_color: Option<Color>,
+    /// Arco do velocímetro, marcador do mapa e valores de telemetria das estatísticas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<Color>,
+    /// Fonte regular: "DejaVuSans", "DejaVuSans-Bold" ou o caminho de um arquivo TTF/OTF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// Fonte dos valores em destaque, com os mesmos valores de `font`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold_font: Option<String>,
+}
+
+/// Cor em hexadecimal: "#RRGGBB" ou "#RRGGBBAA"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub Rgba<u8>);
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Cor inválida: '{}'. Use \"#RRGGBB\" ou \"#RRGGBBAA\"", value);
+        let hex = value.trim().strip_prefix('#').ok_or_else(invalid)?;
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+        let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+        Ok(Color(Rgba([channel(0)?, channel(2)?, channel(4)?, alpha])))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b, a] = self.0 .0;
+        write!(f, "#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Layout {
+    /// Lê um documento JSON (começa por `{`) ou TOML e valida-o com os widgets incluídos
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        Layout::parse_with(text, &WidgetRegistry::builtin())
+    }
+
+    /// Como `parse`, aceitando os widgets de `registry`
+    pub fn parse_with(text: &str, registry: &WidgetRegistry) -> Result<Self, Box<dyn Error>> {
+        let layout: Layout = if text.trim_start().starts_with('{') {
+            serde_json::from_str(text).map_err(|e| format!("Layout JSON inválido: {}", e))?
+        } else {
+            toml::from_str(text).map_err(|e| format!("Layout TOML inválido: {}", e))?
+        };
+        layout.validate(registry)?;
+        Ok(layout)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Não foi possível ler o layout {}: {}", path.display(), e))?;
+        Layout::parse(&text)
+    }
+
+    /// Layout incluído com este nome (ver `PRESETS`)
+    pub fn preset(name: &str) -> Option<Self> {
+        PRESETS.iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, text)| Layout::parse(text).expect("Os layouts incluídos são válidos"))
+    }
+
+    /// Layout equivalente aos cantos escolhidos no formulário; `None` omite o widget
+    pub fn from_corners(speedometer: Option<&str>, track_map: Option<&str>, stats: Option<&str>) -> Self {
+        let widgets = [
+            ("speedometer", speedometer),
+            ("track_map", track_map),
+            ("stats", stats),
+        ];
+        Layout {
+            widgets: widgets.into_iter()
+                .filter_map(|(widget, position)| Some(WidgetLayout::new(widget, Anchor::from_corner(position?))))
+                .collect(),
+            portrait: Vec::new(),
+        }
+    }
+
+    /// Define a opção `name` de todos os widgets `widget`, incluindo os de `portrait`
+    pub fn set_option(&mut self, widget: &str, name: &str, value: serde_json::Value) {
+        for layout in self.widgets.iter_mut().chain(self.portrait.iter_mut()).filter(|w| w.widget == widget) {
+            layout.options.insert(name.to_string(), value.clone());
+        }
+    }
+
+    /// Restringe os arquivos que um layout recebido de um cliente pode abrir: as fontes têm de
+    /// ser as incluídas ou estar em `resources.fonts_dir`, os mapas base em
+    /// `resources.basemaps_dir`, e passam a ser o caminho resolvido
+    pub fn confine(&mut self, resources: &LayoutResources) -> Result<(), String> {
+        for widget in self.widgets.iter_mut().chain(self.portrait.iter_mut()) {
+            for font in [&mut widget.style.font, &mut widget.style.bold_font].into_iter().flatten() {
+                if !BUILTIN_FONTS.contains(&font.as_str()) {
+                    let path = resolve_in_dir(resources.fonts_dir.as_deref(), font, "Fonte")?;
+                    *font = path.to_string_lossy().into_owned();
+                }
+            }
+            if let Some(serde_json::Value::String(name)) = widget.options.get("basemap") {
+                let path = resolve_in_dir(resources.basemaps_dir.as_deref(), name, "Mapa base")?;
+                widget.options.insert("basemap".to_string(), serde_json::json!(path.to_string_lossy()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, widget: &str) -> bool {
+        self.widgets.iter().chain(&self.portrait).any(|w| w.widget == widget)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty() && self.portrait.is_empty()
+    }
+
+    /// Widgets para um vídeo `canvas`: os de `portrait` se for vertical e houver algum
+    pub fn widgets_for(&self, canvas: (u32, u32)) -> &[WidgetLayout] {
+        if canvas.1 > canvas.0 && !self.portrait.is_empty() { &self.portrait } else { &self.widgets }
+    }
+
+    /// Widgets de `widgets_for` pela ordem em que são desenhados
+    pub fn ordered(&self, canvas: (u32, u32)) -> Vec<&WidgetLayout> {
+        let mut widgets: Vec<&WidgetLayout> = self.widgets_for(canvas).iter().collect();
+        widgets.sort_by_key(|w| w.z);
+        widgets
+    }
+
+    pub fn validate(&self, registry: &WidgetRegistry) -> Result<(), String> {
+        let sections = [("widgets", &self.widgets), ("portrait", &self.portrait)];
+        for (index, widget, section) in sections.into_iter().flat_map(|(section, widgets)| {
+            widgets.iter().enumerate().map(move |(index, widget)| (index, widget, section))
+        }) {
+            let name = format!("{}[{}] ({})", section, index, widget.widget);
+            if !registry.contains(&widget.widget) {
+                return Err(format!("{}: widget desconhecido (disponíveis: {}).", name, registry.names().join(", ")));
+            }
+            if !(widget.scale.is_finite() && widget.scale > 0.0 && widget.scale <= MAX_SCALE) {
+                return Err(format!("{}: `scale` tem de ser maior que 0 e no máximo {}.", name, MAX_SCALE));
+            }
+            if !(0.0..=1.0).contains(&widget.opacity) {
+                return Err(format!("{}: `opacity` tem de estar entre 0 e 1.", name));
+            }
+            let size = widget.size.unwrap_or_default();
+            let lengths = [size.width, size.height].into_iter().flatten();
+            if lengths.clone().any(|length| !(length.value().is_finite() && length.value() > 0.0)) {
+                return Err(format!("{}: as medidas de `size` têm de ser positivas.", name));
+            }
+            // Um widget nunca é maior que o vídeo
+            if lengths.into_iter().any(|length| match length {
+                Length::Pixels(px) => px > REFERENCE_LONG_SIDE,
+                Length::Percent(percent) => percent > 100.0,
+            }) {
+                return Err(format!("{}: as medidas de `size` vão até 100% ou {} px.", name, REFERENCE_LONG_SIDE));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WidgetLayout {
+    /// Widget na âncora indicada, com o tamanho e o estilo padrão
+    pub fn new(widget: &str, anchor: Anchor) -> Self {
+        WidgetLayout {
+            widget: widget.to_string(),
+            anchor,
+            offset: Offset::default(),
+            size: None,
+            scale: 1.0,
+            opacity: 1.0,
+            z: 0,
+            style: WidgetStyle::default(),
+            options: BTreeMap::new(),
+        }
+    }
+
+    /// Tamanho final de um widget cujo tamanho natural (em pixels de referência) é `natural`,
+    /// num vídeo `canvas`
+    pub fn output_size(&self, natural: (u32, u32), canvas: (u32, u32)) -> (u32, u32) {
+        let size = self.size.unwrap_or_default();
+        let resolution = resolution_scale(canvas);
+        let (natural_w, natural_h) = (natural.0.max(1) as f64, natural.1.max(1) as f64);
+        let width = size.width.map(|w| w.pixels(canvas.0, resolution));
+        let height = size.height.map(|h| h.pixels(canvas.1, resolution));
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, width * natural_h / natural_w),
+            (None, Some(height)) => (height * natural_w / natural_h, height),
+            (None, None) => (natural_w * resolution, natural_h * resolution),
+        };
+        // Reduzido na proporção para caber no vídeo, qualquer que seja o layout
+        let (width, height) = (width * self.scale, height * self.scale);
+        let fit = (canvas.0.max(1) as f64 / width).min(canvas.1.max(1) as f64 / height).min(1.0);
+        let scaled = |value: f64| ((value * fit).round() as u32).max(1);
+        (scaled(width), scaled(height))
+    }
+
+    /// Canto superior esquerdo do widget já redimensionado
+    pub fn origin(&self, canvas: (u32, u32), widget: (u32, u32)) -> (i64, i64) {
+        self.anchor.origin(canvas, widget, &self.offset)
+    }
+
+    /// `defaults` com as cores e fontes do estilo aplicadas; falha se uma fonte não carregar
+    pub fn theme(&self, defaults: Theme) -> Result<Theme, Box<dyn Error>> {
+        let mut theme = defaults;
+        let style = &self.style;
+        if let Some(Color(color)) = style.text_color {
+            theme.text_color = color;
+        }
+        if let Some(Color(color)) = style.accent_color {
+            theme.accent_color = color;
+        }
+        if let Some(Color(color)) = style.background_color {
+            theme.background_color = color;
+        }
+        if let Some(font) = &style.font {
+            theme.font = load_font(font)?;
+        }
+        if let Some(font) = &style.bold_font {
+            theme.bold_font = load_font(font)?;
+        }
+        Ok(theme)
+    }
+}
+
+/// Multiplica o canal alfa de todos os pixels por `opacity`
+pub fn apply_opacity(image: &mut RgbaImage, opacity: f64) {
+    if opacity >= 1.0 {
+        return;
+    }
+    for pixel in image.pixels_mut() {
+        pixel.0[3] = (pixel.0[3] as f64 * opacity).round() as u8;
+    }
+}
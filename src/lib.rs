@@ -0,0 +1,71 @@
+//! Overlays de telemetria GPS em vídeos: leitura de trilhas, sincronização com o vídeo,
+//! desenho dos widgets e composição do vídeo final com o FFmpeg.
+//!
+//! A API pública está organizada por etapa:
+//!
+//! - **Trilhas**: [`track::read_track_file`] lê GPX, TCX, FIT, SRT da DJI ou a telemetria GPMF
+//!   de um vídeo GoPro e devolve um [`TrackFileData`]; [`utils::interpolate_gpx_points`]
+//!   adensa os pontos.
+//! - **Sincronização**: [`suggest::build_suggestion`] propõe o ponto da trilha que corresponde
+//!   ao início do vídeo, a partir da hora do vídeo ([`video_time`]), dos fusos ([`timezone`]) e,
+//!   opcionalmente, do movimento do vídeo ([`auto_sync`]); [`clock_sync`] corrige a deriva do
+//!   relógio com uma segunda âncora.
+//! - **Widgets**: [`drawing`] desenha o velocímetro, o mapa do percurso e o painel de
+//!   estatísticas em imagens RGBA; [`widget`] liga-os ao trait [`widget::OverlayWidget`] e a um
+//!   registo por nome, e [`layout`] define onde e como cada um aparece.
+//! - **Composição**: [`processing::run_processing`] gera o vídeo com os overlays escolhidos num
+//!   [`ProcessRequest`], publicando o progresso ([`progress`]) e respeitando o cancelamento
+//!   ([`cancellation`]); [`overlay_stream`] envia os frames ao FFmpeg.
+//!
+//! O servidor web (feature `server`) e a linha de comando (feature `cli`) são apenas
+//! consumidores desta API.
+
+pub mod drawing;
+pub mod processing;
+pub mod utils;
+pub mod tcx_adapter;
+pub mod fit_adapter;
+pub mod gpx_adapter;
+pub mod telemetry;
+pub mod gpmf;
+pub mod dji_srt;
+pub mod timezone;
+pub mod video_time;
+pub mod video_geometry;
+pub mod units;
+pub mod heart_rate;
+pub mod power;
+pub mod elevation_profile;
+pub mod mbtiles;
+pub mod basemap;
+pub mod athlete;
+pub mod auto_sync;
+pub mod clock_sync;
+pub mod overlay_stream;
+pub mod layout;
+pub mod widget;
+pub mod progress;
+pub mod cancellation;
+pub mod track;
+pub mod suggest;
+
+#[cfg(feature = "server")]
+pub mod jobs;
+#[cfg(feature = "server")]
+pub mod upload;
+#[cfg(feature = "server")]
+pub mod assets;
+#[cfg(feature = "server")]
+pub mod server;
+
+#[cfg(feature = "cli")]
+pub mod cli;
+
+pub use athlete::AthleteProfile;
+pub use cancellation::CancellationToken;
+pub use processing::{run_processing, ProcessRequest};
+pub use progress::{ProgressEvent, ProgressReporter};
+pub use suggest::{build_suggestion, SuggestionResponse};
+pub use timezone::TimezoneSettings;
+pub use track::{read_track_file, TrackFileData};
+pub use units::UnitSystem;
@@ -0,0 +1,574 @@
+// src/mbtiles.rs - Leitura dos tiles de um arquivo MBTiles (SQLite), só de leitura e sem rede
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const HEADER_SIZE: usize = 100;
+
+// Tipos de página da B-tree
+const INDEX_INTERIOR: u8 = 0x02;
+const TABLE_INTERIOR: u8 = 0x05;
+const INDEX_LEAF: u8 = 0x0a;
+const TABLE_LEAF: u8 = 0x0d;
+
+/// Um valor de uma linha do SQLite
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            Value::Real(value) => Some(*value as i64),
+            Value::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_text(&self) -> Option<String> {
+        match self {
+            Value::Text(text) => Some(text.clone()),
+            Value::Integer(value) => Some(value.to_string()),
+            Value::Real(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    fn into_blob(self) -> Option<Vec<u8>> {
+        match self {
+            Value::Blob(data) => Some(data),
+            Value::Text(text) => Some(text.into_bytes()),
+            _ => None,
+        }
+    }
+
+    /// Ordem do SQLite: NULL, números, texto e blobs (texto e blobs byte a byte)
+    fn compare(&self, other: &Value) -> Ordering {
+        let rank = |value: &Value| match value {
+            Value::Null => 0,
+            Value::Integer(_) | Value::Real(_) => 1,
+            Value::Text(_) => 2,
+            Value::Blob(_) => 3,
+        };
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Integer(_) | Value::Real(_), Value::Integer(_) | Value::Real(_)) => {
+                let number = |value: &Value| match value {
+                    Value::Integer(v) => *v as f64,
+                    Value::Real(v) => *v,
+                    _ => 0.0,
+                };
+                number(self).total_cmp(&number(other))
+            },
+            (Value::Text(a), Value::Text(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (Value::Blob(a), Value::Blob(b)) => a.cmp(b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+/// Tabela do esquema, com as colunas pela ordem da definição
+#[derive(Debug, Clone)]
+struct Table {
+    root: u32,
+    columns: Vec<String>,
+    /// Coluna `INTEGER PRIMARY KEY`: gravada como NULL, o valor é o rowid
+    rowid_alias: Option<usize>,
+}
+
+impl Table {
+    fn column(&self, name: &str) -> Result<usize, Box<dyn Error>> {
+        self.columns.iter().position(|c| c.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("MBTiles sem a coluna {}", name).into())
+    }
+}
+
+/// Índice cujas primeiras colunas são as procuradas, por ordem
+#[derive(Debug, Clone)]
+struct Index {
+    root: u32,
+    columns: Vec<String>,
+}
+
+/// Onde estão os tiles: na tabela `tiles` ou, nos arquivos com deduplicação, em `map` (posição
+/// e `tile_id`) e `images` (`tile_id` e `tile_data`), com `tiles` como vista
+#[derive(Debug, Clone)]
+enum TileLookup {
+    Table { tiles: Table, index: Option<Index> },
+    Deduplicated { map: Table, map_index: Option<Index>, images: Table, images_index: Option<Index> },
+}
+
+/// Arquivo SQLite aberto só para leitura, página a página
+struct Database {
+    file: File,
+    page_size: usize,
+    usable_size: usize,
+    /// Páginas no arquivo: limita o tamanho declarado de cada célula
+    page_count: u64,
+}
+
+/// Página da B-tree: tipo, posição de cada célula e, nas interiores, o filho mais à direita
+struct BtreePage {
+    kind: u8,
+    data: Vec<u8>,
+    cells: Vec<usize>,
+    right_child: u32,
+}
+
+/// Tiles e metadados de um MBTiles. Lê o formato do SQLite diretamente (B-trees de tabelas e
+/// índices, com páginas de overflow); um journal WAL ainda não aplicado é ignorado.
+pub struct MbtilesReader {
+    db: Database,
+    lookup: TileLookup,
+    metadata: BTreeMap<String, String>,
+}
+
+impl MbtilesReader {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut db = Database::open(path)
+            .map_err(|e| format!("Não foi possível abrir o MBTiles {}: {}", path.display(), e))?;
+        let (tables, indexes) = db.schema()?;
+
+        let index_on = |table: &str, columns: &[&str]| {
+            indexes.iter()
+                .find(|(indexed, index)| indexed.eq_ignore_ascii_case(table) && columns.iter().enumerate()
+                    .all(|(i, column)| index.columns.get(i).is_some_and(|c| c.eq_ignore_ascii_case(column))))
+                .map(|(_, index)| index.clone())
+        };
+        let lookup = match (tables.get("tiles"), tables.get("map"), tables.get("images")) {
+            (Some(tiles), _, _) => TileLookup::Table { tiles: tiles.clone(), index: index_on("tiles", &POSITION_COLUMNS) },
+            (None, Some(map), Some(images)) => TileLookup::Deduplicated {
+                map: map.clone(),
+                map_index: index_on("map", &POSITION_COLUMNS),
+                images: images.clone(),
+                images_index: index_on("images", &["tile_id"]),
+            },
+            _ => return Err(format!("{} não é um MBTiles: falta a tabela tiles", path.display()).into()),
+        };
+
+        let mut metadata = BTreeMap::new();
+        if let Some(table) = tables.get("metadata") {
+            let (name, value) = (table.column("name")?, table.column("value")?);
+            db.scan_table(table, &mut |row| {
+                if let (Some(name), Some(value)) = (row.get(name).and_then(Value::as_text), row.get(value).and_then(Value::as_text)) {
+                    metadata.insert(name, value);
+                }
+                Ok(true)
+            })?;
+        }
+        Ok(MbtilesReader { db, lookup, metadata })
+    }
+
+    /// Valor da tabela `metadata` (`attribution`, `minzoom`, `maxzoom`, `format`, ...)
+    pub fn metadata(&self, name: &str) -> Option<&str> {
+        self.metadata.get(name).map(String::as_str)
+    }
+
+    /// Zooms declarados em `minzoom` e `maxzoom`
+    pub fn zoom_range(&self) -> Option<(u8, u8)> {
+        let zoom = |name| self.metadata(name)?.trim().parse::<u8>().ok();
+        Some((zoom("minzoom")?, zoom("maxzoom")?))
+    }
+
+    /// Imagem (PNG, JPEG ou WebP) do tile `x`, `y` no esquema XYZ, com y a crescer para sul;
+    /// o MBTiles guarda as linhas no esquema TMS, a crescer para norte
+    pub fn tile(&mut self, z: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        if z > 30 || x >= 1 << z || y >= 1 << z {
+            return Ok(None);
+        }
+        let tms_row = (1_i64 << z) - 1 - y as i64;
+        let key = [Value::Integer(z as i64), Value::Integer(x as i64), Value::Integer(tms_row)];
+        match self.lookup.clone() {
+            TileLookup::Table { tiles, index } => {
+                let Some(row) = self.db.find_row(&tiles, index.as_ref(), &POSITION_COLUMNS, &key)? else { return Ok(None) };
+                Ok(row.into_iter().nth(tiles.column("tile_data")?).and_then(Value::into_blob))
+            },
+            TileLookup::Deduplicated { map, map_index, images, images_index } => {
+                let Some(row) = self.db.find_row(&map, map_index.as_ref(), &POSITION_COLUMNS, &key)? else { return Ok(None) };
+                let Some(tile_id) = row.into_iter().nth(map.column("tile_id")?) else { return Ok(None) };
+                let Some(image) = self.db.find_row(&images, images_index.as_ref(), &["tile_id"], &[tile_id])? else { return Ok(None) };
+                Ok(image.into_iter().nth(images.column("tile_data")?).and_then(Value::into_blob))
+            },
+        }
+    }
+}
+
+const POSITION_COLUMNS: [&str; 3] = ["zoom_level", "tile_column", "tile_row"];
+
+impl Database {
+    fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut file = File::open(path)?;
+        let mut header = [0u8; HEADER_SIZE];
+        file.read_exact(&mut header).map_err(|_| "arquivo demasiado curto para SQLite")?;
+        if &header[..16] != SQLITE_MAGIC {
+            return Err("não é um banco de dados SQLite".into());
+        }
+        let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+            1 => 65536,
+            size if size >= 512 && size.is_power_of_two() => size as usize,
+            size => return Err(format!("tamanho de página inválido: {}", size).into()),
+        };
+        if u32::from_be_bytes([header[56], header[57], header[58], header[59]]) > 1 {
+            return Err("só é suportado texto em UTF-8".into());
+        }
+        // O SQLite exige pelo menos 480 bytes úteis por página
+        let usable_size = page_size - header[20] as usize;
+        if usable_size < 480 {
+            return Err(format!("espaço útil por página inválido: {}", usable_size).into());
+        }
+        let page_count = file.metadata()?.len() / page_size as u64;
+        Ok(Database { file, page_size, usable_size, page_count })
+    }
+
+    /// Página `number` (a primeira é 1)
+    fn page(&mut self, number: u32) -> Result<Vec<u8>, Box<dyn Error>> {
+        if number == 0 || number as u64 > self.page_count {
+            return Err(format!("MBTiles corrompido: página {} fora do arquivo", number).into());
+        }
+        let mut data = vec![0u8; self.page_size];
+        self.file.seek(SeekFrom::Start((number as u64 - 1) * self.page_size as u64))?;
+        self.file.read_exact(&mut data).map_err(|_| format!("MBTiles corrompido: página {} fora do arquivo", number))?;
+        Ok(data)
+    }
+
+    fn btree_page(&mut self, number: u32) -> Result<BtreePage, Box<dyn Error>> {
+        let data = self.page(number)?;
+        // A primeira página começa com o cabeçalho do arquivo
+        let start = if number == 1 { HEADER_SIZE } else { 0 };
+        let kind = data[start];
+        let interior = match kind {
+            INDEX_INTERIOR | TABLE_INTERIOR => true,
+            INDEX_LEAF | TABLE_LEAF => false,
+            _ => return Err(format!("MBTiles corrompido: página {} do tipo {}", number, kind).into()),
+        };
+        let cell_count = read_u16(&data, start + 3)? as usize;
+        let right_child = if interior { read_u32(&data, start + 8)? } else { 0 };
+        let pointers = start + if interior { 12 } else { 8 };
+        let cells = (0..cell_count)
+            .map(|i| read_u16(&data, pointers + 2 * i).map(usize::from))
+            .collect::<Result<_, _>>()?;
+        Ok(BtreePage { kind, data, cells, right_child })
+    }
+
+    /// Conteúdo de uma célula com `size` bytes a partir de `offset`, seguindo as páginas de
+    /// overflow quando não cabe na página. Um tamanho maior que o arquivo ou uma cadeia de
+    /// overflow que repete uma página são erros, para um arquivo corrompido não esgotar a memória
+    /// nem ficar em ciclo.
+    fn payload(&mut self, page: &[u8], offset: usize, size: i64, index: bool) -> Result<Vec<u8>, Box<dyn Error>> {
+        if size < 0 || size as u64 > self.page_count.saturating_mul(self.page_size as u64) {
+            return Err(format!("MBTiles corrompido: célula de {} bytes", size).into());
+        }
+        let size = size as usize;
+        let usable = self.usable_size;
+        let max_local = if index { (usable - 12) * 64 / 255 - 23 } else { usable - 35 };
+        if size <= max_local {
+            return Ok(slice(page, offset, size)?.to_vec());
+        }
+        let min_local = (usable - 12) * 32 / 255 - 23;
+        let local = match min_local + (size - min_local) % (usable - 4) {
+            local if local <= max_local => local,
+            _ => min_local,
+        };
+        let mut data = slice(page, offset, local)?.to_vec();
+        let mut next = read_u32(page, offset + local)?;
+        let mut visited = HashSet::new();
+        while data.len() < size {
+            if next == 0 {
+                return Err("MBTiles corrompido: overflow incompleto".into());
+            }
+            if !visited.insert(next) {
+                return Err(format!("MBTiles corrompido: ciclo nas páginas de overflow (página {})", next).into());
+            }
+            let overflow = self.page(next)?;
+            next = read_u32(&overflow, 0)?;
+            let take = (size - data.len()).min(usable - 4);
+            data.extend_from_slice(slice(&overflow, 4, take)?);
+        }
+        Ok(data)
+    }
+
+    /// Tabelas e índices de `sqlite_master`, pelo nome (os índices com a tabela indexada)
+    #[allow(clippy::type_complexity)]
+    fn schema(&mut self) -> Result<(BTreeMap<String, Table>, Vec<(String, Index)>), Box<dyn Error>> {
+        let master = Table { root: 1, columns: Vec::new(), rowid_alias: None };
+        let mut tables = BTreeMap::new();
+        let mut indexes = Vec::new();
+        self.scan_table(&master, &mut |row| {
+            let text = |i: usize| row.get(i).and_then(Value::as_text).unwrap_or_default();
+            let root = row.get(3).and_then(Value::as_integer).unwrap_or(0) as u32;
+            let (kind, name, table, sql) = (text(0), text(1).to_lowercase(), text(2).to_lowercase(), text(4));
+            if root == 0 || sql.is_empty() {
+                return Ok(true);
+            }
+            let definitions = column_definitions(&sql);
+            match kind.as_str() {
+                "table" => {
+                    if sql.to_uppercase().contains("WITHOUT ROWID") {
+                        return Ok(true);
+                    }
+                    let rowid_alias = definitions.iter().position(|(_, definition)| {
+                        let words: Vec<String> = definition.split_whitespace().map(str::to_uppercase).collect();
+                        words.get(1).is_some_and(|t| t == "INTEGER") && words.windows(2).any(|w| w[0] == "PRIMARY" && w[1] == "KEY")
+                    });
+                    let columns = definitions.into_iter().map(|(column, _)| column).collect();
+                    tables.insert(name, Table { root, columns, rowid_alias });
+                },
+                "index" => indexes.push((table, Index { root, columns: definitions.into_iter().map(|(column, _)| column).collect() })),
+                _ => {},
+            }
+            Ok(true)
+        })?;
+        Ok((tables, indexes))
+    }
+
+    /// Chama `visit` com cada linha da tabela, até devolver `false`
+    fn scan_table(&mut self, table: &Table, visit: &mut dyn FnMut(Vec<Value>) -> Result<bool, Box<dyn Error>>) -> Result<bool, Box<dyn Error>> {
+        self.scan_page(table, table.root, visit, &mut HashSet::new())
+    }
+
+    /// `visited` guarda as páginas já percorridas: uma página repetida é um ciclo
+    fn scan_page(&mut self, table: &Table, number: u32, visit: &mut dyn FnMut(Vec<Value>) -> Result<bool, Box<dyn Error>>, visited: &mut HashSet<u32>) -> Result<bool, Box<dyn Error>> {
+        if !visited.insert(number) {
+            return Err(format!("MBTiles corrompido: ciclo na B-tree (página {})", number).into());
+        }
+        let page = self.btree_page(number)?;
+        match page.kind {
+            TABLE_INTERIOR => {
+                for &cell in &page.cells {
+                    if !self.scan_page(table, read_u32(&page.data, cell)?, visit, visited)? {
+                        return Ok(false);
+                    }
+                }
+                self.scan_page(table, page.right_child, visit, visited)
+            },
+            TABLE_LEAF => {
+                for &cell in &page.cells {
+                    if !visit(self.table_cell(table, &page.data, cell)?)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            },
+            _ => Err("MBTiles corrompido: índice no lugar de uma tabela".into()),
+        }
+    }
+
+    /// Valores de uma célula de folha de tabela
+    fn table_cell(&mut self, table: &Table, page: &[u8], cell: usize) -> Result<Vec<Value>, Box<dyn Error>> {
+        let (size, size_len) = read_varint(page, cell)?;
+        let (rowid, rowid_len) = read_varint(page, cell + size_len)?;
+        let payload = self.payload(page, cell + size_len + rowid_len, size, false)?;
+        let mut row = decode_record(&payload)?;
+        if let Some(alias) = table.rowid_alias {
+            if let Some(value @ Value::Null) = row.get_mut(alias) {
+                *value = Value::Integer(rowid);
+            }
+        }
+        Ok(row)
+    }
+
+    /// Linha com este `rowid`, descendo pela B-tree
+    fn table_row(&mut self, table: &Table, rowid: i64) -> Result<Option<Vec<Value>>, Box<dyn Error>> {
+        let mut number = table.root;
+        for _ in 0..32 {
+            let page = self.btree_page(number)?;
+            match page.kind {
+                TABLE_INTERIOR => {
+                    // Cada célula aponta para os rowids até à sua chave
+                    number = page.right_child;
+                    for &cell in &page.cells {
+                        if rowid <= read_varint(&page.data, cell + 4)?.0 {
+                            number = read_u32(&page.data, cell)?;
+                            break;
+                        }
+                    }
+                },
+                TABLE_LEAF => {
+                    for &cell in &page.cells {
+                        let size_len = read_varint(&page.data, cell)?.1;
+                        if read_varint(&page.data, cell + size_len)?.0 == rowid {
+                            return Ok(Some(self.table_cell(table, &page.data, cell)?));
+                        }
+                    }
+                    return Ok(None);
+                },
+                _ => return Err("MBTiles corrompido: índice no lugar de uma tabela".into()),
+            }
+        }
+        Err("MBTiles corrompido: B-tree demasiado profunda".into())
+    }
+
+    /// Entrada do índice cujas primeiras colunas são iguais a `key`; a última coluna é o rowid
+    fn index_entry(&mut self, index: &Index, key: &[Value]) -> Result<Option<Vec<Value>>, Box<dyn Error>> {
+        let matches = |entry: &[Value]| key.iter().enumerate()
+            .map(|(i, value)| entry.get(i).map_or(Ordering::Greater, |other| value.compare(other)))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal);
+        let mut number = index.root;
+        for _ in 0..32 {
+            let page = self.btree_page(number)?;
+            let interior = match page.kind {
+                INDEX_INTERIOR => true,
+                INDEX_LEAF => false,
+                _ => return Err("MBTiles corrompido: tabela no lugar de um índice".into()),
+            };
+            let header = if interior { 4 } else { 0 };
+            let mut next = page.right_child;
+            for &cell in &page.cells {
+                let (size, size_len) = read_varint(&page.data, cell + header)?;
+                let payload = self.payload(&page.data, cell + header + size_len, size, true)?;
+                let entry = decode_record(&payload)?;
+                match matches(&entry) {
+                    Ordering::Equal => return Ok(Some(entry)),
+                    Ordering::Less if interior => {
+                        next = read_u32(&page.data, cell)?;
+                        break;
+                    },
+                    Ordering::Less => return Ok(None),
+                    Ordering::Greater => {},
+                }
+            }
+            if !interior {
+                return Ok(None);
+            }
+            number = next;
+        }
+        Err("MBTiles corrompido: B-tree demasiado profunda".into())
+    }
+
+    /// Linha cujas `columns` valem `key`: pelo índice quando existe, senão percorrendo a tabela
+    fn find_row(&mut self, table: &Table, index: Option<&Index>, columns: &[&str], key: &[Value]) -> Result<Option<Vec<Value>>, Box<dyn Error>> {
+        if let Some(index) = index {
+            let Some(entry) = self.index_entry(index, key)? else { return Ok(None) };
+            let rowid = entry.last().and_then(Value::as_integer).ok_or("MBTiles corrompido: índice sem rowid")?;
+            return self.table_row(table, rowid);
+        }
+        let positions = columns.iter().map(|c| table.column(c)).collect::<Result<Vec<_>, _>>()?;
+        let mut found = None;
+        self.scan_table(table, &mut |row| {
+            let matches = positions.iter().zip(key)
+                .all(|(&i, value)| row.get(i).is_some_and(|v| v.compare(value) == Ordering::Equal));
+            if matches {
+                found = Some(row);
+            }
+            Ok(!matches)
+        })?;
+        Ok(found)
+    }
+}
+
+/// Valores de um registo: cabeçalho com o tipo de cada coluna, seguido dos dados
+fn decode_record(payload: &[u8]) -> Result<Vec<Value>, Box<dyn Error>> {
+    let (header_size, mut position) = read_varint(payload, 0)?;
+    let mut types = Vec::new();
+    while position < header_size as usize {
+        let (serial_type, len) = read_varint(payload, position)?;
+        types.push(serial_type);
+        position += len;
+    }
+    let mut offset = header_size as usize;
+    let mut values = Vec::with_capacity(types.len());
+    for serial_type in types {
+        let (value, len) = match serial_type {
+            0 => (Value::Null, 0),
+            1..=6 => {
+                let len = [1, 2, 3, 4, 6, 8][serial_type as usize - 1];
+                let bytes = slice(payload, offset, len)?;
+                let unsigned = bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+                let shift = 64 - 8 * len as u32;
+                (Value::Integer(((unsigned << shift) as i64) >> shift), len)
+            },
+            7 => {
+                let bytes: [u8; 8] = slice(payload, offset, 8)?.try_into()?;
+                (Value::Real(f64::from_bits(u64::from_be_bytes(bytes))), 8)
+            },
+            8 => (Value::Integer(0), 0),
+            9 => (Value::Integer(1), 0),
+            t if t >= 12 && t % 2 == 0 => {
+                let len = (t as usize - 12) / 2;
+                (Value::Blob(slice(payload, offset, len)?.to_vec()), len)
+            },
+            t if t >= 13 => {
+                let len = (t as usize - 13) / 2;
+                (Value::Text(String::from_utf8_lossy(slice(payload, offset, len)?).into_owned()), len)
+            },
+            t => return Err(format!("MBTiles corrompido: tipo de coluna {}", t).into()),
+        };
+        values.push(value);
+        offset += len;
+    }
+    Ok(values)
+}
+
+/// Nome e definição de cada coluna de um `CREATE TABLE` ou `CREATE INDEX`, sem as restrições
+/// da tabela (`PRIMARY KEY (...)`, `UNIQUE (...)`, ...)
+fn column_definitions(sql: &str) -> Vec<(String, String)> {
+    let (Some(start), Some(end)) = (sql.find('('), sql.rfind(')')) else { return Vec::new() };
+    if end <= start {
+        return Vec::new();
+    }
+    let mut definitions = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in sql[start + 1..end].chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                definitions.push(std::mem::take(&mut current));
+                continue;
+            },
+            _ => {},
+        }
+        current.push(c);
+    }
+    definitions.push(current);
+    definitions.into_iter()
+        .filter_map(|definition| {
+            let name = definition.split_whitespace().next()?
+                .trim_matches(|c| matches!(c, '"' | '`' | '[' | ']' | '\''))
+                .to_lowercase();
+            let constraint = ["primary", "unique", "check", "foreign", "constraint"].contains(&name.as_str());
+            (!constraint && !name.is_empty()).then(|| (name, definition.trim().to_string()))
+        })
+        .collect()
+}
+
+fn slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8], Box<dyn Error>> {
+    offset.checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| "MBTiles corrompido: dados fora da página".into())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Box<dyn Error>> {
+    Ok(u16::from_be_bytes(slice(data, offset, 2)?.try_into()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Box<dyn Error>> {
+    Ok(u32::from_be_bytes(slice(data, offset, 4)?.try_into()?))
+}
+
+/// Inteiro de 1 a 9 bytes do SQLite: 7 bits por byte, o nono com 8; devolve o valor e o tamanho
+fn read_varint(data: &[u8], offset: usize) -> Result<(i64, usize), Box<dyn Error>> {
+    let mut value = 0u64;
+    for i in 0..8 {
+        let byte = *data.get(offset + i).ok_or("MBTiles corrompido: inteiro fora da página")?;
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Ok((value as i64, i + 1));
+        }
+    }
+    let last = *data.get(offset + 8).ok_or("MBTiles corrompido: inteiro fora da página")?;
+    Ok((((value << 8) | last as u64) as i64, 9))
+}
@@ -0,0 +1,156 @@
+// src/overlay_stream.rs - Envio dos overlays ao FFmpeg como um único stream RGBA pelo stdin
+
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command as StdCommand, ExitStatus, Stdio};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use image::RgbaImage;
+use crate::cancellation::CancellationToken;
+use crate::progress::{EncodeProgressParser, ProgressEvent, ProgressReporter};
+
+/// Frames de overlay por segundo de vídeo. Os dados da trilha mudam no máximo uma vez por
+/// segundo (nível de interpolação 1), por isso 5 fps mantém o erro de tempo abaixo de 0,2 s.
+pub const OVERLAY_FPS: f64 = 5.0;
+/// Os dados de um ponto ficam visíveis até ao ponto seguinte; o último fica 1 s
+const LAST_FRAME_SECS: f64 = 1.0;
+/// Intervalo entre verificações do cancelamento enquanto o FFmpeg termina a codificação
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Processo do FFmpeg que sobrepõe ao vídeo os frames RGBA recebidos pelo stdin. Se for
+/// descartado antes de `finish` (por exemplo num erro a meio dos frames), o FFmpeg é terminado.
+pub struct OverlayStream {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    progress_reader: Option<JoinHandle<()>>,
+    /// O FFmpeg já terminou e foi esperado
+    exited: bool,
+}
+
+impl OverlayStream {
+    /// Inicia o FFmpeg; o progresso da codificação (`-progress pipe:1`) é lido numa thread
+    /// e publicado em `progress`, com a percentagem relativa a `duration_secs`
+    pub fn spawn(
+        video_path: &Path,
+        width: u32,
+        height: u32,
+        output_file: &Path,
+        duration_secs: f64,
+        progress: ProgressReporter,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut child = StdCommand::new("ffmpeg")
+            .args(ffmpeg_args(video_path, width, height, output_file))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take();
+        let progress_reader = child.stdout.take().map(|stdout| {
+            std::thread::spawn(move || {
+                let mut parser = EncodeProgressParser::new(duration_secs);
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if let Some(encode) = parser.feed(&line) {
+                        progress.report(ProgressEvent::Encode(encode));
+                    }
+                }
+            })
+        });
+        Ok(OverlayStream { child, stdin, progress_reader, exited: false })
+    }
+
+    /// Escreve um frame. Devolve `false` se o FFmpeg já terminou (pipe fechado).
+    pub fn write_frame(&mut self, frame: &RgbaImage) -> Result<bool, Box<dyn Error>> {
+        let Some(stdin) = self.stdin.as_mut() else { return Ok(false) };
+        match stdin.write_all(frame.as_raw()) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                self.stdin = None;
+                Ok(false)
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fecha o stdin (fim do stream de overlay) e espera o FFmpeg terminar o vídeo.
+    /// Devolve `None` se `cancel` for acionado entretanto; o FFmpeg é então terminado.
+    pub fn finish(mut self, cancel: &CancellationToken) -> Result<Option<ExitStatus>, Box<dyn Error>> {
+        drop(self.stdin.take());
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                self.exited = true;
+                self.join_progress_reader();
+                return Ok(Some(status));
+            }
+            if cancel.is_cancelled() {
+                self.terminate();
+                return Ok(None);
+            }
+            std::thread::sleep(CANCEL_POLL_INTERVAL);
+        }
+    }
+
+    /// Termina o FFmpeg sem esperar pelo fim do vídeo
+    pub fn kill(mut self) {
+        self.terminate();
+    }
+
+    fn terminate(&mut self) {
+        if self.exited {
+            return;
+        }
+        drop(self.stdin.take());
+        if let Err(e) = self.child.kill() {
+            tracing::debug!("Não foi possível terminar o FFmpeg: {}", e);
+        }
+        self.child.wait().ok();
+        self.exited = true;
+        self.join_progress_reader();
+    }
+
+    fn join_progress_reader(&mut self) {
+        if let Some(reader) = self.progress_reader.take() {
+            reader.join().ok();
+        }
+    }
+}
+
+impl Drop for OverlayStream {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+/// Argumentos do FFmpeg: o vídeo original é a entrada 0 e o overlay em rawvideo RGBA a
+/// `OVERLAY_FPS` chega pelo stdin como entrada 1, composto com um único filtro `overlay`.
+pub fn ffmpeg_args(video_path: &Path, width: u32, height: u32, output_file: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-y", "-progress", "pipe:1", "-nostats", "-i"].map(String::from).to_vec();
+    args.push(video_path.to_string_lossy().into_owned());
+    args.extend([
+        "-f", "rawvideo",
+        "-pixel_format", "rgba",
+        "-video_size", &format!("{}x{}", width, height),
+        "-framerate", &OVERLAY_FPS.to_string(),
+        "-i", "pipe:0",
+        "-filter_complex", "[0:v][1:v]overlay=0:0:eof_action=pass[out]",
+        "-map", "[out]",
+        "-map", "0:a?",
+        "-c:a", "copy",
+        "-map_metadata", "0",
+        "-movflags", "use_metadata_tags",
+    ].map(String::from));
+    args.push(output_file.to_string_lossy().into_owned());
+    args
+}
+
+/// Número de frames de overlay necessários para cobrir `duration_secs` de vídeo
+pub fn frame_count(duration_secs: f64) -> usize {
+    (duration_secs.max(0.0) * OVERLAY_FPS).ceil() as usize
+}
+
+/// Índice do ponto visível no instante `time_secs`, dados os instantes (ordenados) em que
+/// cada ponto aparece no vídeo
+pub fn active_frame(timestamps: &[f64], time_secs: f64) -> Option<usize> {
+    let index = timestamps.partition_point(|&ts| ts <= time_secs).checked_sub(1)?;
+    let end = timestamps.get(index + 1).copied().unwrap_or(timestamps[index] + LAST_FRAME_SECS);
+    (time_secs < end).then_some(index)
+}
@@ -0,0 +1,160 @@
+// src/power.rs - Potência do ciclismo: médias móveis, Normalized Power, IF e TSS
+
+use std::collections::VecDeque;
+use chrono::{DateTime, Duration, Utc};
+use gpx::Gpx;
+use serde::Serialize;
+use crate::telemetry::TelemetrySample;
+
+/// Intervalos sem leitura até este limite repetem a última potência; os maiores (pausas da
+/// gravação) ficam fora da série
+const MAX_HOLD_SECS: i64 = 10;
+/// Janela da média móvel do Normalized Power
+const NP_WINDOW_SECS: usize = 30;
+/// Escala do mostrador de potência sem FTP configurado, em watts
+pub const DEFAULT_POWER_SCALE_W: f64 = 1000.0;
+
+/// Potência segundo a segundo: as leituras do medidor (em qualquer cadência) levadas a 1 Hz
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PowerSeries {
+    seconds: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl PowerSeries {
+    /// Série da telemetria de uma trilha, paralela aos pontos de `gpx` (antes da interpolação)
+    pub fn from_track(gpx: &Gpx, telemetry: &[TelemetrySample]) -> Self {
+        let samples = gpx.tracks.iter()
+            .flat_map(|t| t.segments.iter())
+            .flat_map(|s| s.points.iter())
+            .zip(telemetry)
+            .filter_map(|(point, sample)| {
+                let time = point.time.as_ref()?.format().ok()?.parse::<DateTime<Utc>>().ok()?;
+                Some((time, sample.power?))
+            });
+        PowerSeries::from_samples(samples)
+    }
+
+    /// Série a partir de leituras por ordem de tempo; cada leitura vale até à seguinte
+    pub fn from_samples(samples: impl IntoIterator<Item = (DateTime<Utc>, f64)>) -> Self {
+        let mut seconds: Vec<(DateTime<Utc>, f64)> = Vec::new();
+        let mut previous: Option<(DateTime<Utc>, f64)> = None;
+        for (time, watts) in samples {
+            if let Some((last_time, last_watts)) = previous {
+                let gap = (time - last_time).num_seconds();
+                if gap <= 0 {
+                    continue;
+                }
+                if gap <= MAX_HOLD_SECS {
+                    seconds.extend((1..gap).map(|s| (last_time + Duration::seconds(s), last_watts)));
+                }
+            }
+            seconds.push((time, watts.max(0.0)));
+            previous = Some((time, watts));
+        }
+        PowerSeries { seconds }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seconds.is_empty()
+    }
+
+    /// Média dos segundos em (`time` - `window_secs`, `time`]; `None` sem leituras na janela
+    pub fn average(&self, time: DateTime<Utc>, window_secs: i64) -> Option<f64> {
+        let window: Vec<f64> = self.watts_between(time - Duration::seconds(window_secs), time).collect();
+        (!window.is_empty()).then(|| window.iter().sum::<f64>() / window.len() as f64)
+    }
+
+    /// Potência de cada segundo em (`from`, `to`]
+    pub fn watts_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> impl Iterator<Item = f64> + '_ {
+        let start = self.seconds.partition_point(|(t, _)| *t <= from);
+        let end = self.seconds.partition_point(|(t, _)| *t <= to);
+        self.seconds[start..end.max(start)].iter().map(|(_, watts)| *watts)
+    }
+
+    /// Resumo da atividade inteira
+    pub fn summary(&self, ftp: Option<f64>) -> Option<PowerSummary> {
+        let mut totals = PowerTotals::default();
+        self.seconds.iter().for_each(|(_, watts)| totals.push(*watts));
+        totals.summary(ftp)
+    }
+}
+
+/// Acumulador de potência segundo a segundo, para os valores até um instante da trilha
+#[derive(Debug, Clone, Default)]
+pub struct PowerTotals {
+    seconds: u64,
+    sum: f64,
+    max: f64,
+    /// Últimos 30 s, para a média móvel do Normalized Power
+    window: VecDeque<f64>,
+    window_sum: f64,
+    /// Soma das quartas potências das médias móveis e quantas são
+    rolling_fourth_sum: f64,
+    rolling_count: u64,
+}
+
+impl PowerTotals {
+    pub fn push(&mut self, watts: f64) {
+        self.seconds += 1;
+        self.sum += watts;
+        self.max = self.max.max(watts);
+        self.window.push_back(watts);
+        self.window_sum += watts;
+        if self.window.len() > NP_WINDOW_SECS {
+            self.window_sum -= self.window.pop_front().unwrap_or_default();
+        }
+        if self.window.len() == NP_WINDOW_SECS {
+            self.rolling_fourth_sum += (self.window_sum / NP_WINDOW_SECS as f64).powi(4);
+            self.rolling_count += 1;
+        }
+    }
+
+    /// `None` antes do primeiro segundo; o NP (e com ele o IF e o TSS) só existe a partir de
+    /// 30 s, e o IF e o TSS só com FTP
+    pub fn summary(&self, ftp: Option<f64>) -> Option<PowerSummary> {
+        if self.seconds == 0 {
+            return None;
+        }
+        let normalized_w = (self.rolling_count > 0).then(|| (self.rolling_fourth_sum / self.rolling_count as f64).powf(0.25));
+        let intensity_factor = normalized_w.zip(ftp).map(|(np, ftp)| np / ftp);
+        let duration_secs = self.seconds as f64;
+        Some(PowerSummary {
+            average_w: self.sum / duration_secs,
+            max_w: self.max,
+            normalized_w,
+            intensity_factor,
+            training_stress_score: intensity_factor.map(|intensity| duration_secs / 3600.0 * intensity * intensity * 100.0),
+            duration_secs,
+        })
+    }
+}
+
+/// Potência média e máxima, Normalized Power, Intensity Factor (NP / FTP) e Training Stress
+/// Score (horas × IF² × 100)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PowerSummary {
+    pub average_w: f64,
+    pub max_w: f64,
+    pub normalized_w: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    pub training_stress_score: Option<f64>,
+    pub duration_secs: f64,
+}
+
+impl PowerSummary {
+    /// "NP 230 W · IF 0.85 · TSS 45", com o que estiver disponível
+    pub fn describe(&self) -> String {
+        let mut parts = vec![match self.normalized_w {
+            Some(np) => format!("NP {:.0} W", np),
+            None => format!("AVG {:.0} W", self.average_w),
+        }];
+        parts.extend(self.intensity_factor.map(|intensity| format!("IF {:.2}", intensity)));
+        parts.extend(self.training_stress_score.map(|tss| format!("TSS {:.0}", tss)));
+        parts.join(" · ")
+    }
+}
+
+/// Máximo do mostrador de potência: 150% do FTP arredondado a 100 W, ou `DEFAULT_POWER_SCALE_W`
+pub fn gauge_max(ftp: Option<f64>) -> f64 {
+    ftp.map_or(DEFAULT_POWER_SCALE_W, |ftp| (ftp * 1.5 / 100.0).ceil() * 100.0)
+}
@@ -0,0 +1,130 @@
+// src/progress.rs - Eventos de progresso de um processamento (logs, frames e codificação do FFmpeg)
+
+use std::sync::Arc;
+use serde::Serialize;
+
+/// Frames de overlay já enviados ao FFmpeg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FrameProgress {
+    pub current: usize,
+    pub total: usize,
+}
+
+/// Progresso da codificação lido da saída `-progress` do FFmpeg
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct EncodeProgress {
+    /// Segundos do vídeo final já codificados
+    pub out_time_secs: f64,
+    pub fps: Option<f64>,
+    /// Velocidade em relação ao tempo real (1.0 = tempo real)
+    pub speed: Option<f64>,
+    /// 0 a 100, quando a duração do vídeo é conhecida
+    pub percent: Option<f64>,
+    /// Segundos estimados até ao fim
+    pub eta_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    Log { message: String },
+    Frames(FrameProgress),
+    Encode(EncodeProgress),
+}
+
+impl ProgressEvent {
+    /// Nome do evento no stream SSE
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgressEvent::Log { .. } => "log",
+            ProgressEvent::Frames(_) => "frames",
+            ProgressEvent::Encode(_) => "encode",
+        }
+    }
+}
+
+/// Destino dos eventos de progresso; sem destino (`default`) os eventos são descartados
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    sink: Option<Arc<dyn Fn(ProgressEvent) + Send + Sync>>,
+}
+
+impl ProgressReporter {
+    pub fn new(sink: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Self {
+        ProgressReporter { sink: Some(Arc::new(sink)) }
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        if let Some(sink) = &self.sink {
+            sink(event);
+        }
+    }
+}
+
+/// Logs do processamento: guarda as linhas e publica cada uma assim que é escrita
+pub struct ProgressLog {
+    lines: Vec<String>,
+    reporter: ProgressReporter,
+}
+
+impl ProgressLog {
+    pub fn new(reporter: ProgressReporter) -> Self {
+        ProgressLog { lines: Vec::new(), reporter }
+    }
+
+    pub fn push(&mut self, line: String) {
+        self.reporter.report(ProgressEvent::Log { message: line.clone() });
+        self.lines.push(line);
+    }
+
+    pub fn reporter(&self) -> &ProgressReporter {
+        &self.reporter
+    }
+
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines
+    }
+}
+
+/// Interpreta a saída de `ffmpeg -progress pipe:1`: blocos de linhas `chave=valor`
+/// terminados por `progress=continue` ou `progress=end`.
+pub struct EncodeProgressParser {
+    current: EncodeProgress,
+    duration_secs: f64,
+}
+
+impl EncodeProgressParser {
+    /// `duration_secs` é a duração do vídeo final, usada para a percentagem e o ETA
+    pub fn new(duration_secs: f64) -> Self {
+        EncodeProgressParser { current: EncodeProgress::default(), duration_secs }
+    }
+
+    /// Lê uma linha; devolve o progresso quando ela fecha um bloco
+    pub fn feed(&mut self, line: &str) -> Option<EncodeProgress> {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim();
+        match key {
+            // Apesar do nome, `out_time_ms` também está em microssegundos
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(micros) = value.parse::<i64>() {
+                    self.current.out_time_secs = micros.max(0) as f64 / 1_000_000.0;
+                }
+            },
+            "fps" => self.current.fps = value.parse().ok(),
+            "speed" => self.current.speed = value.trim_end_matches('x').trim().parse().ok().filter(|s: &f64| *s > 0.0),
+            "progress" => return Some(self.finish_block(value == "end")),
+            _ => {},
+        }
+        None
+    }
+
+    fn finish_block(&mut self, ended: bool) -> EncodeProgress {
+        if self.duration_secs > 0.0 {
+            let done = if ended { self.duration_secs } else { self.current.out_time_secs.min(self.duration_secs) };
+            let remaining = self.duration_secs - done;
+            self.current.percent = Some(done / self.duration_secs * 100.0);
+            self.current.eta_secs = self.current.speed.map(|speed| remaining / speed);
+        }
+        self.current
+    }
+}
@@ -0,0 +1,501 @@
+// src/server.rs - Servidor web: interface do navegador, uploads, jobs e sugestão de sincronização
+
+use axum::{
+    extract::{DefaultBodyLimit, FromRef, Multipart, Path as UrlPath, State},
+    http::StatusCode,
+    response::{sse::{Event, KeepAlive, Sse}, IntoResponse, Json},
+    routing::{get, post},
+    Router,
+};
+use serde::Serialize;
+use futures_util::stream::{self, StreamExt};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_http::services::ServeDir;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::assets::{self, AssetStore};
+use crate::athlete::AthleteProfile;
+use crate::clock_sync::SyncAnchor;
+use crate::jobs::{self, CancelOutcome, JobEvent, JobPaths, JobQueue};
+use crate::layout::{Layout, LayoutResources, PRESETS};
+use crate::processing::{self, ProcessRequest};
+use crate::suggest::{build_suggestion, SuggestionResponse};
+use crate::timezone::TimezoneSettings;
+use crate::units::UnitSystem;
+use crate::upload::{self, UploadError};
+
+/// Inicia o servidor em `addr` com a fila de jobs e os assets configurados pelas variáveis
+/// de ambiente, e serve até o processo terminar
+pub async fn serve(addr: SocketAddr) -> std::io::Result<()> {
+    let state = AppState {
+        jobs: Arc::new(JobQueue::from_env()),
+        assets: Arc::new(AssetStore::from_env()),
+        layout_resources: Arc::new(LayoutResources::from_env()),
+    };
+    assets::spawn_janitor(Arc::clone(&state.assets));
+    jobs::spawn_janitor(Arc::clone(&state.jobs));
+
+    tracing::debug!("A escutar em {}", addr);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, router(state)).await
+}
+
+/// Rotas da API, arquivos estáticos da interface (`static/`) e vídeos gerados (`/output`)
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/uploads", post(upload_asset))
+        .route("/uploads/:id", get(get_asset))
+        .route("/process", post(process_files))
+        .route("/jobs/:id", get(get_job).delete(cancel_job))
+        .route("/jobs/:id/events", get(job_events))
+        .route("/suggest", post(suggest_sync_point))
+        .route("/layouts", get(list_layouts))
+        .nest_service("/", ServeDir::new("static"))
+        .nest_service("/output", ServeDir::new(jobs::OUTPUT_DIR))
+        .layer(DefaultBodyLimit::max(upload::MAX_BODY_BYTES as usize))
+        .with_state(state)
+}
+
+/// Estado partilhado pelos handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub jobs: Arc<JobQueue>,
+    pub assets: Arc<AssetStore>,
+    /// Diretórios de onde os layouts enviados pelos clientes podem ler fontes e mapas base
+    pub layout_resources: Arc<LayoutResources>,
+}
+
+impl FromRef<AppState> for Arc<JobQueue> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.jobs)
+    }
+}
+
+impl FromRef<AppState> for Arc<AssetStore> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.assets)
+    }
+}
+
+impl FromRef<AppState> for Arc<LayoutResources> {
+    fn from_ref(state: &AppState) -> Self {
+        Arc::clone(&state.layout_resources)
+    }
+}
+
+#[derive(Serialize)]
+struct ProcessResponse {
+    message: String,
+    job_id: Option<Uuid>,
+    status_url: Option<String>,
+    events_url: Option<String>,
+}
+
+impl ProcessResponse {
+    fn error(message: impl Into<String>) -> Self {
+        ProcessResponse { message: message.into(), job_id: None, status_url: None, events_url: None }
+    }
+}
+
+
+#[derive(Debug, Default)]
+struct ProcessParams {
+    track_file_path: Option<PathBuf>,
+    video_path: Option<PathBuf>,
+    sync_timestamp: Option<String>,
+    add_speedo_overlay: bool,
+    speedo_position: Option<String>,
+    add_track_overlay: bool,
+    track_position: Option<String>,
+    add_stats_overlay: bool,
+    stats_position: Option<String>,
+    /// Documento de layout (JSON ou TOML); tem prioridade sobre `layout_preset` e os cantos
+    layout: Option<String>,
+    layout_preset: Option<String>,
+    lang: String,
+    units: UnitSystem,
+    hr_zone_method: Option<String>,
+    max_heart_rate: Option<String>,
+    resting_heart_rate: Option<String>,
+    lthr: Option<String>,
+    ftp: Option<String>,
+    weight_kg: Option<String>,
+    interpolation_level: i64,
+    camera_timezone: Option<String>,
+    display_timezone: Option<String>,
+    video_last_modified: Option<i64>,
+    second_sync_video_time: Option<String>,
+    second_sync_timestamp: Option<String>,
+}
+
+
+/// Aplica ao arquivo enviado a data de modificação original (`File.lastModified` do navegador),
+/// usada como último recurso para a hora de início do vídeo.
+fn restore_modified_time(path: &Path, last_modified_ms: i64) {
+    let Some(modified) = DateTime::<Utc>::from_timestamp_millis(last_modified_ms) else { return };
+    if let Ok(file) = std::fs::File::options().write(true).open(path) {
+        if let Err(e) = file.set_modified(modified.into()) {
+            tracing::debug!("Não foi possível restaurar a data de modificação de {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Guarda um arquivo (campo `file`) para ser usado por ID em `/suggest` e `/process`,
+/// sem o enviar de novo. O campo opcional `lastModified` é a data original do arquivo.
+async fn upload_asset(State(assets): State<Arc<AssetStore>>, mut multipart: Multipart) -> impl IntoResponse {
+    let mut stored = None;
+    let mut last_modified: Option<i64> = None;
+
+    let result: Result<(), UploadError> = async {
+        let dir = assets.dir()?.to_path_buf();
+        while let Some(field) = upload::next_field(&mut multipart).await? {
+            let name = field.name().unwrap_or_default().to_string();
+            if name == "file" && field.file_name().is_some() {
+                stored = Some(upload::save_field(field, &dir).await?);
+            } else if name == "lastModified" {
+                last_modified = upload::read_text(field).await?.parse().ok();
+            }
+        }
+        Ok(())
+    }.await;
+
+    if let Err(e) = result {
+        if let Some(upload) = &stored {
+            std::fs::remove_file(&upload.path).ok();
+        }
+        return (e.status(), Json(ProcessResponse::error(e.to_string()))).into_response();
+    }
+    let Some(upload) = stored else {
+        return (StatusCode::BAD_REQUEST, Json(ProcessResponse::error("Erro: Arquivo em falta."))).into_response();
+    };
+
+    if let Some(last_modified) = last_modified {
+        restore_modified_time(&upload.path, last_modified);
+    }
+    let asset = assets.register(upload);
+    tracing::debug!("Asset {} guardado: {} ({} bytes, SHA-256 {})", asset.asset_id, asset.file_name, asset.size, asset.sha256);
+    (StatusCode::CREATED, Json(asset)).into_response()
+}
+
+async fn get_asset(State(assets): State<Arc<AssetStore>>, UrlPath(id): UrlPath<Uuid>) -> impl IntoResponse {
+    match assets.get(id) {
+        Some(asset) => (StatusCode::OK, Json(asset)).into_response(),
+        None => {
+            let error = UploadError::UnknownAsset(id);
+            (error.status(), Json(ProcessResponse::error(error.to_string()))).into_response()
+        }
+    }
+}
+
+async fn process_files(
+    State(queue): State<Arc<JobQueue>>,
+    State(assets): State<Arc<AssetStore>>,
+    State(layout_resources): State<Arc<LayoutResources>>,
+    multipart: Multipart,
+) -> impl IntoResponse {
+    // Cada job recebe os uploads num diretório próprio, apagado quando termina
+    let paths = match queue.prepare() {
+        Ok(paths) => paths,
+        Err(e) => {
+            let response = ProcessResponse::error(format!("Não foi possível criar o diretório do job: {}", e));
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
+
+    match read_process_request(multipart, &paths, &assets, &layout_resources).await {
+        Ok(request) => {
+            let job_id = queue.submit(paths, move |_, progress, cancel| processing::run_processing(request, progress, cancel));
+            let response = ProcessResponse {
+                message: "Processamento em fila.".to_string(),
+                job_id: Some(job_id),
+                status_url: Some(format!("/jobs/{}", job_id)),
+                events_url: Some(format!("/jobs/{}/events", job_id)),
+            };
+            (StatusCode::ACCEPTED, Json(response))
+        }
+        Err(e) => {
+            queue.discard(&paths);
+            (e.status(), Json(ProcessResponse::error(e.to_string())))
+        }
+    }
+}
+
+/// Lê o formulário de `/process`, grava os arquivos (ou liga os assets indicados) no
+/// diretório do job e valida os parâmetros
+async fn read_process_request(mut multipart: Multipart, paths: &JobPaths, assets: &AssetStore, layout_resources: &LayoutResources) -> Result<ProcessRequest, UploadError> {
+    let mut params = ProcessParams {
+        interpolation_level: 1,
+        ..Default::default()
+    };
+
+    while let Some(field) = upload::next_field(&mut multipart).await? {
+        let name = field.name().unwrap_or_default().to_string();
+        
+        // Os arquivos vão para o disco parte a parte, sem passar inteiros pela memória
+        if field.file_name().is_some() {
+            let stored = upload::save_field(field, &paths.work_dir).await?;
+            tracing::debug!("Job {}: {} recebido ({} bytes, SHA-256 {})", paths.id, stored.file_name, stored.size, stored.sha256);
+
+            if name == "gpxFile" {
+                params.track_file_path = Some(stored.path);
+            } else if name == "videoFile" {
+                params.video_path = Some(stored.path);
+            }
+        } else {
+            let value = upload::read_text(field).await?;
+            
+            match name.as_str() {
+                "syncTimestamp" => params.sync_timestamp = Some(value),
+                "addSpeedoOverlay" => params.add_speedo_overlay = value.parse().unwrap_or(false),
+                "speedoPosition" => params.speedo_position = Some(value),
+                "addTrackOverlay" => params.add_track_overlay = value.parse().unwrap_or(false),
+                "trackPosition" => params.track_position = Some(value),
+                "addStatsOverlay" => params.add_stats_overlay = value.parse().unwrap_or(false),
+                "statsPosition" => params.stats_position = Some(value),
+                "layout" if !value.trim().is_empty() => params.layout = Some(value),
+                "layoutPreset" if !value.trim().is_empty() => params.layout_preset = Some(value),
+                "lang" => params.lang = value,
+                "units" if !value.trim().is_empty() => params.units = value.parse().map_err(UploadError::Invalid)?,
+                "hrZoneMethod" => params.hr_zone_method = Some(value),
+                "maxHeartRate" => params.max_heart_rate = Some(value),
+                "restingHeartRate" => params.resting_heart_rate = Some(value),
+                "lthr" => params.lthr = Some(value),
+                "ftp" => params.ftp = Some(value),
+                "weightKg" => params.weight_kg = Some(value),
+                "interpolationLevel" => params.interpolation_level = value.parse().unwrap_or(1),
+                "cameraTimezone" => params.camera_timezone = Some(value),
+                "displayTimezone" => params.display_timezone = Some(value),
+                "videoLastModified" => params.video_last_modified = value.parse().ok(),
+                "secondSyncVideoTime" => params.second_sync_video_time = Some(value),
+                "secondSyncTimestamp" => params.second_sync_timestamp = Some(value),
+                "gpxAssetId" => params.track_file_path = Some(assets.resolve_into(&value, &paths.work_dir)?),
+                "videoAssetId" => params.video_path = Some(assets.resolve_into(&value, &paths.work_dir)?),
+                _ => {}
+            }
+        }
+    }
+
+    if let (Some(video), Some(last_modified)) = (&params.video_path, params.video_last_modified) {
+        restore_modified_time(video, last_modified);
+    }
+
+    let timezones = TimezoneSettings::from_params(params.camera_timezone.as_deref(), params.display_timezone.as_deref())
+        .map_err(|e| UploadError::Invalid(e.to_string()))?;
+
+    let athlete = AthleteProfile::from_params(
+        params.hr_zone_method.as_deref(),
+        params.max_heart_rate.as_deref(),
+        params.resting_heart_rate.as_deref(),
+        params.lthr.as_deref(),
+        params.ftp.as_deref(),
+        params.weight_kg.as_deref(),
+    ).map_err(UploadError::Invalid)?;
+
+    // Segunda âncora opcional (tempo do vídeo + hora da trilha) para corrigir a deriva do relógio
+    let second_anchor = match (params.second_sync_video_time.as_deref(), params.second_sync_timestamp.as_deref()) {
+        (Some(video_time), Some(timestamp)) => Some(SyncAnchor::parse(video_time, timestamp).map_err(UploadError::Invalid)?),
+        (None, None) => None,
+        _ => return Err(UploadError::Invalid("A segunda âncora precisa do tempo do vídeo e da hora da trilha.".to_string())),
+    };
+
+    let layout = match (params.layout, params.layout_preset) {
+        (Some(document), _) => {
+            let mut layout = Layout::parse(&document).map_err(|e| UploadError::Invalid(e.to_string()))?;
+            // Os caminhos do layout vêm do cliente: só nos diretórios configurados
+            layout.confine(layout_resources).map_err(UploadError::Invalid)?;
+            layout
+        },
+        (None, Some(name)) => Layout::preset(&name).ok_or_else(|| UploadError::Invalid(format!("Layout desconhecido: {}", name)))?,
+        // Formulário antigo: um canto por widget
+        (None, None) => Layout::from_corners(
+            params.add_speedo_overlay.then(|| params.speedo_position.as_deref().unwrap_or_default()),
+            params.add_track_overlay.then(|| params.track_position.as_deref().unwrap_or_default()),
+            params.add_stats_overlay.then(|| params.stats_position.as_deref().unwrap_or_default()),
+        ),
+    };
+
+    // Sem arquivo de trilha, a telemetria GPMF do próprio vídeo (GoPro) é usada como trilha
+    let track_file_path = params.track_file_path.or_else(|| params.video_path.clone());
+
+    let (Some(track_file_path), Some(video_path), Some(sync_timestamp)) = (track_file_path, params.video_path, params.sync_timestamp) else {
+        return Err(UploadError::Invalid("Erro: Arquivos ou ponto de sincronização em falta.".to_string()));
+    };
+
+    Ok(ProcessRequest {
+        track_file_path,
+        video_path,
+        sync_timestamp,
+        second_anchor,
+        layout,
+        lang: params.lang,
+        units: params.units,
+        athlete,
+        interpolation_level: params.interpolation_level,
+        timezones,
+        output_file: paths.output_file.clone(),
+    })
+}
+
+#[derive(Serialize)]
+struct LayoutPreset {
+    name: &'static str,
+    layout: Layout,
+}
+
+/// Layouts incluídos, para a interface os oferecer como alternativa aos cantos
+async fn list_layouts() -> impl IntoResponse {
+    let presets: Vec<LayoutPreset> = PRESETS.iter()
+        .filter_map(|&(name, _)| Some(LayoutPreset { name, layout: Layout::preset(name)? }))
+        .collect();
+    Json(presets)
+}
+
+async fn get_job(State(queue): State<Arc<JobQueue>>, UrlPath(id): UrlPath<Uuid>) -> impl IntoResponse {
+    match queue.status(id) {
+        Some(status) => (StatusCode::OK, Json(status)).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(ProcessResponse::error("Job não encontrado."))).into_response(),
+    }
+}
+
+/// Cancela o job: termina o FFmpeg, apaga os arquivos do job e marca-o como `cancelled`
+async fn cancel_job(State(queue): State<Arc<JobQueue>>, UrlPath(id): UrlPath<Uuid>) -> impl IntoResponse {
+    match queue.cancel(id) {
+        Some(CancelOutcome::Cancelled(status)) => (StatusCode::OK, Json(status)).into_response(),
+        Some(CancelOutcome::Cancelling(status)) => (StatusCode::ACCEPTED, Json(status)).into_response(),
+        Some(CancelOutcome::AlreadyFinished(status)) => (StatusCode::CONFLICT, Json(status)).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(ProcessResponse::error("Job não encontrado."))).into_response(),
+    }
+}
+
+/// Stream SSE do job: o estado e os logs já existentes e depois cada log, o progresso dos
+/// frames e da codificação e as mudanças de estado; termina quando o job termina
+async fn job_events(State(queue): State<Arc<JobQueue>>, UrlPath(id): UrlPath<Uuid>) -> impl IntoResponse {
+    let Some(subscription) = queue.subscribe(id) else {
+        return (StatusCode::NOT_FOUND, Json(ProcessResponse::error("Job não encontrado."))).into_response();
+    };
+
+    let live = stream::unfold(subscription.receiver, |receiver| async move {
+        let mut receiver = receiver?;
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let finished = matches!(&event, JobEvent::State(change) if change.state.is_finished());
+                    return Some((event, (!finished).then_some(receiver)));
+                },
+                // Ouvinte lento: os eventos perdidos são ignorados e o stream continua
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    let events = stream::iter(subscription.history).chain(live).map(sse_event);
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+fn sse_event(event: JobEvent) -> Result<Event, Infallible> {
+    let sse = match &event {
+        JobEvent::State(change) => Event::default().event("state").json_data(change),
+        JobEvent::Progress(progress) => Event::default().event(progress.name()).json_data(progress),
+    };
+    Ok(sse.unwrap_or_else(|e| Event::default().event("error").data(e.to_string())))
+}
+
+async fn suggest_sync_point(State(assets): State<Arc<AssetStore>>, mut multipart: Multipart) -> impl IntoResponse {
+    let mut track_file_path: Option<PathBuf> = None;
+    let mut video_path: Option<PathBuf> = None;
+    let mut interpolation_level: i64 = 1;
+    let mut camera_timezone: Option<String> = None;
+    let mut display_timezone: Option<String> = None;
+    let mut video_last_modified: Option<i64> = None;
+    let mut auto_sync_requested = false;
+    let mut units = UnitSystem::default();
+    // Só o FTP e o peso entram no resumo da atividade
+    let mut ftp: Option<String> = None;
+    let mut weight_kg: Option<String> = None;
+
+    // Diretório próprio de cada pedido, para sugestões simultâneas não apagarem os arquivos umas das outras
+    let upload_dir = PathBuf::from("uploads_temp_suggest").join(Uuid::new_v4().to_string());
+    if let Err(e) = tokio::fs::create_dir_all(&upload_dir).await {
+        let error = UploadError::Io(e);
+        return (error.status(), Json(SuggestionResponse::error(error.to_string())));
+    }
+
+    loop {
+        let field = match upload::next_field(&mut multipart).await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = tokio::fs::remove_dir_all(&upload_dir).await;
+                return (e.status(), Json(SuggestionResponse::error(e.to_string())));
+            }
+        };
+        let name = field.name().unwrap_or("").to_string();
+        
+        let result = if field.file_name().is_some() {
+            upload::save_field(field, &upload_dir).await.map(|stored| {
+                tracing::debug!("Sugestão: {} recebido ({} bytes, SHA-256 {})", stored.file_name, stored.size, stored.sha256);
+                if name == "gpxFile" { track_file_path = Some(stored.path); }
+                else if name == "videoFile" { video_path = Some(stored.path); }
+            })
+        } else {
+            upload::read_text(field).await.and_then(|value| {
+                match name.as_str() {
+                    "interpolationLevel" => interpolation_level = value.parse().unwrap_or(1),
+                    "cameraTimezone" => camera_timezone = Some(value),
+                    "displayTimezone" => display_timezone = Some(value),
+                    "videoLastModified" => video_last_modified = value.parse().ok(),
+                    "autoSync" => auto_sync_requested = value == "true",
+                    "units" if !value.trim().is_empty() => units = value.parse().map_err(UploadError::Invalid)?,
+                    "ftp" => ftp = Some(value),
+                    "weightKg" => weight_kg = Some(value),
+                    // Assets são lidos no próprio lugar: não há nada a copiar nem a apagar
+                    "gpxAssetId" => track_file_path = Some(assets.resolve(&value)?.path),
+                    "videoAssetId" => video_path = Some(assets.resolve(&value)?.path),
+                    _ => {}
+                }
+                Ok(())
+            })
+        };
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_dir_all(&upload_dir).await;
+            return (e.status(), Json(SuggestionResponse::error(e.to_string())));
+        }
+    }
+
+    if let (Some(video), Some(last_modified)) = (&video_path, video_last_modified) {
+        restore_modified_time(video, last_modified);
+    }
+
+    let track_file_path = track_file_path.or_else(|| video_path.clone());
+
+    let settings = TimezoneSettings::from_params(camera_timezone.as_deref(), display_timezone.as_deref())
+        .map_err(|e| e.to_string())
+        .and_then(|timezones| {
+            let athlete = AthleteProfile::from_params(None, None, None, None, ftp.as_deref(), weight_kg.as_deref())?;
+            Ok((timezones, athlete))
+        });
+    let (timezones, athlete) = match settings {
+        Ok(settings) => settings,
+        Err(message) => {
+            let _ = tokio::fs::remove_dir_all(&upload_dir).await;
+            return (StatusCode::BAD_REQUEST, Json(SuggestionResponse::error(message)));
+        }
+    };
+
+    let response = match (track_file_path, video_path) {
+        // ffprobe, leitura da trilha e sincronização automática bloqueiam: correm fora do runtime
+        (Some(track_p), Some(video_p)) => tokio::task::spawn_blocking(move || {
+            build_suggestion(&track_p, &video_p, interpolation_level, timezones, auto_sync_requested, units, &athlete)
+        }).await
+            .unwrap_or_else(|e| Err(format!("A sugestão foi interrompida: {}", e)))
+            .unwrap_or_else(SuggestionResponse::error),
+        _ => SuggestionResponse::error("Missing video or track file.".to_string()),
+    };
+    
+    let _ = tokio::fs::remove_dir_all(&upload_dir).await;
+    (StatusCode::OK, Json(response))
+}
+
@@ -0,0 +1,226 @@
+// src/suggest.rs - Ponto de sincronização sugerido para um vídeo e uma trilha
+
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::athlete::AthleteProfile;
+use crate::auto_sync::{self, AutoSyncResult};
+use crate::power::PowerSeries;
+use crate::telemetry::{AltitudeSource, CameraSettings};
+use crate::timezone::TimezoneSettings;
+use crate::track::{detect_file_type, read_track_file};
+use crate::units::UnitSystem;
+use crate::utils;
+use crate::video_time::{get_video_time_range, VideoTimeSource};
+
+/// Ponto interpolado da trilha, com a telemetria desse instante
+#[derive(Serialize)]
+pub struct PointJson {
+    pub lat: f64,
+    pub lon: f64,
+    pub time: Option<String>,
+    /// Campos extras para telemetria
+    pub heart_rate: Option<f64>,
+    pub cadence: Option<f64>,
+    pub speed: Option<f64>,
+    pub power: Option<f64>,
+    pub temperature: Option<f64>,
+    pub g_force: Option<f64>,
+    pub altitude_source: Option<AltitudeSource>,
+    pub camera: Option<CameraSettings>,
+}
+
+/// Resposta do `/suggest` e saída do `suggest` da linha de comando; `timestamp` é a hora
+/// do ponto sugerido, em RFC 3339
+#[derive(Serialize)]
+pub struct SuggestionResponse {
+    pub message: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub timestamp: Option<String>,
+    pub display_timestamp: Option<String>,
+    pub interpolated_points: Option<Vec<PointJson>>,
+    pub file_type: Option<String>,
+    pub sport_type: Option<String>,
+    pub extra_data: Option<TcxExtraDataJson>,
+    /// Fuso IANA usado em `display_timestamp` (resolvido quando o pedido usa "auto")
+    pub display_timezone: Option<String>,
+    /// Fonte da hora de início do vídeo, para o utilizador saber o quanto confiar nela
+    pub video_time_source: Option<VideoTimeSource>,
+    /// Desvio estimado pela sincronização automática, quando pedida
+    pub auto_sync: Option<AutoSyncResult>,
+    /// Unidades dos campos `display_*` do resumo
+    pub units: Option<UnitSystem>,
+}
+
+impl SuggestionResponse {
+    pub fn error(message: String) -> Self {
+        SuggestionResponse {
+            message,
+            latitude: None, longitude: None, timestamp: None, display_timestamp: None,
+            interpolated_points: None, file_type: None, sport_type: None, extra_data: None,
+            display_timezone: None, video_time_source: None, auto_sync: None, units: None,
+        }
+    }
+}
+
+/// Resumo da atividade, quando o formato da trilha o fornece
+#[derive(Serialize)]
+pub struct TcxExtraDataJson {
+    pub total_time_seconds: f64,
+    pub total_distance_meters: f64,
+    pub total_calories: f64,
+    pub max_speed: f64,
+    pub average_heart_rate: Option<f64>,
+    pub max_heart_rate: Option<f64>,
+    pub average_cadence: Option<f64>,
+    pub max_cadence: Option<f64>,
+    pub average_speed: Option<f64>,
+    /// Distância total e velocidades (ou ritmos) já formatadas nas unidades pedidas
+    pub display_distance: String,
+    pub display_max_speed: String,
+    pub display_average_speed: Option<String>,
+    /// Potência em watts, a média também em W/kg com o peso do atleta
+    pub average_power: Option<f64>,
+    pub max_power: Option<f64>,
+    pub average_watts_per_kg: Option<f64>,
+    /// Normalized Power e, com o FTP do atleta, Intensity Factor e TSS
+    pub normalized_power: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    pub training_stress_score: Option<f64>,
+}
+
+/// Ponto de sincronização sugerido: o primeiro ponto da trilha depois do início do vídeo.
+/// Usado pelo `/suggest` e pelo `suggest` da linha de comando. Devolve erro quando o vídeo
+/// ou a trilha não podem ser lidos; sem ponto depois do início, a resposta vem sem `timestamp`.
+/// O FTP e o peso de `athlete` completam o resumo de potência.
+pub fn build_suggestion(
+    track_path: &Path,
+    video_path: &Path,
+    interpolation_level: i64,
+    timezones: TimezoneSettings,
+    auto_sync_requested: bool,
+    units: UnitSystem,
+    athlete: &AthleteProfile,
+) -> Result<SuggestionResponse, String> {
+    let video_time = get_video_time_range(video_path, timezones.camera, "en")
+        .map_err(|e| format!("Error reading video metadata: {}", e))?;
+    let track_file_data = read_track_file(track_path, timezones.camera)
+        .map_err(|e| format!("Error reading track file: {}", e))?;
+
+    let file_type = detect_file_type(track_path);
+    let display_tz = timezones.display_timezone_for(&track_file_data.gpx);
+    // O relógio do GPS embutido no vídeo é mais preciso que o creation_time
+    let (video_start_time, video_time_source) = match track_file_data.video_start_time {
+        Some(gps_start_time) => (gps_start_time, VideoTimeSource::GpmfGps),
+        None => (video_time.start, video_time.source),
+    };
+
+    // Com o relógio do GPS a hora já é exata; nos outros casos corrige-se o relógio da câmara
+    let auto_sync = if auto_sync_requested && track_file_data.video_start_time.is_none() {
+        match auto_sync::estimate_video_offset(video_path, &track_file_data.gpx, video_start_time, auto_sync::DEFAULT_MAX_OFFSET_SECS) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::warn!("Sincronização automática falhou: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let video_start_time = match auto_sync.filter(AutoSyncResult::is_reliable) {
+        Some(result) => video_start_time + chrono::Duration::milliseconds((result.offset_seconds * 1000.0) as i64),
+        None => video_start_time,
+    };
+    // NP, IF e TSS sobre as leituras originais, antes da interpolação
+    let power_summary = PowerSeries::from_track(&track_file_data.gpx, &track_file_data.telemetry).summary(athlete.ftp);
+    let (interpolated_gpx, telemetry) = utils::interpolate_gpx_points(
+        track_file_data.gpx,
+        &track_file_data.telemetry,
+        interpolation_level,
+    );
+    
+    let first_point_after = interpolated_gpx
+        .tracks.iter().flat_map(|t| t.segments.iter()).flat_map(|s| s.points.iter())
+        .find(|p| p.time.and_then(|t| t.format().ok()).and_then(|ts| ts.parse::<DateTime<Utc>>().ok()).is_some_and(|pt| pt > video_start_time));
+
+    let (extra_data_json, sport_type) = if let Some(tcx_extra) = track_file_data.extra_data {
+        // Velocidades do resumo em m/s
+        let display_speed = |mps: f64| format!("{} {}", units.format_speed(mps * 3.6, 1), units.speed_unit());
+        let json = TcxExtraDataJson {
+            total_time_seconds: tcx_extra.total_time_seconds,
+            total_distance_meters: tcx_extra.total_distance_meters,
+            total_calories: tcx_extra.total_calories,
+            max_speed: tcx_extra.max_speed,
+            average_heart_rate: tcx_extra.average_heart_rate(),
+            max_heart_rate: tcx_extra.max_heart_rate(),
+            average_cadence: tcx_extra.average_cadence(),
+            max_cadence: tcx_extra.max_cadence(),
+            average_speed: tcx_extra.average_speed(),
+            display_distance: format!("{:.2} {}", units.distance(tcx_extra.total_distance_meters / 1000.0), units.distance_unit()),
+            display_max_speed: display_speed(tcx_extra.max_speed),
+            display_average_speed: tcx_extra.average_speed().map(display_speed),
+            average_power: tcx_extra.average_power(),
+            max_power: tcx_extra.max_power(),
+            average_watts_per_kg: tcx_extra.average_power().and_then(|watts| athlete.watts_per_kg(watts)),
+            normalized_power: power_summary.and_then(|summary| summary.normalized_w),
+            intensity_factor: power_summary.and_then(|summary| summary.intensity_factor),
+            training_stress_score: power_summary.and_then(|summary| summary.training_stress_score),
+        };
+        (Some(json), tcx_extra.sport)
+    } else {
+        (None, None)
+    };
+
+    let points_for_json: Vec<PointJson> = interpolated_gpx.tracks.iter()
+        .flat_map(|t| t.segments.iter())
+        .flat_map(|s| s.points.iter())
+        .zip(telemetry.iter())
+        .map(|(p, sample)| PointJson {
+            lat: p.point().y(),
+            lon: p.point().x(),
+            time: p.time.and_then(|t| t.format().ok()),
+            heart_rate: sample.heart_rate,
+            cadence: sample.cadence,
+            speed: sample.speed_kmh(),
+            power: sample.power,
+            temperature: sample.temperature,
+            g_force: sample.g_force,
+            altitude_source: sample.altitude_source,
+            camera: sample.camera,
+        })
+        .collect();
+
+    let mut response = SuggestionResponse {
+        message: "No track point found after video start.".to_string(),
+        interpolated_points: Some(points_for_json),
+        file_type: Some(file_type),
+        sport_type,
+        extra_data: extra_data_json,
+        display_timezone: Some(display_tz.name().to_string()),
+        video_time_source: Some(video_time_source),
+        auto_sync,
+        units: Some(units),
+        ..SuggestionResponse::error(String::new())
+    };
+
+    if let Some(point) = first_point_after {
+        let point_coords = point.point();
+        let timestamp_iso_str = point.time.and_then(|t| t.format().ok()).unwrap();
+
+        let display_timestamp_str = if let Ok(utc_time) = timestamp_iso_str.parse::<DateTime<Utc>>() {
+            let local_time = utc_time.with_timezone(&display_tz);
+            format!("{} ({})", local_time.format("%d/%m/%Y, %H:%M:%S"), local_time.format("%:z"))
+        } else {
+            timestamp_iso_str.clone()
+        };
+
+        response.message = "Sync point suggested.".to_string();
+        response.latitude = Some(point_coords.y());
+        response.longitude = Some(point_coords.x());
+        response.timestamp = Some(timestamp_iso_str);
+        response.display_timestamp = Some(display_timestamp_str);
+    }
+    Ok(response)
+}
+
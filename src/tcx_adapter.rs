@@ -5,7 +5,7 @@ use std::path::Path;
 use chrono::{DateTime, Utc};
 use gpx::{Gpx, Track, TrackSegment, Waypoint, GpxVersion};
 use geo_types::Point;
-use tcx;
+use crate::telemetry::TelemetrySample;
 
 /// Estrutura para armazenar dados extras específicos do TCX
 #[derive(Debug, Default, Clone)]
@@ -18,12 +18,17 @@ pub struct TcxExtraData {
     pub heart_rate_data: Vec<f64>,
     pub cadence_data: Vec<f64>,
     pub speed_data: Vec<f64>,
+    pub power_data: Vec<f64>,
+    pub temperature_data: Vec<f64>,
+    pub device_name: Option<String>,
 }
 
 /// Estrutura para o resultado do processamento de um arquivo TCX
 pub struct TcxProcessResult {
     pub gpx: Gpx,
     pub extra_data: TcxExtraData,
+    /// Telemetria por ponto, paralela aos pontos de `gpx`
+    pub telemetry: Vec<TelemetrySample>,
 }
 
 /// Lê e processa um arquivo TCX uma única vez, retornando a estrutura GPX e os dados extras.
@@ -39,95 +44,96 @@ pub fn read_and_process_tcx(path: &Path) -> Result<TcxProcessResult, Box<dyn Err
         ..Default::default()
     };
     let mut extra_data = TcxExtraData::default();
-
-    if let Some(activities) = tcx_data.activities {
-        for activity in activities.activities {
-            extra_data.sport = Some(activity.sport.clone());
-            
-            let mut track = Track::new();
-            track.name = Some(activity.id.clone());
-            track.type_ = Some(map_sport_to_track_type(&activity.sport));
-
-            for lap in activity.laps {
-                // Acumula estatísticas para extra_data
-                extra_data.total_time_seconds += lap.total_time_seconds;
-                extra_data.total_distance_meters += lap.distance_meters;
-                extra_data.total_calories += lap.calories as f64;
-                if let Some(max_speed) = lap.maximum_speed {
-                    if max_speed > extra_data.max_speed {
-                        extra_data.max_speed = max_speed;
-                    }
+    let mut telemetry = Vec::new();
+
+    // Um XML sem `<Activities>` não é um TCX de atividade
+    let activities = tcx_data.activities.ok_or("Nenhuma atividade encontrada no arquivo TCX.")?;
+    for activity in activities.activities {
+        extra_data.sport = Some(activity.sport.clone());
+        
+        let mut track = Track::new();
+        track.name = Some(activity.id.clone());
+        track.type_ = Some(map_sport_to_track_type(&activity.sport));
+
+        for lap in activity.laps {
+            // Acumula estatísticas para extra_data
+            extra_data.total_time_seconds += lap.total_time_seconds;
+            extra_data.total_distance_meters += lap.distance_meters;
+            extra_data.total_calories += lap.calories as f64;
+            if let Some(max_speed) = lap.maximum_speed {
+                if max_speed > extra_data.max_speed {
+                    extra_data.max_speed = max_speed;
                 }
+            }
 
-                let mut segment = TrackSegment::new();
-                for track_data in lap.tracks {
-                    for trackpoint in track_data.trackpoints {
-                        if let Some(position) = trackpoint.position {
-                            let mut waypoint = Waypoint::new(Point::new(
-                                position.longitude,
-                                position.latitude,
-                            ));
-
-                            if let Some(altitude) = trackpoint.altitude_meters {
-                                waypoint.elevation = Some(altitude);
-                            }
+            let mut segment = TrackSegment::new();
+            for track_data in lap.tracks {
+                for trackpoint in track_data.trackpoints {
+                    if let Some(position) = trackpoint.position {
+                        let mut waypoint = Waypoint::new(Point::new(
+                            position.longitude,
+                            position.latitude,
+                        ));
+
+                        if let Some(altitude) = trackpoint.altitude_meters {
+                            waypoint.elevation = Some(altitude);
+                        }
 
-                            let time_str = trackpoint.time.to_rfc3339();
-                            if let Ok(time_parsed) = DateTime::parse_from_rfc3339(&time_str) {
-                                let utc_time = time_parsed.with_timezone(&Utc);
-                                if let Ok(offset_dt) = time::OffsetDateTime::from_unix_timestamp(utc_time.timestamp()) {
-                                    waypoint.time = Some(gpx::Time::from(offset_dt));
-                                }
+                        let time_str = trackpoint.time.to_rfc3339();
+                        if let Ok(time_parsed) = DateTime::parse_from_rfc3339(&time_str) {
+                            let utc_time = time_parsed.with_timezone(&Utc);
+                            if let Ok(offset_dt) = time::OffsetDateTime::from_unix_timestamp(utc_time.timestamp()) {
+                                waypoint.time = Some(gpx::Time::from(offset_dt));
                             }
+                        }
 
-                            // --- CORREÇÃO: Armazena dados de telemetria no campo `comment` ---
-                            let mut comment_parts = Vec::new();
-                            
-                            if let Some(heart_rate) = trackpoint.heart_rate {
-                                let hr_value = heart_rate.value as f64;
-                                extra_data.heart_rate_data.push(hr_value);
-                                comment_parts.push(format!("HR:{}", hr_value));
-                            }
-                            
-                            if let Some(cadence) = trackpoint.cadence {
-                                let cad_value = cadence as f64;
-                                extra_data.cadence_data.push(cad_value);
-                                comment_parts.push(format!("CAD:{}", cad_value));
-                            }
-                            
-                            if let Some(extensions_data) = &trackpoint.extensions {
-                                if let Some(tpx) = &extensions_data.tpx {
-                                    if let Some(speed) = tpx.speed {
-                                        extra_data.speed_data.push(speed);
-                                        comment_parts.push(format!("SPD:{:.2}", speed));
-                                    }
-                                }
-                            }
+                        let mut sample = TelemetrySample::default();
 
-                            if !comment_parts.is_empty() {
-                                waypoint.comment = Some(comment_parts.join(";"));
+                        if let Some(heart_rate) = trackpoint.heart_rate {
+                            let hr_value = heart_rate.value;
+                            extra_data.heart_rate_data.push(hr_value);
+                            sample.heart_rate = Some(hr_value);
+                        }
+                        
+                        if let Some(cadence) = trackpoint.cadence {
+                            let cad_value = cadence as f64;
+                            extra_data.cadence_data.push(cad_value);
+                            sample.cadence = Some(cad_value);
+                        }
+                        
+                        if let Some(extensions_data) = &trackpoint.extensions {
+                            if let Some(tpx) = &extensions_data.tpx {
+                                if let Some(speed) = tpx.speed {
+                                    extra_data.speed_data.push(speed);
+                                    sample.speed_mps = Some(speed);
+                                }
+                                if let Some(watts) = tpx.watts {
+                                    let power = watts as f64;
+                                    extra_data.power_data.push(power);
+                                    sample.power = Some(power);
+                                }
                             }
-                            // --- FIM DA CORREÇÃO ---
-
-                            segment.points.push(waypoint);
                         }
+
+                        telemetry.push(sample);
+                        segment.points.push(waypoint);
                     }
                 }
-                if !segment.points.is_empty() {
-                    track.segments.push(segment);
-                }
             }
-            if !track.segments.is_empty() {
-                gpx.tracks.push(track);
+            if !segment.points.is_empty() {
+                track.segments.push(segment);
             }
         }
+        if !track.segments.is_empty() {
+            gpx.tracks.push(track);
+        }
     }
 
-    Ok(TcxProcessResult { gpx, extra_data })
+    Ok(TcxProcessResult { gpx, extra_data, telemetry })
 }
 
 /// Mapeia tipos de esporte TCX para tipos de trilha GPX
-fn map_sport_to_track_type(sport: &str) -> String {
+pub fn map_sport_to_track_type(sport: &str) -> String {
     match sport.to_lowercase().as_str() {
         "running" => "Running".to_string(),
         "biking" | "cycling" => "Cycling".to_string(),
@@ -161,4 +167,19 @@ impl TcxExtraData {
     pub fn max_cadence(&self) -> Option<f64> {
         self.cadence_data.iter().max_by(|a, b| a.partial_cmp(b).unwrap()).copied()
     }
+
+    pub fn average_power(&self) -> Option<f64> {
+        if self.power_data.is_empty() { None }
+        else { Some(self.power_data.iter().sum::<f64>() / self.power_data.len() as f64) }
+    }
+
+    pub fn max_power(&self) -> Option<f64> {
+        self.power_data.iter().max_by(|a, b| a.partial_cmp(b).unwrap()).copied()
+    }
+
+    /// Ritmo médio de gasto (kcal/s): as calorias da atividade divididas pela sua duração
+    pub fn calories_per_second(&self) -> Option<f64> {
+        if self.total_calories <= 0.0 || self.total_time_seconds <= 0.0 { None }
+        else { Some(self.total_calories / self.total_time_seconds) }
+    }
 }
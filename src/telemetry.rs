@@ -0,0 +1,120 @@
+// src/telemetry.rs - Modelo tipado de telemetria por ponto da trilha
+
+use serde::Serialize;
+
+/// Origem da altitude registrada pelo dispositivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AltitudeSource {
+    Gps,
+    Barometric,
+}
+
+/// Parâmetros de exposição da câmara num ponto (legendas SRT da DJI)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct CameraSettings {
+    pub iso: Option<f64>,
+    /// Tempo de exposição em segundos
+    pub exposure_secs: Option<f64>,
+    pub f_number: Option<f64>,
+    pub ev: Option<f64>,
+    pub focal_length_mm: Option<f64>,
+}
+
+impl CameraSettings {
+    /// Tempo de exposição como as câmaras o mostram: "1/500", ou em segundos a partir de 1 s
+    pub fn shutter(&self) -> Option<String> {
+        self.exposure_secs.map(|secs| {
+            if secs >= 1.0 {
+                format!("{}s", (secs * 10.0).round() / 10.0)
+            } else {
+                format!("1/{}", (1.0 / secs).round())
+            }
+        })
+    }
+
+    /// Resumo numa linha para o overlay, ex.: "ISO 100  1/500  f/1.7  EV 0"
+    pub fn describe(&self) -> String {
+        [
+            self.iso.map(|iso| format!("ISO {:.0}", iso)),
+            self.shutter(),
+            self.f_number.map(|f| format!("f/{}", f)),
+            self.ev.map(|ev| format!("EV {}", ev)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("  ")
+    }
+}
+
+/// Canais de telemetria de um único ponto da trilha.
+///
+/// As amostras são guardadas num `Vec` paralelo aos pontos do GPX, na mesma ordem em que
+/// aparecem ao percorrer `tracks → segments → points`. Todos os canais são opcionais:
+/// um ponto sem sensor associado é simplesmente `TelemetrySample::default()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct TelemetrySample {
+    pub heart_rate: Option<f64>,
+    pub cadence: Option<f64>,
+    /// Velocidade do sensor em m/s
+    pub speed_mps: Option<f64>,
+    pub power: Option<f64>,
+    pub temperature: Option<f64>,
+    /// Aceleração medida pelo acelerómetro, em g e sem a gravidade
+    pub g_force: Option<f64>,
+    pub altitude_source: Option<AltitudeSource>,
+    /// Exposição da câmara no ponto; mantém-se até ao ponto seguinte
+    pub camera: Option<CameraSettings>,
+}
+
+impl TelemetrySample {
+    /// Velocidade do sensor convertida para km/h
+    pub fn speed_kmh(&self) -> Option<f64> {
+        self.speed_mps.map(|s| s * 3.6)
+    }
+
+    /// Interpola linearmente os canais numéricos entre duas amostras.
+    /// Um canal só é interpolado quando existe em ambas as extremidades.
+    pub fn lerp(&self, other: &TelemetrySample, ratio: f64) -> TelemetrySample {
+        let mix = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + (b - a) * ratio),
+            _ => None,
+        };
+
+        TelemetrySample {
+            heart_rate: mix(self.heart_rate, other.heart_rate),
+            cadence: mix(self.cadence, other.cadence),
+            speed_mps: mix(self.speed_mps, other.speed_mps),
+            power: mix(self.power, other.power),
+            temperature: mix(self.temperature, other.temperature),
+            g_force: mix(self.g_force, other.g_force),
+            altitude_source: self.altitude_source,
+            camera: self.camera,
+        }
+    }
+}
+
+/// Mantém o último valor conhecido de cada canal, para sensores que não
+/// enviam leitura em todos os pontos.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LastKnownTelemetry {
+    current: TelemetrySample,
+}
+
+impl LastKnownTelemetry {
+    pub fn update(&mut self, sample: &TelemetrySample) -> TelemetrySample {
+        let keep = |new: Option<f64>, old: Option<f64>| new.or(old);
+        self.current = TelemetrySample {
+            heart_rate: keep(sample.heart_rate, self.current.heart_rate),
+            cadence: keep(sample.cadence, self.current.cadence),
+            speed_mps: keep(sample.speed_mps, self.current.speed_mps),
+            power: keep(sample.power, self.current.power),
+            temperature: keep(sample.temperature, self.current.temperature),
+            g_force: keep(sample.g_force, self.current.g_force),
+            altitude_source: sample.altitude_source.or(self.current.altitude_source),
+            camera: sample.camera.or(self.current.camera),
+        };
+        self.current
+    }
+}
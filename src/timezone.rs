@@ -0,0 +1,89 @@
+// src/timezone.rs - Fusos horários da câmara e de exibição (nomes IANA via chrono-tz)
+
+use std::error::Error;
+use std::sync::OnceLock;
+use chrono::{DateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use gpx::Gpx;
+use tzf_rs::DefaultFinder;
+
+/// Fuso usado quando o pedido não indica nenhum (comportamento histórico)
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::America::Sao_Paulo;
+
+/// Valor do parâmetro de exibição que deriva o fuso da primeira coordenada da trilha
+pub const AUTO_TIMEZONE: &str = "auto";
+
+/// Fuso em que as horas são exibidas (sugestão de sincronização e painel de estatísticas)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayTimezone {
+    Fixed(Tz),
+    /// Determinado pela primeira coordenada da trilha, com o mapa de fronteiras offline
+    Auto,
+}
+
+/// Fusos horários de um pedido de processamento
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimezoneSettings {
+    /// Fuso do relógio da câmara: `creation_time` e horas locais (ex.: SRT da DJI) são lidos nele
+    pub camera: Tz,
+    pub display: DisplayTimezone,
+}
+
+impl Default for TimezoneSettings {
+    fn default() -> Self {
+        TimezoneSettings {
+            camera: DEFAULT_TIMEZONE,
+            display: DisplayTimezone::Fixed(DEFAULT_TIMEZONE),
+        }
+    }
+}
+
+impl TimezoneSettings {
+    /// Monta as configurações a partir dos campos do formulário. Campos vazios usam o
+    /// padrão; sem fuso de exibição, as horas são exibidas no fuso da câmara.
+    pub fn from_params(camera: Option<&str>, display: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let camera = match camera.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(name) => parse_timezone(name)?,
+            None => DEFAULT_TIMEZONE,
+        };
+        let display = match display.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(name) if name.eq_ignore_ascii_case(AUTO_TIMEZONE) => DisplayTimezone::Auto,
+            Some(name) => DisplayTimezone::Fixed(parse_timezone(name)?),
+            None => DisplayTimezone::Fixed(camera),
+        };
+        Ok(TimezoneSettings { camera, display })
+    }
+
+    /// Resolve o fuso de exibição para uma trilha. No modo automático, trilhas sem pontos
+    /// ou coordenadas fora do mapa de fusos usam o fuso da câmara.
+    pub fn display_timezone_for(&self, gpx: &Gpx) -> Tz {
+        match self.display {
+            DisplayTimezone::Fixed(tz) => tz,
+            DisplayTimezone::Auto => gpx.tracks.iter()
+                .flat_map(|t| t.segments.iter())
+                .flat_map(|s| s.points.iter())
+                .next()
+                .and_then(|p| timezone_for_coordinate(p.point().y(), p.point().x()))
+                .unwrap_or(self.camera),
+        }
+    }
+}
+
+/// Converte um nome IANA ("Europe/Lisbon", "UTC") para o fuso do chrono-tz
+pub fn parse_timezone(name: &str) -> Result<Tz, Box<dyn Error>> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("Fuso horário inválido: '{}'. Use um nome IANA, ex.: America/Sao_Paulo", name).into())
+}
+
+/// Procura o fuso de uma coordenada no mapa de fronteiras embutido (timezone-boundary-builder).
+/// O mapa é carregado na primeira consulta e reaproveitado nas seguintes.
+pub fn timezone_for_coordinate(lat: f64, lon: f64) -> Option<Tz> {
+    static FINDER: OnceLock<DefaultFinder> = OnceLock::new();
+    let name = FINDER.get_or_init(DefaultFinder::new).get_tz_name(lon, lat);
+    name.parse::<Tz>().ok()
+}
+
+/// Diferença em segundos entre a hora local do fuso e UTC no instante indicado (inclui horário de verão)
+pub fn utc_offset_secs(tz: Tz, instant: &DateTime<Utc>) -> i32 {
+    tz.offset_from_utc_datetime(&instant.naive_utc()).fix().local_minus_utc()
+}
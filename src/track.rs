@@ -0,0 +1,93 @@
+// src/track.rs - Leitura dos arquivos de trilha em qualquer formato suportado
+
+use std::error::Error;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use gpx::Gpx;
+use crate::{dji_srt, fit_adapter, gpmf, gpx_adapter, tcx_adapter};
+use crate::tcx_adapter::TcxExtraData;
+use crate::telemetry::TelemetrySample;
+
+/// Dados unificados de um arquivo de trilha, qualquer que seja o formato
+#[derive(Debug)]
+pub struct TrackFileData {
+    pub gpx: Gpx,
+    pub extra_data: Option<TcxExtraData>,
+    /// Telemetria paralela aos pontos do GPX (vazia quando o formato não a fornece)
+    pub telemetry: Vec<TelemetrySample>,
+    /// Início do vídeo pelo relógio do GPS, quando a trilha vem do próprio vídeo (GPMF)
+    pub video_start_time: Option<DateTime<Utc>>,
+}
+
+/// Tipo do arquivo de trilha pela extensão: "TCX", "GPX", "FIT", "GPMF" (vídeo GoPro),
+/// "SRT" (DJI) ou "Unknown"
+pub fn detect_file_type(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|s| s.to_lowercase())
+        .map(|ext| match ext.as_str() {
+            "tcx" => "TCX".to_string(),
+            "gpx" => "GPX".to_string(),
+            "fit" => "FIT".to_string(),
+            "mp4" | "mov" | "lrv" => "GPMF".to_string(),
+            "srt" => "SRT".to_string(),
+            _ => "Unknown".to_string(),
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// Lê um arquivo de trilha (GPX, TCX, FIT, SRT da DJI ou vídeo GoPro) e devolve os dados unificados.
+/// `camera_tz` é o fuso do relógio do dispositivo, usado pelos formatos que gravam hora local (SRT da DJI).
+pub fn read_track_file(path: &Path, camera_tz: Tz) -> Result<TrackFileData, Box<dyn Error>> {
+    let file_type = detect_file_type(path);
+    
+    match file_type.as_str() {
+        "TCX" => {
+            let result = tcx_adapter::read_and_process_tcx(path)?;
+            Ok(TrackFileData {
+                gpx: result.gpx,
+                extra_data: Some(result.extra_data),
+                telemetry: result.telemetry,
+                video_start_time: None,
+            })
+        },
+        "FIT" => {
+            let result = fit_adapter::read_and_process_fit(path)?;
+            Ok(TrackFileData {
+                gpx: result.gpx,
+                extra_data: Some(result.extra_data),
+                telemetry: result.telemetry,
+                video_start_time: None,
+            })
+        },
+        "GPX" => {
+            let result = gpx_adapter::read_and_process_gpx(path)?;
+            Ok(TrackFileData {
+                gpx: result.gpx,
+                extra_data: result.extra_data,
+                telemetry: result.telemetry,
+                video_start_time: None,
+            })
+        },
+        "GPMF" => {
+            let result = gpmf::read_and_process_gpmf(path)?;
+            Ok(TrackFileData {
+                gpx: result.gpx,
+                extra_data: Some(result.extra_data),
+                telemetry: result.telemetry,
+                video_start_time: result.video_start_time,
+            })
+        },
+        "SRT" => {
+            let result = dji_srt::read_and_process_dji_srt(path, camera_tz)?;
+            Ok(TrackFileData {
+                gpx: result.gpx,
+                extra_data: Some(result.extra_data),
+                telemetry: result.telemetry,
+                video_start_time: result.video_start_time,
+            })
+        },
+        _ => Err(format!("Formato de arquivo não suportado: {}", file_type).into()),
+    }
+}
@@ -0,0 +1,186 @@
+// src/units.rs - Sistemas de unidades dos overlays: métrico, imperial, náutico e ritmo
+
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+const KM_PER_MILE: f64 = 1.609344;
+const KM_PER_NAUTICAL_MILE: f64 = 1.852;
+const FEET_PER_METER: f64 = 3.280839895;
+
+/// Máximo do velocímetro sem máximo detectado na trilha, em km/h
+const DEFAULT_GAUGE_MAX_KMH: f64 = 120.0;
+/// O mesmo para o ritmo, pensado para corrida (20 km/h = 3:00 /km)
+const DEFAULT_PACE_GAUGE_MAX_KMH: f64 = 20.0;
+/// Ritmos mais lentos que uma hora por unidade de distância aparecem como "--:--"
+const MAX_PACE_MINUTES: f64 = 60.0;
+
+/// Unidades em que os valores são exibidos; os dados continuam em km, m e km/h
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnitSystem {
+    /// km/h, km e m
+    #[default]
+    Metric,
+    /// mph, mi e ft
+    Imperial,
+    /// nós, milhas náuticas e m
+    Nautical,
+    /// Ritmo em min/km, km e m
+    PaceKm,
+    /// Ritmo em min/mi, mi e ft
+    PaceMi,
+}
+
+/// Divisões do velocímetro, na unidade de velocidade do sistema
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaugeScale {
+    pub max: f64,
+    /// Intervalo entre traços curtos e entre traços longos (com rótulo)
+    pub minor_step: u32,
+    pub major_step: u32,
+}
+
+impl UnitSystem {
+    pub const ALL: [UnitSystem; 5] = [UnitSystem::Metric, UnitSystem::Imperial, UnitSystem::Nautical, UnitSystem::PaceKm, UnitSystem::PaceMi];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+            UnitSystem::Nautical => "nautical",
+            UnitSystem::PaceKm => "pace-km",
+            UnitSystem::PaceMi => "pace-mi",
+        }
+    }
+
+    pub fn is_pace(&self) -> bool {
+        matches!(self, UnitSystem::PaceKm | UnitSystem::PaceMi)
+    }
+
+    fn km_per_unit(&self) -> f64 {
+        match self {
+            UnitSystem::Metric | UnitSystem::PaceKm => 1.0,
+            UnitSystem::Imperial | UnitSystem::PaceMi => KM_PER_MILE,
+            UnitSystem::Nautical => KM_PER_NAUTICAL_MILE,
+        }
+    }
+
+    /// Distância em km na unidade do sistema
+    pub fn distance(&self, km: f64) -> f64 {
+        km / self.km_per_unit()
+    }
+
+    pub fn distance_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric | UnitSystem::PaceKm => "KM",
+            UnitSystem::Imperial | UnitSystem::PaceMi => "MI",
+            UnitSystem::Nautical => "NM",
+        }
+    }
+
+    /// Velocidade em km/h na unidade do velocímetro (km/h, mph ou nós; também para o ritmo)
+    pub fn speed(&self, kmh: f64) -> f64 {
+        self.distance(kmh)
+    }
+
+    /// Unidade do valor de `format_speed`
+    pub fn speed_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "KM/H",
+            UnitSystem::Imperial => "MPH",
+            UnitSystem::Nautical => "KN",
+            UnitSystem::PaceKm => "/KM",
+            UnitSystem::PaceMi => "/MI",
+        }
+    }
+
+    /// Altitude e ganho de elevação em m na unidade do sistema
+    pub fn elevation(&self, meters: f64) -> f64 {
+        match self {
+            UnitSystem::Imperial | UnitSystem::PaceMi => meters * FEET_PER_METER,
+            _ => meters,
+        }
+    }
+
+    pub fn elevation_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Imperial | UnitSystem::PaceMi => "FT",
+            _ => "M",
+        }
+    }
+
+    /// Velocidade com `decimals` casas ou, nos sistemas de ritmo, o ritmo em "m:ss"
+    pub fn format_speed(&self, kmh: f64, decimals: usize) -> String {
+        if self.is_pace() {
+            format_pace(self.speed(kmh))
+        } else {
+            format!("{:.*}", decimals, self.speed(kmh))
+        }
+    }
+
+    /// Divisões do velocímetro para um máximo em km/h (o detectado na trilha ou o padrão):
+    /// arredondado a 10 com traços de 5 e 25, ou a 5 com traços de 1 e 5 nas escalas curtas
+    pub fn gauge_scale(&self, max_kmh: Option<f64>) -> GaugeScale {
+        let default = if self.is_pace() { DEFAULT_PACE_GAUGE_MAX_KMH } else { DEFAULT_GAUGE_MAX_KMH };
+        let max = self.speed(max_kmh.unwrap_or(default));
+        if max > 40.0 {
+            GaugeScale { max: (max / 10.0).ceil() * 10.0, minor_step: 5, major_step: 25 }
+        } else {
+            GaugeScale { max: ((max / 5.0).ceil() * 5.0).max(10.0), minor_step: 1, major_step: 5 }
+        }
+    }
+
+    /// Rótulo de um traço longo do velocímetro (`speed` na unidade do velocímetro); no ritmo é
+    /// o ritmo correspondente e o zero fica sem rótulo
+    pub fn gauge_label(&self, speed: u32) -> Option<String> {
+        match (self.is_pace(), speed) {
+            (false, _) => Some(speed.to_string()),
+            (true, 0) => None,
+            (true, _) => Some(format_pace(speed as f64)),
+        }
+    }
+
+    pub fn describe(&self, lang: &str) -> &'static str {
+        match (self, lang == "en") {
+            (UnitSystem::Metric, true) => "Metric (km/h, km, m)",
+            (UnitSystem::Metric, false) => "Métrico (km/h, km, m)",
+            (UnitSystem::Imperial, _) => "Imperial (mph, mi, ft)",
+            (UnitSystem::Nautical, true) => "Nautical (kn, nm, m)",
+            (UnitSystem::Nautical, false) => "Náutico (nós, mn, m)",
+            (UnitSystem::PaceKm, true) => "Pace (min/km, km, m)",
+            (UnitSystem::PaceKm, false) => "Ritmo (min/km, km, m)",
+            (UnitSystem::PaceMi, true) => "Pace (min/mi, mi, ft)",
+            (UnitSystem::PaceMi, false) => "Ritmo (min/mi, mi, ft)",
+        }
+    }
+}
+
+/// Ritmo (minutos por unidade de distância) de uma velocidade em unidades por hora, em "m:ss"
+pub fn format_pace(speed_per_hour: f64) -> String {
+    if speed_per_hour.is_nan() || speed_per_hour <= 60.0 / MAX_PACE_MINUTES {
+        return "--:--".to_string();
+    }
+    let total_secs = (3600.0 / speed_per_hour).round() as u32;
+    format!("{}:{:02}", total_secs / 60, total_secs % 60)
+}
+
+impl fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UnitSystem {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        UnitSystem::ALL.into_iter()
+            .find(|units| units.as_str() == value)
+            .ok_or_else(|| {
+                let names: Vec<&str> = UnitSystem::ALL.iter().map(UnitSystem::as_str).collect();
+                format!("Sistema de unidades desconhecido: {} (disponíveis: {})", value, names.join(", "))
+            })
+    }
+}
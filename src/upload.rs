@@ -0,0 +1,163 @@
+// src/upload.rs - Gravação dos uploads em disco por partes, com limite de tamanho e SHA-256
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use axum::body::Bytes;
+use axum::extract::multipart::{Field, Multipart, MultipartError};
+use axum::http::StatusCode;
+use futures_util::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// Maior arquivo aceite num upload: 2 GB
+pub const MAX_UPLOAD_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Limite do corpo do pedido: um arquivo de 2 GB mais 1 MB para as fronteiras e os
+/// cabeçalhos do multipart e os campos de texto que o acompanham
+pub const MAX_BODY_BYTES: u64 = MAX_UPLOAD_BYTES + 1024 * 1024;
+/// Maior valor aceite num campo de texto do formulário
+const MAX_TEXT_FIELD_BYTES: usize = 64 * 1024;
+
+/// Arquivo recebido e gravado em disco
+#[derive(Debug, Clone)]
+pub struct StoredUpload {
+    pub path: PathBuf,
+    /// Nome original, sem diretórios
+    pub file_name: String,
+    pub size: u64,
+    /// SHA-256 do conteúdo em hexadecimal
+    pub sha256: String,
+}
+
+#[derive(Debug)]
+pub enum UploadError {
+    /// Multipart malformado ou ligação interrompida
+    Multipart(MultipartError),
+    /// Arquivo maior que o limite
+    TooLarge { limit: u64 },
+    /// Campo de formulário inválido
+    Invalid(String),
+    /// ID de asset desconhecido ou já expirado
+    UnknownAsset(Uuid),
+    Io(std::io::Error),
+}
+
+impl UploadError {
+    /// Código HTTP da resposta: 4xx para erros do cliente, 500 para falhas do servidor
+    pub fn status(&self) -> StatusCode {
+        match self {
+            UploadError::Multipart(e) => e.status(),
+            UploadError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            UploadError::Invalid(_) => StatusCode::BAD_REQUEST,
+            UploadError::UnknownAsset(_) => StatusCode::NOT_FOUND,
+            UploadError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::Multipart(e) => write!(f, "Pedido multipart inválido: {}", e.body_text()),
+            UploadError::TooLarge { limit } => write!(f, "O arquivo excede o limite de {} MB.", limit / (1024 * 1024)),
+            UploadError::Invalid(message) => write!(f, "{}", message),
+            UploadError::UnknownAsset(id) => write!(f, "O arquivo {} não existe ou já expirou; envie-o de novo.", id),
+            UploadError::Io(e) => write!(f, "Erro ao gravar o upload: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+impl From<MultipartError> for UploadError {
+    fn from(e: MultipartError) -> Self {
+        UploadError::Multipart(e)
+    }
+}
+
+impl From<std::io::Error> for UploadError {
+    fn from(e: std::io::Error) -> Self {
+        UploadError::Io(e)
+    }
+}
+
+/// Próximo campo do formulário, com erro 4xx em vez de pânico quando o multipart é inválido
+pub async fn next_field(multipart: &mut Multipart) -> Result<Option<Field<'_>>, UploadError> {
+    Ok(multipart.next_field().await?)
+}
+
+/// Lê um campo de texto, limitado a `MAX_TEXT_FIELD_BYTES`
+pub async fn read_text(mut field: Field<'_>) -> Result<String, UploadError> {
+    let name = field.name().unwrap_or_default().to_string();
+    let mut data = Vec::new();
+    while let Some(chunk) = field.chunk().await? {
+        if data.len() + chunk.len() > MAX_TEXT_FIELD_BYTES {
+            return Err(UploadError::Invalid(format!("O campo '{}' é demasiado grande.", name)));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    String::from_utf8(data).map_err(|_| UploadError::Invalid(format!("O campo '{}' não é texto UTF-8 válido.", name)))
+}
+
+/// Grava um campo de arquivo em `dir` como `<uuid>-<nome original>`, parte a parte
+pub async fn save_field(field: Field<'_>, dir: &Path) -> Result<StoredUpload, UploadError> {
+    let file_name = sanitize_file_name(field.file_name().unwrap_or_default());
+    let path = dir.join(format!("{}-{}", Uuid::new_v4(), file_name));
+    let (size, sha256) = save_stream(field, &path, MAX_UPLOAD_BYTES).await?;
+    Ok(StoredUpload { path, file_name, size, sha256 })
+}
+
+/// Escreve as partes de `chunks` em `path` sem as juntar em memória, calculando o tamanho
+/// e o SHA-256 pelo caminho. Acima de `max_bytes`, ou se o stream falhar, o arquivo
+/// parcial é apagado.
+pub async fn save_stream<S, E>(chunks: S, path: &Path, max_bytes: u64) -> Result<(u64, String), UploadError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<UploadError>,
+{
+    let result = write_chunks(chunks, path, max_bytes).await;
+    if result.is_err() {
+        tokio::fs::remove_file(path).await.ok();
+    }
+    result
+}
+
+async fn write_chunks<S, E>(chunks: S, path: &Path, max_bytes: u64) -> Result<(u64, String), UploadError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Into<UploadError>,
+{
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+
+    let mut chunks = std::pin::pin!(chunks);
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(Into::into)?;
+        size += chunk.len() as u64;
+        if size > max_bytes {
+            return Err(UploadError::TooLarge { limit: max_bytes });
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    // A data de modificação seria a desta gravação no servidor, que nada diz sobre o vídeo:
+    // fica no início da época Unix, que a hora de início do vídeo descarta, até o cliente
+    // enviar a original (`lastModified`)
+    file.into_std().await.set_modified(std::time::UNIX_EPOCH)?;
+
+    let sha256 = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok((size, sha256))
+}
+
+/// Só o último componente do nome enviado pelo navegador, sem separadores de diretório
+pub fn sanitize_file_name(file_name: &str) -> String {
+    let base = file_name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    if base.is_empty() || base == "." || base == ".." {
+        "upload".to_string()
+    } else {
+        base.to_string()
+    }
+}
@@ -1,52 +1,15 @@
 // src/utils.rs
 
-use std::path::Path;
-use std::error::Error;
-use chrono::{DateTime, Duration, TimeZone, Utc};
-use chrono_tz::America::Sao_Paulo;
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use gpx::{Gpx, Waypoint, Track, TrackSegment};
 use geo_types::Point;
+use crate::telemetry::TelemetrySample;
 
-pub fn get_video_time_range(video_path: &Path, lang: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), Box<dyn Error>> {
-    let metadata = ffprobe::ffprobe(video_path).map_err(|e| {
-        let error_message = e.to_string();
-        if error_message.contains("No such file or directory") || error_message.contains("not found") {
-            let msg = if lang == "en" {
-                "Command 'ffprobe' not found. Make sure FFmpeg is installed and in the system's PATH."
-            } else {
-                "Comando 'ffprobe' não encontrado. Verifique se o FFmpeg está instalado e no PATH do sistema."
-            };
-            Box::<dyn Error>::from(msg)
-        } else {
-            Box::<dyn Error>::from(format!("Error executing ffprobe: {}", error_message))
-        }
-    })?;
-
-    let creation_time_str = metadata.streams
-        .iter()
-        .find(|s| s.codec_type == Some("video".to_string()))
-        .and_then(|s| s.tags.as_ref())
-        .and_then(|t| t.creation_time.as_deref())
-        .ok_or(if lang == "en" {
-            "Tag 'creation_time' not found in the video stream."
-        } else {
-            "Tag 'creation_time' não encontrada no stream de vídeo."
-        })?;
-        
-    let naive_datetime_from_video = DateTime::parse_from_rfc3339(creation_time_str)?.naive_utc();
-    let local_datetime = Sao_Paulo.from_local_datetime(&naive_datetime_from_video).single()
-        .ok_or(if lang == "en" {
-            "Could not convert local time for the São Paulo timezone."
-        } else {
-            "Não foi possível converter a hora local para o fuso de São Paulo."
-        })?;
-    let start_time_utc = local_datetime.with_timezone(&Utc);
-
-    let duration_str = metadata.format.duration.ok_or(if lang == "en" { "Duration not found." } else { "Duração não encontrada." })?;
-    let duration_secs = duration_str.parse::<f64>()?;
-    let duration = Duration::microseconds((duration_secs * 1_000_000.0) as i64);
-    
-    Ok((start_time_utc, start_time_utc + duration))
+/// Converte uma hora local de relógio de câmara (sem fuso) para UTC.
+/// Na hora repetida do fim do horário de verão usa a primeira ocorrência.
+pub fn local_to_utc(naive: &NaiveDateTime, tz: Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(naive).earliest().map(|local| local.with_timezone(&Utc))
 }
 
 pub fn distance_2d(p1: &Waypoint, p2: &Waypoint) -> f64 {
@@ -128,7 +91,13 @@ pub fn calculate_bearing(p1: &Waypoint, p2: &Waypoint) -> f64 {
     (initial_bearing_deg + 360.0) % 360.0
 }
 
-fn interpolate_points(p1: &Waypoint, p2: &Waypoint, max_interval_secs: i64) -> Vec<Waypoint> {
+fn interpolate_points(
+    p1: &Waypoint,
+    p2: &Waypoint,
+    s1: &TelemetrySample,
+    s2: &TelemetrySample,
+    max_interval_secs: i64,
+) -> Vec<(Waypoint, TelemetrySample)> {
     let mut interpolated_points = Vec::new();
     
     let time1_str = match p1.time.as_ref().and_then(|t| t.format().ok()) {
@@ -188,14 +157,26 @@ fn interpolate_points(p1: &Waypoint, p2: &Waypoint, max_interval_secs: i64) -> V
             new_waypoint.time = Some(gpx::Time::from(offset_dt));
         }
         
-        interpolated_points.push(new_waypoint);
+        interpolated_points.push((new_waypoint, s1.lerp(s2, ratio)));
     }
     
     interpolated_points
 }
 
-pub fn interpolate_gpx_points(mut gpx: Gpx, max_interval_secs: i64) -> Gpx {
+/// Interpola os pontos da trilha para que nenhum intervalo exceda `max_interval_secs`.
+/// A telemetria (paralela aos pontos) é interpolada em conjunto e devolvida na mesma ordem;
+/// se estiver vazia ou incompleta, os pontos em falta recebem amostras vazias.
+pub fn interpolate_gpx_points(
+    mut gpx: Gpx,
+    telemetry: &[TelemetrySample],
+    max_interval_secs: i64,
+) -> (Gpx, Vec<TelemetrySample>) {
+    // Um intervalo de 0 s (ou negativo) pediria infinitos pontos
+    let max_interval_secs = max_interval_secs.max(1);
     let mut new_tracks = Vec::new();
+    let mut new_telemetry = Vec::new();
+    let mut sample_idx = 0;
+    let sample_at = |idx: usize| telemetry.get(idx).copied().unwrap_or_default();
     
     for track in gpx.tracks.iter() {
         let mut new_segments = Vec::new();
@@ -209,16 +190,23 @@ pub fn interpolate_gpx_points(mut gpx: Gpx, max_interval_secs: i64) -> Gpx {
             }
             
             new_points.push(points[0].clone());
+            new_telemetry.push(sample_at(sample_idx));
             
             for i in 1..points.len() {
                 let p1 = &points[i - 1];
                 let p2 = &points[i];
+                let s1 = sample_at(sample_idx + i - 1);
+                let s2 = sample_at(sample_idx + i);
                 
-                let interpolated = interpolate_points(p1, p2, max_interval_secs);
-                new_points.extend(interpolated);
+                for (point, sample) in interpolate_points(p1, p2, &s1, &s2, max_interval_secs) {
+                    new_points.push(point);
+                    new_telemetry.push(sample);
+                }
                 
                 new_points.push(p2.clone());
+                new_telemetry.push(s2);
             }
+            sample_idx += points.len();
             
             let mut new_segment = TrackSegment::new();
             new_segment.points = new_points;
@@ -238,5 +226,5 @@ pub fn interpolate_gpx_points(mut gpx: Gpx, max_interval_secs: i64) -> Gpx {
     }
     
     gpx.tracks = new_tracks;
-    gpx
+    (gpx, new_telemetry)
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+// src/video_geometry.rs - Resolução, proporção dos pixels (SAR) e rotação do stream de vídeo
+
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+use ffprobe::FfProbe;
+use serde_json::Value;
+
+/// Geometria do primeiro stream de vídeo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoGeometry {
+    /// Pixels gravados no stream, antes da rotação
+    pub width: u32,
+    pub height: u32,
+    /// Proporção dos pixels (SAR) como (numerador, denominador); (1, 1) em quase todos os vídeos
+    pub sample_aspect_ratio: (u32, u32),
+    /// Rotação de exibição no sentido horário: 0, 90, 180 ou 270 graus (vídeos de telemóvel)
+    pub rotation: u32,
+}
+
+impl VideoGeometry {
+    /// Lê a geometria da saída JSON do ffprobe (`-show_streams`); `None` sem stream de vídeo
+    /// com resolução conhecida
+    pub fn from_ffprobe_json(json: &Value) -> Option<Self> {
+        let stream = json.get("streams")?.as_array()?.iter().find(|stream| {
+            stream.get("codec_type").and_then(Value::as_str) == Some("video")
+                && stream.pointer("/disposition/attached_pic").and_then(Value::as_i64) != Some(1)
+        })?;
+        let dimension = |key: &str| stream.get(key)?.as_u64().and_then(|v| u32::try_from(v).ok()).filter(|&v| v > 0);
+
+        Some(VideoGeometry {
+            width: dimension("width")?,
+            height: dimension("height")?,
+            sample_aspect_ratio: stream.get("sample_aspect_ratio")
+                .and_then(Value::as_str)
+                .and_then(parse_ratio)
+                .unwrap_or((1, 1)),
+            rotation: stream_rotation(stream),
+        })
+    }
+
+    fn is_rotated(&self) -> bool {
+        self.rotation % 180 == 90
+    }
+
+    /// Tamanho dos frames decodificados pelo FFmpeg, que já os roda: é o tamanho do overlay
+    pub fn frame_size(&self) -> (u32, u32) {
+        if self.is_rotated() { (self.height, self.width) } else { (self.width, self.height) }
+    }
+
+    /// Tamanho com que o vídeo é visto, com o SAR aplicado e rodado; os widgets são compostos
+    /// neste tamanho para não ficarem deformados
+    pub fn display_size(&self) -> (u32, u32) {
+        let (num, den) = self.sample_aspect_ratio;
+        let width = ((self.width as f64 * num as f64 / den as f64).round() as u32).max(1);
+        if self.is_rotated() { (self.height, width) } else { (width, self.height) }
+    }
+
+    pub fn is_portrait(&self) -> bool {
+        let (width, height) = self.display_size();
+        height > width
+    }
+
+    pub fn describe(&self, lang: &str) -> String {
+        let (width, height) = self.display_size();
+        let orientation = match (self.is_portrait(), lang == "en") {
+            (true, true) => "portrait",
+            (true, false) => "retrato",
+            (false, true) => "landscape",
+            (false, false) => "paisagem",
+        };
+        let (num, den) = self.sample_aspect_ratio;
+        format!("{}x{} ({}, {}°, SAR {}:{})", width, height, orientation, self.rotation, num, den)
+    }
+}
+
+/// "16:15" -> (16, 15); "0:1" e "N/A" (desconhecido) ficam de fora
+fn parse_ratio(value: &str) -> Option<(u32, u32)> {
+    let (num, den) = value.split_once(':')?;
+    let (num, den) = (num.trim().parse::<u32>().ok()?, den.trim().parse::<u32>().ok()?);
+    (num > 0 && den > 0).then_some((num, den))
+}
+
+/// A matriz de exibição (`side_data_list`, FFmpeg 5+) guarda a rotação no sentido anti-horário;
+/// a tag `rotate` (versões antigas) já vem no sentido horário
+fn stream_rotation(stream: &Value) -> u32 {
+    let as_degrees = |value: &Value| value.as_f64().or_else(|| value.as_str()?.trim().parse().ok());
+    let display_matrix = stream.get("side_data_list")
+        .and_then(Value::as_array)
+        .and_then(|list| list.iter().find_map(|side_data| side_data.get("rotation").and_then(as_degrees)))
+        .map(|degrees| -degrees);
+    let degrees = display_matrix
+        .or_else(|| stream.pointer("/tags/rotate").and_then(as_degrees))
+        .unwrap_or(0.0);
+    // Arredonda para o múltiplo de 90 mais próximo
+    (((degrees / 90.0).round() as i64 * 90).rem_euclid(360)) as u32
+}
+
+/// Executa o ffprobe uma vez e devolve os metadados e a geometria do vídeo
+pub fn probe(video_path: &Path) -> Result<(FfProbe, Option<VideoGeometry>), Box<dyn Error>> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-show_format", "-show_streams", "-print_format", "json"])
+        .arg(video_path)
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("ffprobe terminou com o código {}: {}", output.status, String::from_utf8_lossy(&output.stderr)).into());
+    }
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    let geometry = VideoGeometry::from_ffprobe_json(&json);
+    Ok((serde_json::from_value(json)?, geometry))
+}
@@ -0,0 +1,260 @@
+// src/video_time.rs - Determinação da hora de início do vídeo a partir de várias fontes
+
+use std::error::Error;
+use std::path::Path;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use ffprobe::FfProbe;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::utils::local_to_utc;
+use crate::video_geometry::{self, VideoGeometry};
+
+/// Datas anteriores a isto vêm de relógios por configurar ou do "zero" do QuickTime (1904)
+const MIN_PLAUSIBLE_YEAR: i32 = 1995;
+
+/// Chaves de data gravadas por iPhone e outros telemóveis nas tags do contentor
+const QUICKTIME_DATE_KEYS: [&str; 3] = ["com.apple.quicktime.creationdate", "creationdate", "date"];
+
+/// Fonte de onde veio a hora de início do vídeo, da mais para a menos confiável
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoTimeSource {
+    /// `creation_time` do stream de vídeo (ou de outro stream)
+    StreamTag,
+    /// `creation_time` do contentor
+    FormatTag,
+    /// `com.apple.quicktime.creationdate` e chaves equivalentes, com fuso explícito
+    QuickTime,
+    /// Relógio do GPS da telemetria GPMF da GoPro
+    GpmfGps,
+    /// Timecode (hora do dia) combinado com a data do nome ou da modificação do arquivo
+    Timecode,
+    /// Data e hora no nome do arquivo (ex.: `VID_20240115_073000`)
+    FileName,
+    /// Data de modificação do arquivo menos a duração do vídeo
+    FileModified,
+}
+
+impl VideoTimeSource {
+    /// Fontes aproximadas: o utilizador deve confirmar o ponto de sincronização
+    pub fn is_approximate(&self) -> bool {
+        matches!(self, VideoTimeSource::Timecode | VideoTimeSource::FileName | VideoTimeSource::FileModified)
+    }
+
+    pub fn describe(&self, lang: &str) -> &'static str {
+        match (self, lang == "en") {
+            (VideoTimeSource::StreamTag, true) => "video stream creation_time tag",
+            (VideoTimeSource::StreamTag, false) => "tag creation_time do stream de vídeo",
+            (VideoTimeSource::FormatTag, true) => "container creation_time tag",
+            (VideoTimeSource::FormatTag, false) => "tag creation_time do contentor",
+            (VideoTimeSource::QuickTime, true) => "QuickTime/Apple creation date",
+            (VideoTimeSource::QuickTime, false) => "data de criação QuickTime/Apple",
+            (VideoTimeSource::GpmfGps, true) => "GoPro GPS clock (GPMF)",
+            (VideoTimeSource::GpmfGps, false) => "relógio do GPS da GoPro (GPMF)",
+            (VideoTimeSource::Timecode, true) => "timecode track (approximate)",
+            (VideoTimeSource::Timecode, false) => "faixa de timecode (aproximado)",
+            (VideoTimeSource::FileName, true) => "date in the file name (approximate)",
+            (VideoTimeSource::FileName, false) => "data no nome do arquivo (aproximado)",
+            (VideoTimeSource::FileModified, true) => "file modification date (approximate)",
+            (VideoTimeSource::FileModified, false) => "data de modificação do arquivo (aproximado)",
+        }
+    }
+}
+
+/// Início e fim do vídeo em UTC, com a fonte usada para o início
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoTimeRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub source: VideoTimeSource,
+    /// Resolução e orientação, lidas na mesma chamada ao ffprobe; `None` sem stream de vídeo
+    pub geometry: Option<VideoGeometry>,
+}
+
+/// Lê o início e o fim do vídeo em UTC. As horas sem fuso (como o `creation_time` gravado
+/// pela câmara) são do relógio dela e por isso são interpretadas no fuso `camera_tz`.
+pub fn get_video_time_range(video_path: &Path, camera_tz: Tz, lang: &str) -> Result<VideoTimeRange, Box<dyn Error>> {
+    let (metadata, geometry) = video_geometry::probe(video_path).map_err(|e| {
+        let error_message = e.to_string();
+        if error_message.contains("No such file or directory") || error_message.contains("not found") {
+            let msg = if lang == "en" {
+                "Command 'ffprobe' not found. Make sure FFmpeg is installed and in the system's PATH."
+            } else {
+                "Comando 'ffprobe' não encontrado. Verifique se o FFmpeg está instalado e no PATH do sistema."
+            };
+            Box::<dyn Error>::from(msg)
+        } else {
+            Box::<dyn Error>::from(format!("Error executing ffprobe: {}", error_message))
+        }
+    })?;
+
+    let duration_str = metadata.format.duration.as_deref()
+        .ok_or(if lang == "en" { "Duration not found." } else { "Duração não encontrada." })?;
+    let duration_secs = duration_str.parse::<f64>()?;
+    let duration = Duration::microseconds((duration_secs * 1_000_000.0) as i64);
+
+    let (start, source) = resolve_start_time(&metadata, video_path, camera_tz, duration)
+        .ok_or(if lang == "en" {
+            "Could not determine when the video was recorded (no date in the metadata, file name or file)."
+        } else {
+            "Não foi possível determinar quando o vídeo foi gravado (sem data nos metadados, no nome ou no arquivo)."
+        })?;
+
+    Ok(VideoTimeRange { start, end: start + duration, source, geometry })
+}
+
+/// Percorre as fontes por ordem de confiança e devolve a primeira hora de início válida.
+pub fn resolve_start_time(
+    metadata: &FfProbe,
+    video_path: &Path,
+    camera_tz: Tz,
+    duration: Duration,
+) -> Option<(DateTime<Utc>, VideoTimeSource)> {
+    let local = |naive: NaiveDateTime| local_to_utc(&naive, camera_tz).filter(is_plausible);
+
+    // 1. creation_time dos streams, começando pelo de vídeo
+    let mut streams: Vec<_> = metadata.streams.iter().collect();
+    streams.sort_by_key(|s| s.codec_type.as_deref() != Some("video"));
+    if let Some(time) = streams.iter()
+        .filter_map(|s| s.tags.as_ref()?.creation_time.as_deref())
+        .find_map(|value| parse_camera_time(value).and_then(local))
+    {
+        return Some((time, VideoTimeSource::StreamTag));
+    }
+
+    let format_tags = metadata.format.tags.as_ref();
+
+    // 2. creation_time do contentor
+    if let Some(time) = format_tags
+        .and_then(|t| t.creation_time.as_deref())
+        .and_then(|value| parse_camera_time(value).and_then(local))
+    {
+        return Some((time, VideoTimeSource::FormatTag));
+    }
+
+    // 3. Chaves QuickTime/Apple, que normalmente trazem o fuso ("2024-01-15T07:30:00-0300")
+    if let Some(time) = format_tags.and_then(|tags| {
+        QUICKTIME_DATE_KEYS.iter()
+            .filter_map(|key| tags.extra.get(*key)?.as_str())
+            .find_map(|value| parse_zoned_time(value).or_else(|| parse_camera_time(value).and_then(local)))
+    }) {
+        return Some((time, VideoTimeSource::QuickTime));
+    }
+
+    // 4. Relógio do GPS da telemetria GoPro
+    if metadata.streams.iter().any(|s| s.codec_tag_string == "gpmd") {
+        if let Some(time) = crate::gpmf::read_and_process_gpmf(video_path).ok().and_then(|r| r.video_start_time) {
+            return Some((time, VideoTimeSource::GpmfGps));
+        }
+    }
+
+    let file_name_time = video_path.file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(parse_file_name_time);
+    let modified = file_modified_time(video_path);
+
+    // 5. Timecode: só traz a hora do dia, a data vem do nome ou da modificação do arquivo
+    let timecode = streams.iter()
+        .filter_map(|s| s.tags.as_ref()?.timecode.as_deref())
+        .chain(format_tags.and_then(|t| t.extra.get("timecode")?.as_str()))
+        .find_map(|value| parse_timecode(value, frame_rate(metadata)));
+    let recording_date = file_name_time.map(|t| t.date())
+        .or_else(|| modified.map(|m| m.with_timezone(&camera_tz).date_naive()));
+    if let (Some(time_of_day), Some(date)) = (timecode, recording_date) {
+        if let Some(time) = local(date.and_time(time_of_day)) {
+            return Some((time, VideoTimeSource::Timecode));
+        }
+    }
+
+    // 6. Data no nome do arquivo
+    if let Some(time) = file_name_time.and_then(local) {
+        return Some((time, VideoTimeSource::FileName));
+    }
+
+    // 7. Modificação do arquivo: o arquivo é fechado no fim da gravação
+    modified
+        .map(|m| m - duration)
+        .filter(is_plausible)
+        .map(|time| (time, VideoTimeSource::FileModified))
+}
+
+fn is_plausible(time: &DateTime<Utc>) -> bool {
+    use chrono::Datelike;
+    time.year() >= MIN_PLAUSIBLE_YEAR
+}
+
+/// Hora do relógio da câmara. Muitas câmaras escrevem a hora local com um "Z" falso,
+/// por isso o fuso da string é descartado e a hora é tratada como local.
+fn parse_camera_time(value: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(value).map(|dt| dt.naive_utc())
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok())
+}
+
+/// Data com fuso explícito, como a escrita pelo iPhone ("2024-01-15T07:30:00-0300")
+fn parse_zoned_time(value: &str) -> Option<DateTime<Utc>> {
+    // "Z" é tratado como hora local da câmara, tal como no creation_time
+    if value.ends_with('Z') {
+        return None;
+    }
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z"))
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+        .filter(is_plausible)
+}
+
+/// Timecode SMPTE ("07:30:00:12" ou "07:30:00;12") convertido em hora do dia
+fn parse_timecode(value: &str, fps: f64) -> Option<NaiveTime> {
+    let parts: Vec<u32> = value.split([':', ';', '.']).map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let [hours, minutes, seconds, frames] = parts.as_slice() else { return None };
+    let time = NaiveTime::from_hms_opt(*hours, *minutes, *seconds)?;
+    let frame_micros = if fps > 0.0 { (*frames as f64 / fps * 1_000_000.0) as i64 } else { 0 };
+    Some(time + Duration::microseconds(frame_micros))
+}
+
+fn frame_rate(metadata: &FfProbe) -> f64 {
+    metadata.streams.iter()
+        .find(|s| s.codec_type.as_deref() == Some("video"))
+        .and_then(|s| {
+            let (num, den) = s.r_frame_rate.split_once('/')?;
+            let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+            (den > 0.0).then(|| num / den)
+        })
+        .unwrap_or(30.0)
+}
+
+/// Procura "AAAAMMDD" seguido de "HHMMSS" no nome, com ou sem separadores:
+/// `VID_20240115_073000`, `PXL_20240115_073000123`, `2024-01-15 07.30.00`, `20240115073000`.
+/// O prefixo UUID acrescentado no upload é ignorado.
+pub fn parse_file_name_time(stem: &str) -> Option<NaiveDateTime> {
+    let stem = strip_upload_prefix(stem);
+    let groups: Vec<&str> = stem.split(|c: char| !c.is_ascii_digit()).filter(|g| !g.is_empty()).collect();
+
+    (0..groups.len()).find_map(|start| {
+        let year: i32 = groups[start].get(..4)?.parse().ok()?;
+        if !(MIN_PLAUSIBLE_YEAR..=2100).contains(&year) {
+            return None;
+        }
+        let digits: String = groups[start..].concat();
+        let date = NaiveDate::parse_from_str(digits.get(..8)?, "%Y%m%d").ok()?;
+        let time = NaiveTime::parse_from_str(digits.get(8..14)?, "%H%M%S").ok()?;
+        Some(date.and_time(time))
+    })
+}
+
+/// Os uploads são gravados como "<uuid>-<nome original>"
+fn strip_upload_prefix(stem: &str) -> &str {
+    match (stem.get(..36), stem.get(36..37)) {
+        (Some(prefix), Some("-")) if Uuid::parse_str(prefix).is_ok() => &stem[37..],
+        _ => stem,
+    }
+}
+
+fn file_modified_time(path: &Path) -> Option<DateTime<Utc>> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified))
+}
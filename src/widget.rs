@@ -0,0 +1,443 @@
+// src/widget.rs - Widgets de overlay: contexto de cada frame, registo por nome e composição
+// genérica dos widgets de um layout num frame do tamanho do vídeo
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use gpx::{Gpx, Waypoint};
+use image::RgbaImage;
+use serde::de::DeserializeOwned;
+use image::imageops::{overlay, FilterType};
+use crate::athlete::AthleteProfile;
+use crate::basemap::{render_basemap, TileSource};
+use crate::drawing::{draw_map_attribution, render_dot_image, render_elevation_profile_image, render_elevation_progress_image, render_heart_rate_image, render_power_image, render_speedometer_image, render_stats_image, render_track_map_image, MapProjection, PowerReading, ProfileProjection, StatsReading, Theme};
+use crate::elevation_profile::ElevationProfile;
+use crate::heart_rate::{HeartRateZones, TimeInZones};
+use crate::power::PowerSummary;
+use crate::layout::{apply_opacity, Layout, WidgetLayout};
+use crate::telemetry::CameraSettings;
+use crate::units::UnitSystem;
+
+/// Telemetria de um ponto da trilha visível no vídeo, partilhada por todos os widgets
+#[derive(Debug, Clone)]
+pub struct FrameContext {
+    pub point: Waypoint,
+    pub point_time: DateTime<Utc>,
+    /// Velocidade do sensor no ponto ou, sem ele, calculada pelo GPS
+    pub speed_kmh: f64,
+    pub bearing: f64,
+    pub g_force: f64,
+    pub elevation: f64,
+    /// Distância e ganho de elevação acumulados desde o início do vídeo
+    pub distance_km: f64,
+    pub elevation_gain_m: f64,
+    /// Últimos valores conhecidos dos sensores
+    pub heart_rate: Option<f64>,
+    pub cadence: Option<f64>,
+    pub sensor_speed_kmh: Option<f64>,
+    /// Calorias desde o início do vídeo, estimadas pelo ritmo médio da atividade
+    pub calories: Option<f64>,
+    /// Exposição da câmara (SRT da DJI)
+    pub camera: Option<CameraSettings>,
+    /// Tempo acumulado em cada zona de FC desde o início do vídeo
+    pub time_in_zones: TimeInZones,
+    /// Última potência conhecida e as médias dos últimos 3 e 10 s
+    pub power: Option<f64>,
+    pub power_3s: Option<f64>,
+    pub power_10s: Option<f64>,
+    /// NP, IF e TSS desde o início do vídeo
+    pub power_summary: Option<PowerSummary>,
+    /// Desvio do fuso de exibição no instante do ponto
+    pub utc_offset_secs: i32,
+}
+
+/// Dados disponíveis ao criar um widget para um processamento
+pub struct WidgetContext<'a> {
+    /// Trilha interpolada completa
+    pub gpx: &'a Gpx,
+    /// Tamanho final do widget no vídeo, já com o `size` e a `scale` do layout
+    pub size: (u32, u32),
+    /// Cores e fontes padrão do widget com o `style` do layout aplicado
+    pub theme: Theme,
+    pub lang: &'a str,
+    /// Unidades em que os valores são exibidos
+    pub units: UnitSystem,
+    pub athlete: &'a AthleteProfile,
+    /// Campo `options` do widget no layout
+    pub options: &'a BTreeMap<String, serde_json::Value>,
+}
+
+impl WidgetContext<'_> {
+    /// Opção `name` do layout convertida para `T`; `None` se não estiver definida
+    pub fn option<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, Box<dyn Error>> {
+        self.options.get(name)
+            .map(|value| T::deserialize(value).map_err(|e| format!("Opção `{}` inválida: {}", name, e).into()))
+            .transpose()
+    }
+}
+
+/// Widget desenhado sobre o vídeo
+pub trait OverlayWidget {
+    /// Imagem do widget no instante `frame`, ou sem ponto visível quando `frame` é `None`;
+    /// `Ok(None)` não desenha nada. Uma imagem de tamanho diferente de `WidgetContext::size`
+    /// é redimensionada pela composição.
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>>;
+}
+
+type BuildWidget = fn(&WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>>;
+
+/// Como criar um widget a partir do layout
+#[derive(Clone, Copy)]
+pub struct WidgetFactory {
+    /// Tamanho em que o widget é desenhado sem `size` nem `scale` no layout
+    pub natural_size: (u32, u32),
+    pub default_theme: fn() -> Theme,
+    pub build: BuildWidget,
+}
+
+/// Widgets disponíveis para os layouts, pelo nome usado no campo `widget`
+#[derive(Clone, Default)]
+pub struct WidgetRegistry {
+    factories: BTreeMap<String, WidgetFactory>,
+}
+
+impl WidgetRegistry {
+    pub fn new() -> Self {
+        WidgetRegistry::default()
+    }
+
+    /// Velocímetro (`speedometer`), mapa do percurso (`track_map`), estatísticas (`stats`),
+    /// frequência cardíaca com zonas (`heart_rate`), potência (`power`) e perfil de elevação
+    /// (`elevation_profile`)
+    pub fn builtin() -> Self {
+        let mut registry = WidgetRegistry::new();
+        registry.register("speedometer", WidgetFactory {
+            natural_size: SPEEDOMETER_SIZE,
+            default_theme: Theme::speedometer,
+            build: SpeedometerWidget::build,
+        });
+        registry.register("track_map", WidgetFactory {
+            natural_size: (MAP_SIZE, MAP_SIZE),
+            default_theme: Theme::track_map,
+            build: TrackMapWidget::build,
+        });
+        registry.register("stats", WidgetFactory {
+            natural_size: STATS_SIZE,
+            default_theme: Theme::stats,
+            build: StatsWidget::build,
+        });
+        registry.register("heart_rate", WidgetFactory {
+            natural_size: HEART_RATE_SIZE,
+            default_theme: Theme::heart_rate,
+            build: HeartRateWidget::build,
+        });
+        registry.register("power", WidgetFactory {
+            natural_size: POWER_SIZE,
+            default_theme: Theme::power,
+            build: PowerWidget::build,
+        });
+        registry.register("elevation_profile", WidgetFactory {
+            natural_size: ELEVATION_PROFILE_SIZE,
+            default_theme: Theme::elevation_profile,
+            build: ElevationProfileWidget::build,
+        });
+        registry
+    }
+
+    /// Acrescenta (ou substitui) o widget `name`
+    pub fn register(&mut self, name: &str, factory: WidgetFactory) {
+        self.factories.insert(name.to_string(), factory);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&WidgetFactory> {
+        self.factories.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Nomes registados, por ordem alfabética
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.keys().map(String::as_str).collect()
+    }
+}
+
+/// Widget criado para um processamento, com a posição e a aparência do layout
+struct PlacedWidget<'a> {
+    layout: &'a WidgetLayout,
+    size: (u32, u32),
+    widget: Box<dyn OverlayWidget>,
+}
+
+/// Compõe os widgets de um layout, por ordem de `z`, em frames RGBA do tamanho do vídeo
+pub struct Compositor<'a> {
+    canvas: (u32, u32),
+    widgets: Vec<PlacedWidget<'a>>,
+}
+
+impl<'a> Compositor<'a> {
+    /// Cria os widgets do layout; falha se um widget não estiver registado ou não puder ser criado
+    pub fn new(
+        layout: &'a Layout,
+        registry: &WidgetRegistry,
+        gpx: &Gpx,
+        canvas: (u32, u32),
+        lang: &str,
+        units: UnitSystem,
+        athlete: &AthleteProfile,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut widgets = Vec::new();
+        for widget_layout in layout.ordered(canvas) {
+            let factory = registry.get(&widget_layout.widget)
+                .ok_or_else(|| format!("Widget desconhecido: {} (disponíveis: {})", widget_layout.widget, registry.names().join(", ")))?;
+            let size = widget_layout.output_size(factory.natural_size, canvas);
+            let context = WidgetContext {
+                gpx,
+                size,
+                theme: widget_layout.theme((factory.default_theme)())?,
+                lang,
+                units,
+                athlete,
+                options: &widget_layout.options,
+            };
+            widgets.push(PlacedWidget { layout: widget_layout, size, widget: (factory.build)(&context)? });
+        }
+        Ok(Compositor { canvas, widgets })
+    }
+
+    /// Frame com todos os widgets no instante `frame` (ou sem ponto visível)
+    pub fn compose(&self, frame: Option<&FrameContext>) -> Result<RgbaImage, Box<dyn Error>> {
+        let mut canvas = RgbaImage::new(self.canvas.0, self.canvas.1);
+        for placed in &self.widgets {
+            let Some(mut image) = placed.widget.render(frame)? else { continue };
+            if image.dimensions() != placed.size {
+                image = image::imageops::resize(&image, placed.size.0, placed.size.1, FilterType::Triangle);
+            }
+            apply_opacity(&mut image, placed.layout.opacity);
+            let origin = placed.layout.origin(self.canvas, placed.size);
+            overlay(&mut canvas, &image, origin.0, origin.1);
+        }
+        Ok(canvas)
+    }
+}
+
+/// Tamanho natural do mini-mapa e do marcador da posição atual
+const MAP_SIZE: u32 = 300;
+const MAP_PADDING: f64 = 20.0;
+const DOT_SIZE: u32 = 8;
+
+/// Tamanho natural do velocímetro, do painel de estatísticas, dos mostradores de FC e de
+/// potência e do perfil de elevação
+const SPEEDOMETER_SIZE: (u32, u32) = (300, 300);
+const STATS_SIZE: (u32, u32) = (280, 420);
+const HEART_RATE_SIZE: (u32, u32) = (300, 360);
+const POWER_SIZE: (u32, u32) = (300, 320);
+const ELEVATION_PROFILE_SIZE: (u32, u32) = (480, 160);
+
+struct SpeedometerWidget {
+    theme: Theme,
+    lang: String,
+    units: UnitSystem,
+    size: (u32, u32),
+}
+
+impl SpeedometerWidget {
+    fn build(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        Ok(Box::new(SpeedometerWidget { theme: context.theme.clone(), lang: context.lang.to_string(), units: context.units, size: context.size }))
+    }
+}
+
+impl OverlayWidget for SpeedometerWidget {
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let Some(frame) = frame else { return Ok(None) };
+        render_speedometer_image(frame.speed_kmh, frame.bearing, frame.g_force, frame.elevation, &self.lang, None, &self.theme, self.units, self.size).map(Some)
+    }
+}
+
+/// O mapa é desenhado uma só vez, já no tamanho final; em cada frame só o marcador muda.
+/// Com a opção `basemap` (MBTiles ou diretório XYZ) a trilha é desenhada sobre os tiles, em
+/// Web Mercator, com a atribuição do MBTiles ou a da opção `attribution` (`""` esconde-a).
+struct TrackMapWidget {
+    map: RgbaImage,
+    dot: RgbaImage,
+    projection: MapProjection,
+}
+
+impl TrackMapWidget {
+    fn build(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        let (width, height) = context.size;
+        let ratio = width.min(height) as f64 / MAP_SIZE as f64;
+        let line_thickness = (2.0 * ratio as f32).max(1.0);
+        let basemap: Option<String> = context.option("basemap")?;
+        let attribution: Option<String> = context.option("attribution")?;
+        let (map, projection) = match basemap {
+            Some(path) => {
+                let projection = MapProjection::web_mercator(context.gpx, width, height, MAP_PADDING * ratio)
+                    .ok_or("GPX não contém pontos suficientes para desenhar.")?;
+                let mut source = TileSource::open(std::path::Path::new(&path))?;
+                // As zonas sem tiles mostram a cor de fundo
+                let mut map = RgbaImage::from_pixel(width, height, context.theme.background_color);
+                overlay(&mut map, &render_basemap(&mut source, &projection, width, height)?, 0, 0);
+                let track = render_track_map_image(context.gpx, &projection, width, height, image::Rgba([0, 0, 0, 0]), line_thickness)?;
+                overlay(&mut map, &track, 0, 0);
+                if let Some(text) = attribution.or_else(|| source.attribution()) {
+                    draw_map_attribution(&mut map, &text, &context.theme, ratio as f32);
+                }
+                (map, projection)
+            },
+            None => {
+                let projection = MapProjection::new(context.gpx, width, height, MAP_PADDING * ratio)
+                    .ok_or("GPX não contém pontos suficientes para desenhar.")?;
+                (render_track_map_image(context.gpx, &projection, width, height, context.theme.background_color, line_thickness)?, projection)
+            },
+        };
+        let dot = render_dot_image(((DOT_SIZE as f64 * ratio).round() as u32).max(2), context.theme.accent_color);
+        Ok(Box::new(TrackMapWidget { map, dot, projection }))
+    }
+}
+
+impl OverlayWidget for TrackMapWidget {
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let mut image = self.map.clone();
+        if let Some(frame) = frame {
+            let (x, y) = self.projection.project(frame.point.point().x(), frame.point.point().y());
+            let half_dot = self.dot.width() as f64 / 2.0;
+            overlay(&mut image, &self.dot, (x - half_dot) as i64, (y - half_dot) as i64);
+        }
+        Ok(Some(image))
+    }
+}
+
+/// O painel é desenhado na escala da sua altura final, para o texto ficar nítido
+struct StatsWidget {
+    theme: Theme,
+    lang: String,
+    units: UnitSystem,
+    /// Só com zonas configuradas a FC é colorida pela zona
+    zones: Option<HeartRateZones>,
+    scale: f32,
+}
+
+impl StatsWidget {
+    fn build(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        let scale = context.size.1 as f32 / STATS_SIZE.1 as f32;
+        Ok(Box::new(StatsWidget { theme: context.theme.clone(), lang: context.lang.to_string(), units: context.units, zones: context.athlete.heart_rate_zones, scale }))
+    }
+}
+
+impl OverlayWidget for StatsWidget {
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let Some(frame) = frame else { return Ok(None) };
+        let reading = StatsReading {
+            distance_km: frame.distance_km,
+            altitude_m: frame.elevation,
+            elevation_gain_m: frame.elevation_gain_m,
+            point_time: frame.point_time,
+            heart_rate: frame.heart_rate,
+            heart_rate_zone: self.zones.zip(frame.heart_rate).map(|(zones, hr)| zones.zone(hr)),
+            cadence: frame.cadence,
+            speed_kmh: frame.sensor_speed_kmh,
+            calories: frame.calories,
+            camera: frame.camera,
+            power: frame.power,
+            power_summary: frame.power_summary,
+            utc_offset_secs: frame.utc_offset_secs,
+        };
+        render_stats_image(&reading, &self.lang, &self.theme, self.units, self.scale).map(Some)
+    }
+}
+
+/// Sem leitura de FC no ponto (trilha sem sensor) o mostrador não é desenhado
+struct HeartRateWidget {
+    theme: Theme,
+    lang: String,
+    zones: HeartRateZones,
+    size: (u32, u32),
+}
+
+impl HeartRateWidget {
+    fn build(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        Ok(Box::new(HeartRateWidget {
+            theme: context.theme.clone(),
+            lang: context.lang.to_string(),
+            zones: context.athlete.zones(),
+            size: context.size,
+        }))
+    }
+}
+
+impl OverlayWidget for HeartRateWidget {
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let Some((frame, heart_rate)) = frame.and_then(|frame| Some((frame, frame.heart_rate?))) else { return Ok(None) };
+        render_heart_rate_image(heart_rate, &self.zones, &frame.time_in_zones, &self.lang, &self.theme, self.size).map(Some)
+    }
+}
+
+/// Sem medidor de potência na trilha o mostrador não é desenhado
+struct PowerWidget {
+    theme: Theme,
+    lang: String,
+    athlete: AthleteProfile,
+    size: (u32, u32),
+}
+
+impl PowerWidget {
+    fn build(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        Ok(Box::new(PowerWidget {
+            theme: context.theme.clone(),
+            lang: context.lang.to_string(),
+            athlete: context.athlete.clone(),
+            size: context.size,
+        }))
+    }
+}
+
+impl OverlayWidget for PowerWidget {
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let Some((frame, power_w)) = frame.and_then(|frame| Some((frame, frame.power?))) else { return Ok(None) };
+        let reading = PowerReading {
+            power_w,
+            average_3s_w: frame.power_3s,
+            average_10s_w: frame.power_10s,
+            watts_per_kg: self.athlete.watts_per_kg(power_w),
+            ftp: self.athlete.ftp,
+        };
+        render_power_image(&reading, &self.lang, &self.theme, self.size).map(Some)
+    }
+}
+
+/// Como o mini-mapa, o perfil é desenhado uma só vez no tamanho final, em duas versões (por
+/// percorrer e percorrida); em cada frame só muda a parte copiada da segunda e o marcador.
+/// Trilhas sem altitude não desenham nada.
+struct ElevationProfileWidget {
+    theme: Theme,
+    units: UnitSystem,
+    scale: f32,
+    /// Perfil, eixos e as duas versões do gráfico
+    profile: Option<(ElevationProfile, ProfileProjection, RgbaImage, RgbaImage)>,
+}
+
+impl ElevationProfileWidget {
+    fn build(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        let color_by_gradient = context.option::<bool>("color_by_gradient")?.unwrap_or(false);
+        let (width, height) = context.size;
+        let scale = height as f32 / ELEVATION_PROFILE_SIZE.1 as f32;
+        let profile = ElevationProfile::new(context.gpx).map(|profile| {
+            let projection = ProfileProjection::new(&profile, width, height, scale);
+            let render = |done| render_elevation_profile_image(&profile, &projection, width, height, &context.theme, context.units, color_by_gradient, done, scale);
+            let (base, done) = (render(false), render(true));
+            (profile, projection, base, done)
+        });
+        Ok(Box::new(ElevationProfileWidget { theme: context.theme.clone(), units: context.units, scale, profile }))
+    }
+}
+
+impl OverlayWidget for ElevationProfileWidget {
+    fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+        let Some((profile, projection, base, done)) = &self.profile else { return Ok(None) };
+        let Some(frame) = frame else { return Ok(Some(base.clone())) };
+        let km = profile.distance_at(frame.point_time).unwrap_or(profile.start_km());
+        Ok(Some(render_elevation_progress_image(base, done, profile, projection, km, &self.theme, self.units, self.scale)))
+    }
+}
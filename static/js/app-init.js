@@ -16,9 +16,14 @@ const mapElement = document.getElementById('map');
 const mapSection = document.getElementById('map-section');
 const trackInfoDiv = document.getElementById('track-info');
 const positionSection = document.getElementById('position-section');
+const secondAnchorToggle = document.getElementById('second-anchor-toggle');
+const secondAnchorVideoTime = document.getElementById('second-anchor-video-time');
+const secondAnchorInfo = document.getElementById('second-anchor-info');
 const speedoCheckbox = document.getElementById('add-speedo-overlay');
 const trackCheckbox = document.getElementById('add-track-overlay');
 const statsCheckbox = document.getElementById('add-stats-overlay');
+const layoutPresetSelect = document.getElementById('layout-preset');
+const layoutFileInput = document.getElementById('layout-file');
 
 // Seletores para o Modal
 const settingsBtn = document.getElementById('settings-btn');
@@ -26,6 +31,16 @@ const settingsModal = document.getElementById('settings-modal');
 const closeModalBtn = document.getElementById('close-modal-btn');
 const interpolationSlider = document.getElementById('interpolation-slider');
 const interpolationValue = document.getElementById('interpolation-value');
+const cameraTimezoneInput = document.getElementById('camera-timezone');
+const displayTimezoneInput = document.getElementById('display-timezone');
+const autoSyncCheckbox = document.getElementById('auto-sync');
+const unitsSelect = document.getElementById('units-select');
+const hrZoneMethodSelect = document.getElementById('hr-zone-method');
+const maxHeartRateInput = document.getElementById('max-heart-rate');
+const restingHeartRateInput = document.getElementById('resting-heart-rate');
+const lthrInput = document.getElementById('lthr');
+const ftpInput = document.getElementById('ftp');
+const weightKgInput = document.getElementById('weight-kg');
 
 // Event Listeners principais
 gpxInput.addEventListener('change', handleGpxUpload);
@@ -40,6 +55,13 @@ settingsBtn.addEventListener('click', () => settingsModal.classList.remove('hidd
 closeModalBtn.addEventListener('click', () => settingsModal.classList.add('hidden'));
 settingsModal.addEventListener('click', (e) => { if (e.target === settingsModal) { settingsModal.classList.add('hidden'); } });
 interpolationSlider.addEventListener('input', () => { interpolationValue.textContent = `${interpolationSlider.value}s`; });
+cameraTimezoneInput.addEventListener('change', fetchAndApplySuggestion);
+displayTimezoneInput.addEventListener('change', fetchAndApplySuggestion);
+autoSyncCheckbox.addEventListener('change', fetchAndApplySuggestion);
+unitsSelect.addEventListener('change', fetchAndApplySuggestion);
+secondAnchorToggle.addEventListener('change', () => { if (!secondAnchorToggle.checked) clearSecondAnchor(); });
+layoutPresetSelect.addEventListener('change', validateGenerateButton);
+layoutFileInput.addEventListener('change', validateGenerateButton);
 
 function initializeInlineOverlaySystem() {
     inlineOverlayManager = new InlineOverlayManager();
@@ -48,6 +70,7 @@ function initializeInlineOverlaySystem() {
 // Inicialização quando DOM carregado
 document.addEventListener('DOMContentLoaded', () => {
     setLanguage(currentLang);
+    loadLayoutPresets();
     
     setTimeout(() => {
         initializeInlineOverlaySystem();
@@ -3,6 +3,10 @@
 // Variáveis de estado dos arquivos
 let gpxFile = null;
 let videoFile = null;
+// Fuso IANA em que o servidor exibe as horas da trilha (resolvido em /suggest)
+let displayTimezone = null;
+// Arquivos já enviados para /uploads (File -> Promise do asset_id): cada um é enviado uma só vez
+const uploadedAssets = new Map();
 
 function handleGpxUpload(event) { 
     gpxFile = event.target.files[0]; 
@@ -10,13 +14,21 @@ function handleGpxUpload(event) {
     
     // NOVO: Detectar tipo de arquivo
     const fileExt = gpxFile.name.split('.').pop().toLowerCase();
-    const fileTypeText = fileExt === 'tcx' ? 'TCX' : 'GPX';
+    const isGoProVideo = ['mp4', 'mov', 'lrv'].includes(fileExt);
+    const fileTypeText = isGoProVideo ? 'GoPro' : fileExt === 'srt' ? 'DJI SRT' : fileExt === 'tcx' ? 'TCX' : fileExt === 'fit' ? 'FIT' : 'GPX';
     
     gpxInfo.textContent = `${fileTypeText}: ${gpxFile.name}`; 
     notify.success(t('notification_gpx_loaded'), `${fileTypeText} - ${t('gpx_loaded')}`);
     
     videoInput.disabled = false; 
-    videoInfo.textContent = t('can_select_video'); 
+    if (isGoProVideo) {
+        // NOVO: O vídeo GoPro traz a trilha GPMF embutida e serve também como vídeo
+        videoFile = gpxFile;
+        videoInfo.textContent = videoFile.name;
+        checkAndShowMapSection();
+    } else {
+        videoInfo.textContent = t('can_select_video'); 
+    }
     validateGenerateButton(); 
     fetchAndApplySuggestion(); 
 }
@@ -47,21 +59,27 @@ async function fetchAndApplySuggestion() {
     notify.info(t('notification_suggestion'), t('analyzing_files'));
     
     const formData = new FormData();
-    formData.append('gpxFile', gpxFile);
-    formData.append('videoFile', videoFile);
     formData.append('interpolationLevel', interpolationSlider.value);
+    appendTimezoneFields(formData);
+    formData.append('autoSync', autoSyncCheckbox.checked);
+    formData.append('units', unitsSelect.value);
+    appendPowerFields(formData);
     
     try {
+        await appendAssetFields(formData);
         const response = await fetch('/suggest', { method: 'POST', body: formData });
+        // Asset expirado no servidor: o próximo pedido volta a enviar os arquivos
+        if (response.status === 404) uploadedAssets.clear();
         const data = await response.json();
+        displayTimezone = data.display_timezone || null;
         
         if (data.interpolated_points && data.interpolated_points.length > 0) {
             notify.success(t('notification_suggestion'), t('high_precision_track_loaded'));
             gpxDataPoints = data.interpolated_points.map(p => ({ ...p, time: new Date(p.time) }));
             displayTrack(gpxDataPoints);
             
-            // NOVO: Mostrar informações extras do TCX se disponível
-            if (data.file_type === 'TCX' && data.extra_data) {
+            // NOVO: Mostrar informações extras (TCX, FIT ou GPX com extensões) se disponível
+            if (data.extra_data) {
                 showTcxExtraInfo(data.extra_data, data.sport_type);
             }
         }
@@ -75,6 +93,8 @@ async function fetchAndApplySuggestion() {
             };
             selectSyncPoint(suggestedPoint, true);
             notify.success(t('notification_suggestion'), t('suggestion_applied'));
+            showVideoTimeSource(data.video_time_source);
+            showAutoSyncResult(data.auto_sync);
         } else {
             if (!data.interpolated_points) { 
                 notify.error(t('notification_error'), t('suggestion_error', { message: data.message || 'Unknown error' }));
@@ -86,6 +106,84 @@ async function fetchAndApplySuggestion() {
     }
 }
 
+// Envia o arquivo para /uploads e devolve o asset_id; `onProgress` recebe a fração enviada (0 a 1)
+function uploadAsset(file, onProgress) {
+    return new Promise((resolve, reject) => {
+        const formData = new FormData();
+        formData.append('lastModified', file.lastModified);
+        formData.append('file', file);
+        
+        const xhr = new XMLHttpRequest();
+        xhr.open('POST', '/uploads', true);
+        xhr.upload.onprogress = (event) => {
+            if (onProgress && event.lengthComputable) onProgress(event.loaded / event.total);
+        };
+        xhr.onload = () => {
+            const result = JSON.parse(xhr.responseText);
+            if (xhr.status === 201) {
+                resolve(result.asset_id);
+            } else {
+                reject(new Error(result.message || `HTTP ${xhr.status}`));
+            }
+        };
+        xhr.onerror = () => reject(new Error(t('network_error')));
+        xhr.send(formData);
+    });
+}
+
+function ensureAsset(file, onProgress) {
+    if (!uploadedAssets.has(file)) {
+        const upload = uploadAsset(file, onProgress).catch((error) => {
+            uploadedAssets.delete(file);
+            throw error;
+        });
+        uploadedAssets.set(file, upload);
+    }
+    return uploadedAssets.get(file);
+}
+
+// IDs dos arquivos no formulário; quando a trilha vem do próprio vídeo (GPMF), só o vídeo é enviado
+async function appendAssetFields(formData, onProgress) {
+    if (gpxFile !== videoFile) formData.append('gpxAssetId', await ensureAsset(gpxFile));
+    formData.append('videoAssetId', await ensureAsset(videoFile, onProgress));
+}
+
+// Campos de fuso horário das configurações avançadas (vazios usam o padrão do servidor)
+function appendTimezoneFields(formData) {
+    formData.append('cameraTimezone', cameraTimezoneInput.value.trim());
+    formData.append('displayTimezone', displayTimezoneInput.value.trim());
+    // Data de modificação original do vídeo (último recurso para a hora de início)
+    if (videoFile) formData.append('videoLastModified', videoFile.lastModified);
+}
+
+// Informa de onde veio a hora de início do vídeo; fontes aproximadas pedem confirmação
+function showVideoTimeSource(source) {
+    if (!source) return;
+    const message = t('video_time_source_' + source);
+    if (['timecode', 'file_name', 'file_modified'].includes(source)) {
+        notify.warning(t('video_time_source_title'), `${message} ${t('video_time_source_check')}`);
+    } else {
+        notify.info(t('video_time_source_title'), message);
+    }
+}
+
+// Resultado da sincronização automática; com confiança baixa a sugestão original é mantida
+function showAutoSyncResult(autoSync) {
+    if (!autoSync) {
+        if (autoSyncCheckbox.checked) notify.warning(t('auto_sync_title'), t('auto_sync_failed'));
+        return;
+    }
+    const params = {
+        offset: autoSync.offset_seconds.toFixed(1),
+        confidence: Math.round(autoSync.confidence * 100)
+    };
+    if (autoSync.confidence >= 0.2) {
+        notify.success(t('auto_sync_title'), t('auto_sync_applied', params));
+    } else {
+        notify.warning(t('auto_sync_title'), t('auto_sync_low_confidence', params));
+    }
+}
+
 // NOVA: Função para exibir informações extras do TCX
 function showTcxExtraInfo(extraData, sportType) {
     const trackInfoDiv = document.getElementById('track-info');
@@ -98,6 +196,15 @@ function showTcxExtraInfo(extraData, sportType) {
     
     const stats = [];
     
+    // Valores já formatados pelo servidor nas unidades escolhidas
+    if (extraData.total_distance_meters > 0) {
+        stats.push(t('tcx_distance', { distance: extraData.display_distance }));
+    }
+    
+    if (extraData.max_speed > 0) {
+        stats.push(t('tcx_speed', { avg: extraData.display_average_speed || '-', max: extraData.display_max_speed }));
+    }
+    
     if (extraData.total_calories > 0) {
         stats.push(t('tcx_calories', { calories: Math.round(extraData.total_calories) }));
     }
@@ -116,6 +223,22 @@ function showTcxExtraInfo(extraData, sportType) {
         }));
     }
     
+    if (extraData.average_power && extraData.max_power) {
+        const wattsPerKg = extraData.average_watts_per_kg ? ` (${extraData.average_watts_per_kg.toFixed(1)} W/kg)` : '';
+        stats.push(t('tcx_power', {
+            avg: Math.round(extraData.average_power),
+            max: Math.round(extraData.max_power)
+        }) + wattsPerKg);
+    }
+    
+    if (extraData.normalized_power) {
+        stats.push(t('tcx_power_load', {
+            np: Math.round(extraData.normalized_power),
+            if: extraData.intensity_factor ? extraData.intensity_factor.toFixed(2) : '-',
+            tss: extraData.training_stress_score ? Math.round(extraData.training_stress_score) : '-'
+        }));
+    }
+    
     if (stats.length > 0) {
         infoHtml += '<ul>';
         stats.forEach(stat => {
@@ -5,6 +5,7 @@ class LoadingManager {
         this.overlay = document.getElementById('loading-overlay');
         this.progressBar = document.getElementById('loading-progress-bar');
         this.percentage = document.getElementById('loading-percentage');
+        this.detail = document.getElementById('loading-detail');
         this.cancelBtn = document.getElementById('loading-cancel');
         this.steps = [
             'step-upload',
@@ -15,6 +16,7 @@ class LoadingManager {
         ];
         this.currentStep = 0;
         this.isVisible = false;
+        this.isSimulating = false;
         this.onCancel = null;
         
         this.cancelBtn.addEventListener('click', () => {
@@ -36,9 +38,11 @@ class LoadingManager {
     
     hide() {
         this.isVisible = false;
+        this.isSimulating = false;
         this.overlay.classList.remove('show');
         this.resetSteps();
         this.setProgress(0);
+        this.setDetail('');
     }
     
     setStep(stepIndex, status = 'active') {
@@ -77,6 +81,40 @@ class LoadingManager {
         this.percentage.textContent = `${Math.round(clampedPercentage)}%`;
     }
     
+    setDetail(text) {
+        this.detail.textContent = text;
+    }
+    
+    // Progresso real do servidor (eventos SSE): substitui a simulação.
+    // O upload ocupa os primeiros 15%; a codificação do FFmpeg, o restante.
+    showJobProgress(event) {
+        this.isSimulating = false;
+        
+        if (event.type === 'frames') {
+            this.completeStepsBefore(3);
+            this.setStep(3, 'active');
+            this.setDetail(t('loading_frames', { current: event.current, total: event.total }));
+        } else if (event.type === 'encode') {
+            this.completeStepsBefore(4);
+            this.setStep(4, 'active');
+            if (event.percent !== null) {
+                this.setProgress(15 + event.percent * 0.85);
+            }
+            if (event.eta_secs !== null && event.speed !== null) {
+                this.setDetail(t('loading_eta', {
+                    speed: event.speed.toFixed(1),
+                    time: formatDuration(event.eta_secs)
+                }));
+            }
+        }
+    }
+    
+    completeStepsBefore(stepIndex) {
+        for (let i = 0; i < stepIndex; i++) {
+            this.setStep(i, 'completed');
+        }
+    }
+    
     resetSteps() {
         this.steps.forEach((stepId, index) => {
             const stepElement = document.getElementById(stepId);
@@ -97,9 +135,10 @@ class LoadingManager {
         ];
         
         let currentProgressIndex = 0;
+        this.isSimulating = true;
         
         const updateProgress = () => {
-            if (!this.isVisible || currentProgressIndex >= progressSteps.length) {
+            if (!this.isVisible || !this.isSimulating || currentProgressIndex >= progressSteps.length) {
                 return;
             }
             
@@ -121,7 +160,7 @@ class LoadingManager {
             } else {
                 // Completar última etapa
                 setTimeout(() => {
-                    if (this.isVisible) {
+                    if (this.isVisible && this.isSimulating) {
                         this.setStep(4, 'completed');
                     }
                 }, 1000);
@@ -132,5 +171,14 @@ class LoadingManager {
     }
 }
 
+// "1:05" ou "1:02:05"
+function formatDuration(totalSeconds) {
+    const seconds = Math.max(0, Math.round(totalSeconds));
+    const h = Math.floor(seconds / 3600);
+    const m = Math.floor((seconds % 3600) / 60);
+    const s = String(seconds % 60).padStart(2, '0');
+    return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`;
+}
+
 // Instância global do gerenciador de loading
 const loader = new LoadingManager();
\ No newline at end of file
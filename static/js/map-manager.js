@@ -7,6 +7,8 @@ let userMarker = null;
 let suggestionMarker = null;
 let gpxDataPoints = [];
 let selectedSyncPoint = null;
+let secondAnchorPoint = null;
+let secondAnchorMarker = null;
 
 // Inicialização do mapa
 map = L.map('map').setView([0, 0], 2);
@@ -44,7 +46,9 @@ function displayTrack(points) {
                 closestPoint = p; 
             } 
         }); 
-        if (closestPoint) { 
+        if (closestPoint && secondAnchorToggle.checked) {
+            selectSecondAnchor(closestPoint);
+        } else if (closestPoint) { 
             selectSyncPoint(closestPoint, false); 
         } 
     }); 
@@ -60,7 +64,7 @@ function selectSyncPoint(point, isSuggestion) {
     
     if(isSuggestion) { suggestionMarker = newMarker; } else { userMarker = newMarker; } 
     
-    const pointTime = point.displayTime || new Date(point.time).toLocaleString(currentLang.startsWith('en') ? 'en-US' : 'pt-BR', { timeZone: 'UTC' });
+    const pointTime = point.displayTime || new Date(point.time).toLocaleString(currentLang.startsWith('en') ? 'en-US' : 'pt-BR', { timeZone: displayTimezone || 'UTC' });
     syncPointInfo.textContent = t('sync_point_selected', { 
         type: isSuggestion ? t('suggestion_type') : t('manual_type'), 
         time: pointTime 
@@ -72,4 +76,21 @@ function selectSyncPoint(point, isSuggestion) {
     
     if (videoFile) { positionSection.style.display = 'block'; }
     validateGenerateButton();
-}
\ No newline at end of file
+}
+
+// Segundo ponto de sincronização: com o instante do vídeo informado, corrige a deriva do relógio da câmara
+function selectSecondAnchor(point) {
+    secondAnchorPoint = point;
+    if (secondAnchorMarker) map.removeLayer(secondAnchorMarker);
+    secondAnchorMarker = L.marker([point.lat, point.lon], { icon: userIcon, opacity: 0.6 }).addTo(map);
+
+    const pointTime = new Date(point.time).toLocaleString(currentLang.startsWith('en') ? 'en-US' : 'pt-BR', { timeZone: displayTimezone || 'UTC' });
+    secondAnchorInfo.textContent = t('second_anchor_selected', { time: pointTime });
+}
+
+function clearSecondAnchor() {
+    secondAnchorPoint = null;
+    if (secondAnchorMarker) map.removeLayer(secondAnchorMarker);
+    secondAnchorMarker = null;
+    secondAnchorInfo.textContent = '';
+}
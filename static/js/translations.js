@@ -3,16 +3,22 @@
 const translations = {
     'en': {
         'main_title': '🎬 GPX/TCX Video Sync',
-        'intro_text': 'Upload your track files (GPX or TCX), select a sync point on the map, and configure the overlays to generate your final video with telemetry.',
-        'step1_title': 'Select Files', 'gpx_file_label': 'Track File (GPX/TCX)', 'choose_gpx': 'Choose GPX/TCX', 'no_gpx_selected': 'No file selected', 'video_file_label': 'Video File', 'choose_video': 'Choose Video', 'select_gpx_first': 'Select a track file first',
-        'step2_title': 'Select Sync Point', 'map_click_prompt': '🎯 Click a point on the map to set it as the sync start.', 'step3_title': 'Positioning', 'speedo_label': '⏱️ Speedometer', 'map_label': '🗺️ Track Map', 'stats_label': '📊 Statistics',
+        'intro_text': 'Upload your track files (GPX, TCX or FIT), select a sync point on the map, and configure the overlays to generate your final video with telemetry.',
+        'step1_title': 'Select Files', 'gpx_file_label': 'Track File (GPX/TCX/FIT/SRT/GoPro)', 'choose_gpx': 'Choose GPX/TCX/FIT/SRT/GoPro', 'no_gpx_selected': 'No file selected', 'video_file_label': 'Video File', 'choose_video': 'Choose Video', 'select_gpx_first': 'Select a track file first',
+        'step2_title': 'Select Sync Point', 'map_click_prompt': '🎯 Click a point on the map to set it as the sync start.', 'step3_title': 'Positioning', 'speedo_label': '⏱️ Speedometer', 'map_label': '🗺️ Track Map', 'stats_label': '📊 Statistics', 'layout_label': 'Layout', 'layout_custom': 'Corners chosen above', 'layout_file_label': 'Layout file (JSON/TOML)',
         'generate_button': 'Confirm and Generate Video', 'download_link': '📥 Download Final Video', 'logs_title': '📋 Processing Logs:',
         'gpx_loaded': 'Track file loaded successfully', 'can_select_video': 'You can now select the video file', 'analyzing_files': 'Analyzing files to suggest sync point and track...', 'high_precision_track_loaded': 'High-precision track loaded from server.',
         'tcx_extra_data_loaded': 'TCX file detected! Extra telemetry data available: heart rate, cadence, and more.',
         'suggestion_applied': 'Automatic suggestion applied! You can adjust it on the map if needed.', 'suggestion_error': 'Could not get suggestion: {{message}}. Please select a point manually.', 'suggestion_comm_error': 'Communication error while getting suggestion. Please select a point manually.',
         'sync_point_selected': 'Point selected ({{type}}): {{time}} (UTC)', 'manual_type': 'manual', 'suggestion_type': 'suggestion', 'error_missing_files': 'Error: Please select both files and a sync point.',
         'uploading_files': 'Uploading files...', 'success_message': 'Success! Your video is ready.', 'server_error': 'Error: {{message}}', 'network_error': 'Network error while uploading files.',
-        'settings_title': 'Advanced Settings', 'interpolation_label': 'Interpolation Precision Level', 'interpolation_desc': 'Lower value = more points = higher precision and slower processing.',
+        'settings_title': 'Advanced Settings', 'interpolation_label': 'Interpolation Precision Level', 'interpolation_desc': 'Lower value = more points = higher precision and slower processing.', 'camera_timezone_label': 'Camera Timezone', 'camera_timezone_desc': 'Timezone of the camera clock (IANA name). Empty = America/Sao_Paulo.', 'display_timezone_label': 'Display Timezone', 'display_timezone_desc': 'Timezone of the displayed times. "auto" = from the first track coordinate. Empty = same as the camera.',
+        'units_label': 'Units', 'units_desc': 'Units of the values drawn on the video and of the track summary.', 'units_metric': 'Metric (km/h, km, m)', 'units_imperial': 'Imperial (mph, mi, ft)', 'units_nautical': 'Nautical (kn, nm, m)', 'units_pace_km': 'Pace (min/km)', 'units_pace_mi': 'Pace (min/mi)',
+        'hr_zones_label': 'Heart Rate Zones', 'hr_zones_desc': 'Used by the heart rate widget ("training" layout). Empty = max HR of 190 bpm.', 'hr_zones_auto': 'Automatic', 'hr_zones_max_hr': '% of max HR', 'hr_zones_lthr': '% of threshold (LTHR)', 'hr_zones_reserve': '% of reserve (Karvonen)', 'max_hr_placeholder': 'Max HR', 'resting_hr_placeholder': 'Resting HR',
+        'power_label': 'Power', 'power_desc': 'FTP for IF and TSS and weight for W/kg, in the summary, the statistics and the power widget.', 'ftp_placeholder': 'FTP (W)', 'weight_placeholder': 'Weight (kg)',
+        'video_time_source_title': 'Video start time', 'video_time_source_stream_tag': 'Read from the video stream creation_time tag.', 'video_time_source_format_tag': 'Read from the container creation_time tag.', 'video_time_source_quick_time': 'Read from the QuickTime/Apple creation date.', 'video_time_source_gpmf_gps': 'Read from the GoPro GPS clock (exact).', 'video_time_source_timecode': 'Estimated from the timecode track.', 'video_time_source_file_name': 'Estimated from the date in the file name.', 'video_time_source_file_modified': 'Estimated from the file modification date.', 'video_time_source_check': 'Check the sync point on the map.',
+        'second_anchor_label': 'Second sync point (corrects clock drift)', 'second_anchor_selected': '2nd point: {{time}} — enter the matching video time (mm:ss).',
+        'auto_sync_label': 'Automatic Sync', 'auto_sync_desc': 'Compares the video motion with the track speed to correct the camera clock. Analysis may take a while on long videos.', 'auto_sync_title': 'Automatic sync', 'auto_sync_applied': 'Camera clock corrected by {{offset}} s (confidence {{confidence}}%).', 'auto_sync_low_confidence': 'Best offset {{offset}} s, but confidence is low ({{confidence}}%). The suggestion was not changed.', 'auto_sync_failed': 'Could not correlate the video motion with the track speed.',
         'speedo_hint': 'Displays a speedometer with the current speed on the video.',
         'map_hint': 'Shows a mini-map with the traveled path and current position.',
         'stats_hint': 'Adds a panel with statistics like distance, time, and elevation.',
@@ -22,6 +28,10 @@ const translations = {
         'tcx_calories': 'Total calories: {{calories}}',
         'tcx_heart_rate': 'Avg/Max heart rate: {{avg}}/{{max}} bpm',
         'tcx_cadence': 'Avg/Max cadence: {{avg}}/{{max}}',
+        'tcx_distance': 'Total distance: {{distance}}',
+        'tcx_speed': 'Avg/Max speed: {{avg}} / {{max}}',
+        'tcx_power': 'Avg/Max power: {{avg}}/{{max}} W',
+        'tcx_power_load': 'NP {{np}} W · IF {{if}} · TSS {{tss}}',
         // Notificações
         'notification_gpx_loaded': 'Track Loaded',
         'notification_video_loaded': 'Video Loaded', 
@@ -39,21 +49,31 @@ const translations = {
         'step_overlays': 'Applying overlays',
         'step_render': 'Rendering final video',
         'loading_cancel': 'Cancel Processing',
+        'loading_frames': 'Overlay frames: {{current}} of {{total}}',
+        'cancel_title': 'Cancelled',
+        'cancel_message': 'Processing cancelled by the user.',
+        'loading_eta': '{{speed}}x · about {{time}} remaining',
         'download_tooltip': 'Download ready video',
         'settings_tooltip': 'Settings'
     },
     'pt-BR': {
         'main_title': '🎬 GPX/TCX Video Sync',
-        'intro_text': 'Carregue os seus ficheiros de trilha (GPX ou TCX), selecione um ponto de sincronização no mapa e configure os overlays para gerar o seu vídeo final com telemetria.',
-        'step1_title': 'Selecionar Ficheiros', 'gpx_file_label': 'Ficheiro de Trilha (GPX/TCX)', 'choose_gpx': 'Escolher GPX/TCX', 'no_gpx_selected': 'Nenhum ficheiro selecionado', 'video_file_label': 'Ficheiro de Vídeo', 'choose_video': 'Escolher Vídeo', 'select_gpx_first': 'Selecione um ficheiro de trilha primeiro',
-        'step2_title': 'Selecionar Ponto de Sincronização', 'map_click_prompt': '🎯 Clique num ponto no mapa para o definir como o início da sincronização.', 'step3_title': 'Posicionamento', 'speedo_label': '⚙️ Velocímetro', 'map_label': '🗺️ Mapa do Trajeto', 'stats_label': '📊 Estatísticas',
+        'intro_text': 'Carregue os seus ficheiros de trilha (GPX, TCX ou FIT), selecione um ponto de sincronização no mapa e configure os overlays para gerar o seu vídeo final com telemetria.',
+        'step1_title': 'Selecionar Ficheiros', 'gpx_file_label': 'Ficheiro de Trilha (GPX/TCX/FIT/SRT/GoPro)', 'choose_gpx': 'Escolher GPX/TCX/FIT/SRT/GoPro', 'no_gpx_selected': 'Nenhum ficheiro selecionado', 'video_file_label': 'Ficheiro de Vídeo', 'choose_video': 'Escolher Vídeo', 'select_gpx_first': 'Selecione um ficheiro de trilha primeiro',
+        'step2_title': 'Selecionar Ponto de Sincronização', 'map_click_prompt': '🎯 Clique num ponto no mapa para o definir como o início da sincronização.', 'step3_title': 'Posicionamento', 'speedo_label': '⚙️ Velocímetro', 'map_label': '🗺️ Mapa do Trajeto', 'stats_label': '📊 Estatísticas', 'layout_label': 'Layout', 'layout_custom': 'Cantos escolhidos acima', 'layout_file_label': 'Arquivo de layout (JSON/TOML)',
         'generate_button': 'Confirmar e Gerar Vídeo', 'download_link': '📥 Descarregar Vídeo Final', 'logs_title': '📋 Logs do Processamento:',
         'gpx_loaded': 'Ficheiro de trilha carregado com sucesso', 'can_select_video': 'Agora pode selecionar o ficheiro de vídeo', 'analyzing_files': 'Analisando ficheiros para sugerir ponto e percurso...', 'high_precision_track_loaded': 'Percurso de alta precisão carregado do servidor.',
         'tcx_extra_data_loaded': 'Ficheiro TCX detectado! Dados extra de telemetria disponíveis: frequência cardíaca, cadência e mais.',
         'suggestion_applied': 'Sugestão automática aplicada! Pode ajustar no mapa se necessário.', 'suggestion_error': 'Não foi possível obter sugestão: {{message}}. Selecione um ponto manualmente.', 'suggestion_comm_error': 'Erro de comunicação ao obter sugestão. Selecione um ponto manualmente.',
         'sync_point_selected': 'Ponto selecionado ({{type}}): {{time}} (UTC)', 'manual_type': 'manual', 'suggestion_type': 'sugestão', 'error_missing_files': 'Erro: Por favor, selecione os dois ficheiros e um ponto de sincronização.',
         'uploading_files': 'A enviar ficheiros...', 'success_message': 'Sucesso! O seu vídeo está pronto.', 'server_error': 'Erro: {{message}}', 'network_error': 'Erro de rede ao enviar os ficheiros.',
-        'settings_title': 'Configurações Avançadas', 'interpolation_label': 'Nível de Precisão da Interpolação', 'interpolation_desc': 'Menor valor = mais pontos = maior precisão e processamento mais lento.',
+        'settings_title': 'Configurações Avançadas', 'interpolation_label': 'Nível de Precisão da Interpolação', 'interpolation_desc': 'Menor valor = mais pontos = maior precisão e processamento mais lento.', 'camera_timezone_label': 'Fuso Horário da Câmara', 'camera_timezone_desc': 'Fuso do relógio da câmara (nome IANA). Vazio = America/Sao_Paulo.', 'display_timezone_label': 'Fuso Horário de Exibição', 'display_timezone_desc': 'Fuso das horas mostradas. "auto" = pela primeira coordenada da trilha. Vazio = igual ao da câmara.',
+        'units_label': 'Unidades', 'units_desc': 'Unidades dos valores desenhados no vídeo e do resumo da trilha.', 'units_metric': 'Métrico (km/h, km, m)', 'units_imperial': 'Imperial (mph, mi, ft)', 'units_nautical': 'Náutico (nós, mn, m)', 'units_pace_km': 'Ritmo (min/km)', 'units_pace_mi': 'Ritmo (min/mi)',
+        'hr_zones_label': 'Zonas de Frequência Cardíaca', 'hr_zones_desc': 'Usadas pelo widget de frequência cardíaca (layout "training"). Vazio = FC máxima de 190 bpm.', 'hr_zones_auto': 'Automático', 'hr_zones_max_hr': '% da FC máxima', 'hr_zones_lthr': '% do limiar (LTHR)', 'hr_zones_reserve': '% da reserva (Karvonen)', 'max_hr_placeholder': 'FC máx.', 'resting_hr_placeholder': 'FC repouso',
+        'power_label': 'Potência', 'power_desc': 'FTP para o IF e o TSS e peso para os W/kg, no resumo, nas estatísticas e no widget de potência.', 'ftp_placeholder': 'FTP (W)', 'weight_placeholder': 'Peso (kg)',
+        'video_time_source_title': 'Início do vídeo', 'video_time_source_stream_tag': 'Lido da tag creation_time do stream de vídeo.', 'video_time_source_format_tag': 'Lido da tag creation_time do contentor.', 'video_time_source_quick_time': 'Lido da data de criação QuickTime/Apple.', 'video_time_source_gpmf_gps': 'Lido do relógio do GPS da GoPro (exato).', 'video_time_source_timecode': 'Estimado pela faixa de timecode.', 'video_time_source_file_name': 'Estimado pela data no nome do arquivo.', 'video_time_source_file_modified': 'Estimado pela data de modificação do arquivo.', 'video_time_source_check': 'Confirme o ponto de sincronização no mapa.',
+        'second_anchor_label': 'Segundo ponto de sincronização (corrige a deriva do relógio)', 'second_anchor_selected': '2º ponto: {{time}} — indique o momento correspondente do vídeo (mm:ss).',
+        'auto_sync_label': 'Sincronização Automática', 'auto_sync_desc': 'Compara o movimento do vídeo com a velocidade da trilha para corrigir o relógio da câmara. A análise pode demorar em vídeos longos.', 'auto_sync_title': 'Sincronização automática', 'auto_sync_applied': 'Relógio da câmara corrigido em {{offset}} s (confiança {{confidence}}%).', 'auto_sync_low_confidence': 'Melhor desvio {{offset}} s, mas a confiança é baixa ({{confidence}}%). A sugestão não foi alterada.', 'auto_sync_failed': 'Não foi possível correlacionar o movimento do vídeo com a velocidade da trilha.',
         'speedo_hint': 'Exibe um velocímetro com a velocidade atual no vídeo.',
         'map_hint': 'Mostra um mini-mapa com o trajeto percorrido e a posição atual.',
         'stats_hint': 'Adiciona um painel com estatísticas como distância, tempo, elevação e dados TCX (frequência cardíaca, cadência, calorias).',
@@ -63,6 +83,10 @@ const translations = {
         'tcx_calories': 'Calorias totais: {{calories}}',
         'tcx_heart_rate': 'Freq. cardíaca média/máx: {{avg}}/{{max}} bpm',
         'tcx_cadence': 'Cadência média/máx: {{avg}}/{{max}}',
+        'tcx_distance': 'Distância total: {{distance}}',
+        'tcx_speed': 'Velocidade média/máx: {{avg}} / {{max}}',
+        'tcx_power': 'Potência média/máx: {{avg}}/{{max}} W',
+        'tcx_power_load': 'NP {{np}} W · IF {{if}} · TSS {{tss}}',
         // Notificações
         'notification_gpx_loaded': 'Trilha Carregada',
         'notification_video_loaded': 'Vídeo Carregado',
@@ -80,6 +104,10 @@ const translations = {
         'step_overlays': 'Aplicando overlays',
         'step_render': 'Renderizando vídeo final',
         'loading_cancel': 'Cancelar Processamento',
+        'loading_frames': 'Frames de overlay: {{current}} de {{total}}',
+        'cancel_title': 'Cancelamento',
+        'cancel_message': 'Processamento cancelado pelo usuário.',
+        'loading_eta': '{{speed}}x · cerca de {{time}} restantes',
         'download_tooltip': 'Baixar vídeo pronto',
         'settings_tooltip': 'Configurações'
     }
@@ -109,6 +137,9 @@ function setLanguage(lang) {
             el.textContent = t(key);
         }
     });
+    document.querySelectorAll('[data-i18n-placeholder]').forEach(el => {
+        el.placeholder = t(el.getAttribute('data-i18n-placeholder'));
+    });
     document.getElementById('lang-pt').classList.toggle('active', lang === 'pt-BR');
     document.getElementById('lang-en').classList.toggle('active', lang === 'en');
     
@@ -3,7 +3,7 @@
 function validateGenerateButton() {
     const hasFiles = gpxFile && videoFile;
     const hasSyncPoint = selectedSyncPoint !== null;
-    const hasActiveOverlay = inlineOverlayManager ? inlineOverlayManager.hasActiveOverlays() : false;
+    const hasActiveOverlay = hasLayout() || (inlineOverlayManager ? inlineOverlayManager.hasActiveOverlays() : false);
     
     const canGenerate = hasFiles && hasSyncPoint && hasActiveOverlay;
     
@@ -16,23 +16,57 @@ function validateGenerateButton() {
     }
 }
 
+// Um layout incluído ou um arquivo de layout substitui os cantos escolhidos nos widgets
+function hasLayout() {
+    return Boolean(layoutPresetSelect.value || layoutFileInput.files.length);
+}
+
+async function appendLayoutFile(formData) {
+    const file = layoutFileInput.files[0];
+    if (file) formData.append('layout', await file.text());
+}
+
+async function loadLayoutPresets() {
+    try {
+        const response = await fetch('/layouts');
+        if (!response.ok) return;
+        for (const preset of await response.json()) {
+            const option = document.createElement('option');
+            option.value = preset.name;
+            option.textContent = preset.name;
+            layoutPresetSelect.appendChild(option);
+        }
+    } catch (error) {
+        console.warn('Layouts indisponíveis:', error);
+    }
+}
+
 function handleGenerateWithInlineOverlays() {
     if (!gpxFile || !videoFile || !selectedSyncPoint) { 
         notify.error(t('notification_error'), t('error_missing_files'));
         return; 
     }
     
-    if (!inlineOverlayManager.hasActiveOverlays()) {
+    if (!hasLayout() && !inlineOverlayManager.hasActiveOverlays()) {
         notify.error(t('notification_error'), t('error_missing_overlay'));
         return;
     }
     
     generateBtn.disabled = true;
+    currentJobId = null;
+    processXhr = null;
+    generationCancelled = false;
     
     // Mostrar loading com simulação realística
     loader.show(() => {
-        // Função de cancelamento (se o servidor suportar)
-        notify.warning('Cancelamento', 'Processamento cancelado pelo usuário');
+        // Antes de o job existir basta não o criar; depois o servidor cancela-o
+        generationCancelled = true;
+        if (currentJobId) {
+            fetch(`/jobs/${currentJobId}`, { method: 'DELETE' }).catch(() => {});
+        } else if (processXhr) {
+            processXhr.abort();
+        }
+        notify.warning(t('cancel_title'), t('cancel_message'));
         generateBtn.disabled = false;
         validateGenerateButton();
     });
@@ -45,11 +79,16 @@ function handleGenerateWithInlineOverlays() {
     downloadBtn.classList.remove('show'); // Ocultar botão de download
     
     const formData = new FormData();
-    formData.append('gpxFile', gpxFile);
-    formData.append('videoFile', videoFile);
     formData.append('syncTimestamp', selectedSyncPoint.time.toISOString());
+    if (secondAnchorToggle.checked && secondAnchorPoint && secondAnchorVideoTime.value.trim()) {
+        formData.append('secondSyncVideoTime', secondAnchorVideoTime.value.trim());
+        formData.append('secondSyncTimestamp', new Date(secondAnchorPoint.time).toISOString());
+    }
     formData.append('lang', currentLang);
     formData.append('interpolationLevel', interpolationSlider.value);
+    appendTimezoneFields(formData);
+    formData.append('units', unitsSelect.value);
+    appendAthleteFields(formData);
 
     const overlayConfig = inlineOverlayManager.getConfiguration();
     
@@ -67,35 +106,42 @@ function handleGenerateWithInlineOverlays() {
     if (overlayConfig.addStatsOverlay) { 
         formData.append('statsPosition', overlayConfig.statsPosition); 
     }
+    formData.append('layoutPreset', layoutPresetSelect.value);
 
+    // Os arquivos já enviados em /suggest não voltam a ser enviados; upload = 15% do total
+    appendLayoutFile(formData)
+        .then(() => appendAssetFields(formData, (fraction) => loader.setProgress(Math.round(fraction * 15))))
+        .then(() => {
+            if (!generationCancelled) sendProcessRequest(formData);
+        })
+        .catch((error) => {
+            if (generationCancelled) return;
+            loader.hide();
+            notify.error(t('notification_error'), t('server_error', { message: error.message }));
+            generateBtn.disabled = false;
+            validateGenerateButton();
+        });
+}
+
+function sendProcessRequest(formData) {
     const xhr = new XMLHttpRequest();
+    processXhr = xhr;
     xhr.open('POST', '/process', true);
     
-    // Monitorar progresso de upload
-    xhr.upload.onprogress = (event) => { 
-        if (event.lengthComputable) { 
-            const uploadPercentage = Math.round((event.loaded / event.total) * 15); // Upload = 15% do total
-            loader.setProgress(uploadPercentage);
-        } 
-    };
-    
     xhr.onload = () => {
-        loader.hide();
-        
         const result = JSON.parse(xhr.responseText);
-        logsPre.textContent = result.logs.join('\n');
-        logsContainer.style.display = 'block';
+        // Asset expirado no servidor: a próxima tentativa volta a enviar os arquivos
+        if (xhr.status === 404) uploadedAssets.clear();
         
-        if (xhr.status >= 200 && xhr.status < 300) {
-            notify.success(t('notification_success'), t('success_message'));
-            if (result.download_url) { 
-                downloadBtn.href = result.download_url;
-                downloadBtn.classList.add('show'); // Mostrar botão discreto
-            }
-        } else {
-            notify.error(t('notification_error'), t('server_error', { message: result.message || 'Unknown error' }));
+        if (xhr.status === 202 && result.status_url) {
+            // O servidor pôs o job na fila: acompanhar os eventos até terminar
+            currentJobId = result.job_id;
+            followJobEvents(result.events_url, result.status_url);
+            return;
         }
         
+        loader.hide();
+        notify.error(t('notification_error'), t('server_error', { message: result.message || 'Unknown error' }));
         generateBtn.disabled = false;
         validateGenerateButton();
     };
@@ -108,4 +154,106 @@ function handleGenerateWithInlineOverlays() {
     };
     
     xhr.send(formData);
-}
\ No newline at end of file
+}
+
+const JOB_POLL_INTERVAL_MS = 1000;
+const FINISHED_JOB_STATES = ['completed', 'failed', 'cancelled'];
+let currentJobId = null;
+let processXhr = null;
+let generationCancelled = false;
+
+// Logs e progresso em tempo real por Server-Sent Events; sem suporte, recorre à consulta periódica
+function followJobEvents(eventsUrl, statusUrl) {
+    if (!eventsUrl || typeof EventSource === 'undefined') {
+        pollJobStatus(statusUrl);
+        return;
+    }
+    
+    const logLines = [];
+    logsPre.textContent = '';
+    logsContainer.style.display = 'block';
+    
+    const source = new EventSource(eventsUrl);
+    source.addEventListener('log', (e) => {
+        logLines.push(JSON.parse(e.data).message);
+        logsPre.textContent = logLines.join('\n');
+    });
+    source.addEventListener('frames', (e) => loader.showJobProgress(JSON.parse(e.data)));
+    source.addEventListener('encode', (e) => loader.showJobProgress(JSON.parse(e.data)));
+    source.addEventListener('state', (e) => {
+        const change = JSON.parse(e.data);
+        if (FINISHED_JOB_STATES.includes(change.state)) {
+            source.close();
+            pollJobStatus(statusUrl);
+        }
+    });
+    source.onerror = () => {
+        // Ligação perdida: o estado final é obtido pela consulta periódica
+        source.close();
+        pollJobStatus(statusUrl);
+    };
+}
+
+function pollJobStatus(statusUrl) {
+    fetch(statusUrl)
+        .then(response => {
+            if (!response.ok) throw new Error(`HTTP ${response.status}`);
+            return response.json();
+        })
+        .then(job => {
+            if (job.state === 'queued' || job.state === 'running') {
+                setTimeout(() => pollJobStatus(statusUrl), JOB_POLL_INTERVAL_MS);
+                return;
+            }
+            finishJob(job);
+        })
+        .catch(() => {
+            loader.hide();
+            notify.error(t('notification_error'), t('network_error'));
+            generateBtn.disabled = false;
+            validateGenerateButton();
+        });
+}
+
+function finishJob(job) {
+    currentJobId = null;
+    loader.hide();
+    
+    if (job.state === 'cancelled') {
+        // O aviso já foi mostrado quando o utilizador cancelou
+        generateBtn.disabled = false;
+        validateGenerateButton();
+        return;
+    }
+    
+    logsPre.textContent = job.logs.join('\n');
+    logsContainer.style.display = 'block';
+    
+    if (job.state === 'completed') {
+        notify.success(t('notification_success'), t('success_message'));
+        if (job.download_url) { 
+            downloadBtn.href = job.download_url;
+            downloadBtn.classList.add('show'); // Mostrar botão discreto
+        }
+    } else {
+        notify.error(t('notification_error'), t('server_error', { message: job.error || 'Unknown error' }));
+    }
+    
+    generateBtn.disabled = false;
+    validateGenerateButton();
+}
+
+// Zonas de FC, FTP e peso do atleta (campos vazios são ignorados pelo servidor)
+function appendAthleteFields(formData) {
+    formData.append('hrZoneMethod', hrZoneMethodSelect.value);
+    formData.append('maxHeartRate', maxHeartRateInput.value);
+    formData.append('restingHeartRate', restingHeartRateInput.value);
+    formData.append('lthr', lthrInput.value);
+    appendPowerFields(formData);
+}
+
+// FTP e peso, também usados no resumo do /suggest
+function appendPowerFields(formData) {
+    formData.append('ftp', ftpInput.value);
+    formData.append('weightKg', weightKgInput.value);
+}
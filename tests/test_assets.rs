@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use axum::http::StatusCode;
+    use chrono::Utc;
+    use uuid::Uuid;
+    use extrator_gpx::assets::AssetStore;
+    use extrator_gpx::upload::StoredUpload;
+
+    fn temp_store(ttl: Duration) -> (AssetStore, PathBuf) {
+        let root = std::env::temp_dir().join(format!("assets_{}", Uuid::new_v4()));
+        (AssetStore::new(root.join("assets"), ttl), root)
+    }
+
+    fn store_file(store: &AssetStore, name: &str, content: &[u8]) -> extrator_gpx::assets::Asset {
+        let path = store.dir().unwrap().join(format!("{}-{}", Uuid::new_v4(), name));
+        std::fs::write(&path, content).unwrap();
+        store.register(StoredUpload {
+            path,
+            file_name: name.to_string(),
+            size: content.len() as u64,
+            sha256: "0".repeat(64),
+        })
+    }
+
+    #[test]
+    fn test_asset_is_resolved_by_id() {
+        let (store, root) = temp_store(Duration::from_secs(60));
+        let asset = store_file(&store, "treino.gpx", b"<gpx/>");
+
+        let resolved = store.resolve(&asset.asset_id.to_string()).unwrap();
+        assert_eq!(resolved.path, asset.path);
+        assert_eq!(resolved.file_name, "treino.gpx");
+        assert!(resolved.expires_at >= asset.expires_at, "Each use extends the expiry");
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn test_linked_copy_is_independent_of_the_asset() {
+        let (store, root) = temp_store(Duration::from_secs(60));
+        let asset = store_file(&store, "GX010042.MP4", b"video");
+        let job_dir = root.join("job");
+        std::fs::create_dir_all(&job_dir).unwrap();
+
+        let linked = store.resolve_into(&asset.asset_id.to_string(), &job_dir).unwrap();
+        assert!(linked.starts_with(&job_dir));
+        assert!(linked.to_string_lossy().ends_with("-GX010042.MP4"), "The original name is kept");
+        assert_eq!(std::fs::read(&linked).unwrap(), b"video");
+
+        // O job apaga o seu diretório; o asset continua disponível para o próximo pedido
+        std::fs::remove_dir_all(&job_dir).unwrap();
+        assert!(asset.path.exists());
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn test_expired_assets_are_removed() {
+        let (store, root) = temp_store(Duration::from_secs(60));
+        let asset = store_file(&store, "video.mp4", b"video");
+
+        assert!(store.remove_expired(Utc::now()).is_empty());
+        let removed = store.remove_expired(Utc::now() + chrono::Duration::minutes(2));
+        assert_eq!(removed, vec![asset.asset_id]);
+        assert!(!asset.path.exists());
+
+        let error = store.resolve(&asset.asset_id.to_string()).unwrap_err();
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn test_invalid_asset_id() {
+        let (store, root) = temp_store(Duration::from_secs(60));
+        assert_eq!(store.resolve("nao-e-um-uuid").unwrap_err().status(), StatusCode::BAD_REQUEST);
+        assert_eq!(store.resolve(&Uuid::new_v4().to_string()).unwrap_err().status(), StatusCode::NOT_FOUND);
+        std::fs::remove_dir_all(root).ok();
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use geo_types::Point;
+    use gpx::{Gpx, Track, TrackSegment, Waypoint};
+    use extrator_gpx::auto_sync::{estimate_offset, frame_difference, SIGNAL_RATE_HZ, DEFAULT_MAX_OFFSET_SECS};
+
+    const TRACK_SECS: i64 = 1200;
+
+    fn track_start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 15, 7, 0, 0).unwrap()
+    }
+
+    // Velocidade irregular (km/h): passeio aleatório determinístico, para que só um desvio encaixe
+    fn speed_profile() -> Vec<f64> {
+        let mut seed: u64 = 42;
+        let mut speed = 20.0_f64;
+        (0..=TRACK_SECS)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let step = ((seed >> 33) % 1000) as f64 / 1000.0 * 6.0 - 3.0;
+                speed = (speed + step).clamp(5.0, 40.0);
+                speed
+            })
+            .collect()
+    }
+
+    // Trilha a 1 Hz rumo ao norte com a velocidade de `speed_profile`
+    fn synthetic_track() -> Gpx {
+        let mut segment = TrackSegment::new();
+        let mut lat = -10.0;
+        for (second, speed) in (0..=TRACK_SECS).zip(speed_profile()) {
+            let mut point = Waypoint::new(Point::new(-48.0, lat));
+            let time = track_start() + Duration::seconds(second);
+            point.time = Some(gpx::Time::from(time::OffsetDateTime::from_unix_timestamp(time.timestamp()).unwrap()));
+            segment.points.push(point);
+            lat += speed / 3.6 / 111_195.0;
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        Gpx { tracks: vec![track], ..Default::default() }
+    }
+
+    // Movimento medido num vídeo de 5 min que começou de facto `real_start_secs` após o início da trilha
+    fn synthetic_motion(real_start_secs: f64) -> Vec<f64> {
+        let speed = speed_profile();
+        (0..600)
+            .map(|k| {
+                let track_secs = real_start_secs + (k as f64 + 0.5) / SIGNAL_RATE_HZ;
+                let jitter = ((k * 7919) % 13) as f64 * 0.1;
+                2.0 + 0.6 * speed[track_secs.floor() as usize] + jitter
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_offset_recovered_from_motion() {
+        // O relógio da câmara está 42 s atrasado em relação ao GPS
+        let real_start = track_start() + Duration::seconds(300);
+        let camera_start = real_start - Duration::seconds(42);
+
+        let result = estimate_offset(&synthetic_motion(300.0), &synthetic_track(), camera_start, DEFAULT_MAX_OFFSET_SECS)
+            .expect("Should find an offset");
+
+        assert!((result.offset_seconds - 42.0).abs() <= 1.0, "Offset was {}", result.offset_seconds);
+        assert!(result.correlation > 0.9, "Correlation was {}", result.correlation);
+        assert!(result.is_reliable(), "Confidence was {}", result.confidence);
+    }
+
+    #[test]
+    fn test_negative_offset() {
+        // Relógio da câmara adiantado 95 s
+        let camera_start = track_start() + Duration::seconds(600 + 95);
+        let result = estimate_offset(&synthetic_motion(600.0), &synthetic_track(), camera_start, DEFAULT_MAX_OFFSET_SECS)
+            .unwrap();
+        assert!((result.offset_seconds + 95.0).abs() <= 1.0, "Offset was {}", result.offset_seconds);
+    }
+
+    #[test]
+    fn test_static_video_has_no_estimate() {
+        let motion = vec![4.0; 600];
+        let camera_start = track_start() + Duration::seconds(300);
+        assert!(estimate_offset(&motion, &synthetic_track(), camera_start, DEFAULT_MAX_OFFSET_SECS).is_none());
+    }
+
+    #[test]
+    fn test_video_outside_track_has_no_estimate() {
+        let camera_start = track_start() + Duration::hours(3);
+        assert!(estimate_offset(&synthetic_motion(300.0), &synthetic_track(), camera_start, 60.0).is_none());
+    }
+
+    #[test]
+    fn test_frame_difference() {
+        let black = vec![0u8; 16];
+        let mut half_white = vec![0u8; 16];
+        half_white[..8].fill(255);
+        assert_eq!(frame_difference(&black, &black), 0.0);
+        assert_eq!(frame_difference(&black, &half_white), 127.5);
+        assert_eq!(frame_difference(&half_white, &black), 127.5);
+    }
+}
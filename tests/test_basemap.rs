@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use geo_types::Point;
+    use gpx::{Gpx, Track, TrackSegment, Waypoint};
+    use image::{Rgba, RgbaImage};
+    use extrator_gpx::{AthleteProfile, UnitSystem};
+    use extrator_gpx::basemap::{render_basemap, zoom_for, TileSource};
+    use extrator_gpx::drawing::MapProjection;
+    use extrator_gpx::layout::{Anchor, Layout, LayoutResources, WidgetLayout};
+    use extrator_gpx::mbtiles::MbtilesReader;
+    use extrator_gpx::widget::{Compositor, WidgetRegistry};
+
+    const FIXTURE: &str = "tests/fixtures/basemap.mbtiles";
+    const DEDUPLICATED_FIXTURE: &str = "tests/fixtures/basemap_dedup.mbtiles";
+
+    // Cores dos tiles de zoom 10 à volta de lat 0, lon 0 nos fixtures
+    const NORTH_WEST: [u8; 3] = [200, 30, 30];
+    const NORTH_EAST: [u8; 3] = [30, 200, 30];
+    const SOUTH_EAST: [u8; 3] = [220, 220, 30];
+
+    // ~1.6 km para sudeste a cruzar o equador no meridiano de Greenwich, que ficam no centro do
+    // mapa, onde se encontram os quatro tiles
+    fn diagonal_track() -> Gpx {
+        let mut segment = TrackSegment::new();
+        for i in -5..=5 {
+            segment.points.push(Waypoint::new(Point::new(i as f64 * 0.001, -i as f64 * 0.001)));
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        Gpx { tracks: vec![track], ..Default::default() }
+    }
+
+    fn track_map(options: &[(&str, &str)]) -> RgbaImage {
+        let mut widget = WidgetLayout::new("track_map", Anchor::TopLeft);
+        widget.options = options.iter().map(|(name, value)| (name.to_string(), serde_json::json!(value))).collect();
+        let layout = Layout { widgets: vec![widget], ..Default::default() };
+        let compositor = Compositor::new(&layout, &WidgetRegistry::builtin(), &diagonal_track(), (1920, 1080), "pt", UnitSystem::Metric, &AthleteProfile::default()).unwrap();
+        let canvas = compositor.compose(None).unwrap();
+        // Tamanho natural 300x300 à margem padrão
+        image::imageops::crop_imm(&canvas, 10, 10, 300, 300).to_image()
+    }
+
+    fn rgb(pixel: &Rgba<u8>) -> [u8; 3] {
+        [pixel.0[0], pixel.0[1], pixel.0[2]]
+    }
+
+    #[test]
+    fn test_mbtiles_tiles_and_metadata() {
+        let mut reader = MbtilesReader::open(Path::new(FIXTURE)).unwrap();
+        assert_eq!(reader.metadata("format"), Some("png"));
+        assert_eq!(reader.zoom_range(), Some((9, 10)));
+
+        // Linhas XYZ: 511 é o tile a norte do equador (linha 512 no TMS do arquivo)
+        let north_east = image::load_from_memory(&reader.tile(10, 512, 511).unwrap().unwrap()).unwrap().to_rgba8();
+        assert_eq!(rgb(north_east.get_pixel(100, 100)), NORTH_EAST);
+        // Tile maior que uma página, lido pelas páginas de overflow
+        let noise = image::load_from_memory(&reader.tile(10, 0, 0).unwrap().unwrap()).unwrap();
+        assert_eq!((noise.width(), noise.height()), (64, 64));
+        // Os 600 tiles de zoom 9 espalham a tabela e o índice por várias páginas
+        let filler = image::load_from_memory(&reader.tile(9, 17, 511 - 13).unwrap().unwrap()).unwrap().to_rgba8();
+        assert_eq!(rgb(filler.get_pixel(0, 0)), [17, 13, 0]);
+
+        assert_eq!(reader.tile(10, 600, 600).unwrap(), None);
+        assert_eq!(reader.tile(3, 100, 0).unwrap(), None, "Outside the zoom level");
+    }
+
+    #[test]
+    fn test_corrupt_overflow_chain() {
+        let noise = MbtilesReader::open(Path::new(FIXTURE)).unwrap().tile(10, 0, 0).unwrap().unwrap();
+        let mut file = std::fs::read(FIXTURE).unwrap();
+        let page_size = u16::from_be_bytes([file[16], file[17]]) as usize;
+        // Primeira página de overflow do tile grande: a que continua o início do tile, e que
+        // passa a apontar para si mesma
+        let (page, _) = (1..file.len() / page_size)
+            .filter_map(|page| {
+                let content = &file[page * page_size + 4..page * page_size + 68];
+                noise.windows(64).position(|window| window == content).map(|position| (page, position))
+            })
+            .min_by_key(|(_, position)| *position)
+            .unwrap();
+        file[page * page_size..page * page_size + 4].copy_from_slice(&(page as u32 + 1).to_be_bytes());
+        let corrupt = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(corrupt.path(), &file).unwrap();
+
+        let mut reader = MbtilesReader::open(corrupt.path()).unwrap();
+        let error = reader.tile(10, 0, 0).unwrap_err();
+        assert!(error.to_string().contains("ciclo"), "{}", error);
+        assert!(reader.tile(10, 512, 511).unwrap().is_some(), "Other tiles are still readable");
+    }
+
+    #[test]
+    fn test_deduplicated_mbtiles() {
+        let mut reader = MbtilesReader::open(Path::new(DEDUPLICATED_FIXTURE)).unwrap();
+        assert_eq!(reader.zoom_range(), None);
+        let tile = |reader: &mut MbtilesReader, x, y| image::load_from_memory(&reader.tile(10, x, y).unwrap().unwrap()).unwrap().to_rgba8();
+        assert_eq!(rgb(tile(&mut reader, 512, 512).get_pixel(0, 0)), SOUTH_EAST);
+        // Outra posição com a mesma imagem (mesmo tile_id)
+        assert_eq!(rgb(tile(&mut reader, 100, 1023 - 100).get_pixel(0, 0)), SOUTH_EAST);
+
+        let error = MbtilesReader::open(Path::new("tests/fixtures/dji_mini3.srt")).err().unwrap();
+        assert!(error.to_string().contains("SQLite"), "{}", error);
+    }
+
+    #[test]
+    fn test_web_mercator_projection() {
+        let gpx = diagonal_track();
+        let projection = MapProjection::web_mercator(&gpx, 300, 300, 20.0).unwrap();
+        let (x, y) = projection.project(-0.005, 0.005);
+        assert!((x - 20.0).abs() < 1e-9 && (y - 20.0).abs() < 1e-9);
+        let (end_x, end_y) = projection.project(0.005, -0.005);
+        assert!((end_x - 280.0).abs() < 1e-6 && (end_y - 280.0).abs() < 1e-3, "{} {}", end_x, end_y);
+        // O centro do mundo Web Mercator é lat 0, lon 0
+        let (center_x, center_y) = projection.plane_at(150.0, 150.0);
+        assert!((center_x - 0.5).abs() < 1e-12 && (center_y - 0.5).abs() < 1e-9);
+        // Mais perto dos polos a latitude estica: 1° vale mais que 1° no equador
+        let one_degree_north = projection.project(0.0, 0.0).1 - projection.project(0.0, 1.0).1;
+        let sixty_degrees = projection.project(0.0, 60.0).1 - projection.project(0.0, 61.0).1;
+        assert!(sixty_degrees > one_degree_north * 1.9, "{} vs {}", sixty_degrees, one_degree_north);
+
+        // 260 px para ~1.1 km de largura pedem o zoom 16, limitado aos zooms disponíveis
+        assert_eq!(zoom_for(&projection, (0, 22)), 16);
+        assert_eq!(zoom_for(&projection, (9, 10)), 10);
+    }
+
+    #[test]
+    fn test_track_map_over_mbtiles() {
+        let map = track_map(&[("basemap", FIXTURE)]);
+        // Norte para cima e leste à direita
+        assert_eq!(rgb(map.get_pixel(225, 75)), NORTH_EAST);
+        assert_eq!(rgb(map.get_pixel(250, 200)), SOUTH_EAST);
+        assert_eq!(rgb(map.get_pixel(100, 50)), NORTH_WEST);
+        // Atribuição do metadata, sem HTML, numa faixa escura em baixo à direita
+        assert!(rgb(map.get_pixel(295, 295)).iter().all(|&c| c < SOUTH_EAST[0]));
+
+        // O mesmo mapa pelos tiles do esquema com deduplicação, ampliados do zoom 10
+        let deduplicated = track_map(&[("basemap", DEDUPLICATED_FIXTURE), ("attribution", "")]);
+        assert_eq!(rgb(deduplicated.get_pixel(225, 75)), NORTH_EAST);
+        assert_eq!(rgb(deduplicated.get_pixel(295, 295)), SOUTH_EAST, "Attribution hidden");
+    }
+
+    #[test]
+    fn test_xyz_directory_with_parent_tiles() {
+        let dir = tempfile::tempdir().unwrap();
+        // Só o zoom 8: os tiles do zoom do mapa vêm ampliados deste
+        for (x, y, color) in [(127, 127, NORTH_WEST), (128, 127, NORTH_EAST), (128, 128, SOUTH_EAST)] {
+            std::fs::create_dir_all(dir.path().join("8").join(x.to_string())).unwrap();
+            RgbaImage::from_pixel(256, 256, Rgba([color[0], color[1], color[2], 255]))
+                .save(dir.path().join("8").join(x.to_string()).join(format!("{}.png", y))).unwrap();
+        }
+        let mut source = TileSource::open(dir.path()).unwrap();
+        assert_eq!(source.zoom_range(), (8, 8));
+        assert_eq!(source.attribution(), None);
+        let projection = MapProjection::web_mercator(&diagonal_track(), 300, 300, 20.0).unwrap();
+        let tiles = render_basemap(&mut source, &projection, 300, 300).unwrap();
+        assert_eq!(rgb(tiles.get_pixel(290, 290)), SOUTH_EAST);
+        assert_eq!(tiles.get_pixel(10, 290).0[3], 0, "Missing south-west tile stays transparent");
+
+        let map = track_map(&[("basemap", dir.path().to_str().unwrap()), ("attribution", "© Tiles locais")]);
+        assert_eq!(rgb(map.get_pixel(290, 10)), NORTH_EAST);
+        assert_ne!(rgb(map.get_pixel(295, 295)), SOUTH_EAST, "Custom attribution drawn");
+
+        let missing = Layout::parse("[[widgets]]\nwidget = \"track_map\"\noptions = { basemap = \"/nao/existe.mbtiles\" }").unwrap();
+        let error = Compositor::new(&missing, &WidgetRegistry::builtin(), &diagonal_track(), (1920, 1080), "pt", UnitSystem::Metric, &AthleteProfile::default()).err().unwrap();
+        assert!(error.to_string().contains("/nao/existe.mbtiles"), "{}", error);
+    }
+
+    #[test]
+    fn test_client_basemap_is_confined() {
+        let with_basemap = |basemap: &str| Layout::parse(&format!(
+            "[[widgets]]\nwidget = \"track_map\"\noptions = {{ basemap = \"{}\" }}", basemap,
+        )).unwrap();
+        let resources = LayoutResources { basemaps_dir: Some("tests/fixtures".into()), ..Default::default() };
+
+        let mut layout = with_basemap("basemap.mbtiles");
+        layout.confine(&resources).unwrap();
+        let resolved = std::fs::canonicalize(FIXTURE).unwrap();
+        assert_eq!(layout.widgets[0].options["basemap"], serde_json::json!(resolved.to_str().unwrap()));
+        Compositor::new(&layout, &WidgetRegistry::builtin(), &diagonal_track(), (1920, 1080), "pt", UnitSystem::Metric, &AthleteProfile::default()).unwrap();
+
+        for basemap in [std::fs::canonicalize(FIXTURE).unwrap().to_str().unwrap(), "../fixtures/basemap.mbtiles", "./basemap.mbtiles", "nao_existe.mbtiles"] {
+            let error = with_basemap(basemap).confine(&resources).unwrap_err();
+            assert!(error.contains("Mapa base"), "{}", error);
+        }
+        assert!(with_basemap("basemap.mbtiles").confine(&LayoutResources::default()).is_err(), "No directory configured");
+    }
+}
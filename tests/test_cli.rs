@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use chrono::{TimeZone, Utc};
+    use clap::Parser;
+    use extrator_gpx::cli::{self, Cli, Command, Position, SyncPoint};
+    use extrator_gpx::UnitSystem;
+
+    fn parse(args: &[&str]) -> Command {
+        let cli = Cli::try_parse_from(std::iter::once("extrator_gpx").chain(args.iter().copied())).unwrap();
+        cli.command.expect("Expected a subcommand")
+    }
+
+    #[test]
+    fn test_render_arguments() {
+        let command = parse(&[
+            "render", "--track", "ride.fit", "--video", "clip.mp4", "--sync", "auto",
+            "--speedo", "bottom-left", "--map", "top-right", "--stats", "top-left", "--out", "out.mp4",
+        ]);
+        let Command::Render(args) = command else { panic!("Expected render") };
+        assert_eq!(args.input.track, Some(PathBuf::from("ride.fit")));
+        assert_eq!(args.input.video, PathBuf::from("clip.mp4"));
+        assert_eq!(args.sync, SyncPoint::Auto);
+        assert_eq!(args.speedo, Some(Position::BottomLeft));
+        assert_eq!(args.map.map(|p| p.as_str()), Some("top-right"));
+        assert_eq!(args.stats.map(|p| p.as_str()), Some("top-left"));
+        assert_eq!(args.out, PathBuf::from("out.mp4"));
+        assert_eq!(args.input.interpolation, 1);
+        assert_eq!(args.lang, "pt");
+    }
+
+    #[test]
+    fn test_sync_point_parsing() {
+        assert_eq!("AUTO".parse::<SyncPoint>(), Ok(SyncPoint::Auto));
+        assert_eq!(
+            "2024-05-01T09:30:00-03:00".parse::<SyncPoint>(),
+            Ok(SyncPoint::At(Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap()))
+        );
+        assert!("10:30".parse::<SyncPoint>().is_err());
+    }
+
+    #[test]
+    fn test_usage_errors_are_rejected_by_the_parser() {
+        let parse_err = |args: &[&str]| Cli::try_parse_from(std::iter::once("extrator_gpx").chain(args.iter().copied())).unwrap_err();
+        // Sem --out, posição desconhecida e segunda âncora incompleta
+        assert_eq!(parse_err(&["render", "--video", "clip.mp4"]).exit_code(), cli::EXIT_USAGE as i32);
+        assert_eq!(parse_err(&["render", "--video", "a.mp4", "--out", "o.mp4", "--speedo", "middle"]).exit_code(), cli::EXIT_USAGE as i32);
+        assert_eq!(
+            parse_err(&["render", "--video", "a.mp4", "--out", "o.mp4", "--second-video-time", "12:00"]).exit_code(),
+            cli::EXIT_USAGE as i32
+        );
+        // Um intervalo de 0 s pediria infinitos pontos interpolados
+        assert_eq!(parse_err(&["render", "--video", "a.mp4", "--out", "o.mp4", "--interpolation", "0"]).exit_code(), cli::EXIT_USAGE as i32);
+        assert_eq!(parse_err(&["suggest", "--video", "a.mp4", "--interpolation", "-3"]).exit_code(), cli::EXIT_USAGE as i32);
+        assert!(Cli::try_parse_from(["extrator_gpx"]).unwrap().command.is_none(), "Without a subcommand the server starts");
+    }
+
+    #[test]
+    fn test_units_option() {
+        let Command::Suggest(args) = parse(&["suggest", "--video", "clip.mp4"]) else { panic!("Expected suggest") };
+        assert_eq!(args.input.units, UnitSystem::Metric);
+        let Command::Render(args) = parse(&["render", "--video", "clip.mp4", "--units", "pace-mi", "--out", "o.mp4"]) else { panic!("Expected render") };
+        assert_eq!(args.input.units, UnitSystem::PaceMi);
+
+        let error = Cli::try_parse_from(["extrator_gpx", "suggest", "--video", "clip.mp4", "--units", "furlongs"]).unwrap_err();
+        assert_eq!(error.exit_code(), cli::EXIT_USAGE as i32);
+    }
+
+    #[test]
+    fn test_heart_rate_zone_options() {
+        let Command::Render(args) = parse(&["render", "--video", "clip.mp4", "--max-hr", "185", "--resting-hr", "50", "--out", "o.mp4"]) else { panic!("Expected render") };
+        assert_eq!((args.hr_zones, args.max_hr, args.resting_hr), (None, Some(185.0), Some(50.0)));
+
+        // Método sem o valor de que precisa: erro de uso antes de procurar os arquivos
+        let command = parse(&["render", "--video", "missing.mp4", "--hr-zones", "lthr", "--max-hr", "185", "--out", "o.mp4"]);
+        assert_eq!(cli::run(command), cli::EXIT_USAGE);
+    }
+
+    #[test]
+    fn test_power_options() {
+        let Command::Suggest(args) = parse(&["suggest", "--video", "clip.mp4", "--ftp", "250", "--weight", "68.5"]) else { panic!("Expected suggest") };
+        assert_eq!((args.input.ftp, args.input.weight), (Some(250.0), Some(68.5)));
+
+        // FTP fora do intervalo: erro de uso antes de procurar os arquivos
+        let command = parse(&["render", "--video", "missing.mp4", "--ftp", "5000", "--out", "o.mp4"]);
+        assert_eq!(cli::run(command), cli::EXIT_USAGE);
+    }
+
+    #[test]
+    fn test_basemap_options() {
+        let Command::Render(args) = parse(&["render", "--video", "clip.mp4", "--map", "top-right", "--basemap", "tiles.mbtiles", "--attribution", "© OSM", "--out", "o.mp4"]) else { panic!("Expected render") };
+        assert_eq!(args.basemap, Some(PathBuf::from("tiles.mbtiles")));
+        assert_eq!(args.attribution.as_deref(), Some("© OSM"));
+        assert!(Cli::try_parse_from(["extrator_gpx", "render", "--video", "clip.mp4", "--attribution", "x", "--out", "o.mp4"]).is_err(), "--attribution requires --basemap");
+
+        // Sem mapa do percurso o mapa base não tem onde ser desenhado
+        let command = parse(&["render", "--video", "missing.mp4", "--speedo", "top-left", "--basemap", "tiles", "--out", "o.mp4"]);
+        assert_eq!(cli::run(command), cli::EXIT_USAGE);
+    }
+
+    #[test]
+    fn test_missing_files_exit_with_input_error() {
+        let missing = std::env::temp_dir().join("cli_missing_video.mp4");
+        let missing = missing.to_str().unwrap();
+        assert_eq!(cli::run(parse(&["suggest", "--video", missing])), cli::EXIT_INPUT);
+        assert_eq!(cli::run(parse(&["render", "--video", missing, "--out", "out.mp4"])), cli::EXIT_INPUT);
+    }
+
+    #[test]
+    fn test_unreadable_files_exit_with_input_error_in_both_sync_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("clip.mp4");
+        let track = dir.path().join("track.gpx");
+        std::fs::write(&video, b"not a video").unwrap();
+        std::fs::write(&track, b"<gpx").unwrap();
+        let out = dir.path().join("out.mp4");
+        let (video, track, out) = (video.to_str().unwrap(), track.to_str().unwrap(), out.to_str().unwrap());
+        for sync in ["auto", "2024-05-01T10:00:00Z"] {
+            let command = parse(&["render", "--video", video, "--track", track, "--sync", sync, "--out", out]);
+            assert_eq!(cli::run(command), cli::EXIT_INPUT, "--sync {}", sync);
+        }
+    }
+
+    #[test]
+    fn test_layout_options() {
+        let parse_err = |args: &[&str]| Cli::try_parse_from(std::iter::once("extrator_gpx").chain(args.iter().copied())).unwrap_err();
+        assert_eq!(
+            parse_err(&["render", "--video", "a.mp4", "--out", "o.mp4", "--layout", "l.toml", "--speedo", "top-left"]).exit_code(),
+            cli::EXIT_USAGE as i32
+        );
+        let command = parse(&["render", "--video", "clip.mp4", "--out", "out.mp4", "--preset", "vertical"]);
+        assert_eq!(cli::run(command), cli::EXIT_USAGE, "Unknown presets are usage errors");
+        let missing = std::env::temp_dir().join("cli_missing_layout.toml");
+        let command = parse(&["render", "--video", "clip.mp4", "--out", "out.mp4", "--layout", missing.to_str().unwrap()]);
+        assert_eq!(cli::run(command), cli::EXIT_INPUT);
+    }
+
+    #[test]
+    fn test_invalid_timezone_is_a_usage_error() {
+        let command = parse(&["render", "--video", "clip.mp4", "--out", "out.mp4", "--camera-tz", "Mars/Olympus"]);
+        assert_eq!(cli::run(command), cli::EXIT_USAGE);
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use extrator_gpx::clock_sync::{ClockSync, SyncAnchor};
+
+    fn anchor(video_secs: f64, track_secs: i64) -> SyncAnchor {
+        SyncAnchor {
+            video_secs,
+            track_time: Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 0).unwrap() + Duration::seconds(track_secs),
+        }
+    }
+
+    #[test]
+    fn test_single_anchor_is_constant_offset() {
+        let clock = ClockSync::new(anchor(0.0, 0));
+        assert_eq!(clock.video_secs(anchor(0.0, 90).track_time), 90.0);
+        assert_eq!(clock.video_secs(anchor(0.0, -5).track_time), -5.0);
+        assert_eq!(clock.drift_secs_per_hour(), 0.0);
+    }
+
+    #[test]
+    fn test_two_anchors_warp_time() {
+        // Após 1 h de trilha o vídeo só avançou 3590 s: o relógio da câmara perde 10 s por hora
+        let clock = ClockSync::from_anchors(anchor(0.0, 0), anchor(3590.0, 3600)).expect("Anchors are consistent");
+
+        assert!((clock.video_secs(anchor(0.0, 3600).track_time) - 3590.0).abs() < 1e-9);
+        assert!((clock.video_secs(anchor(0.0, 1800).track_time) - 1795.0).abs() < 1e-9, "Drift is spread linearly");
+        assert!((clock.drift_secs_per_hour() - 10.03).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fast_camera_clock() {
+        let clock = ClockSync::from_anchors(anchor(0.0, 0), anchor(1805.0, 1800)).unwrap();
+        assert!(clock.drift_secs_per_hour() < 0.0, "A fast camera clock gains time");
+    }
+
+    #[test]
+    fn test_inconsistent_anchors_are_rejected() {
+        assert!(ClockSync::from_anchors(anchor(0.0, 0), anchor(0.0, 600)).is_none(), "Same video instant");
+        assert!(ClockSync::from_anchors(anchor(0.0, 0), anchor(600.0, -600)).is_none(), "Wrong order");
+        assert!(ClockSync::from_anchors(anchor(600.0, 600), anchor(0.0, 0)).is_none(), "Both anchors reversed");
+        assert!(ClockSync::from_anchors(anchor(0.0, 0), anchor(600.0, 600)).is_some());
+        assert!(ClockSync::from_anchors(anchor(0.0, 0), anchor(600.0, 700)).is_none(), "Drift far above 1 %");
+    }
+
+    #[test]
+    fn test_parse_anchor_fields() {
+        let parsed = SyncAnchor::parse("12:34.5", "2024-01-15T07:30:00Z").unwrap();
+        assert_eq!(parsed, SyncAnchor { video_secs: 754.5, ..anchor(0.0, 0) });
+        assert_eq!(SyncAnchor::parse("1:02:03", "2024-01-15T07:30:00Z").unwrap().video_secs, 3723.0);
+        assert_eq!(SyncAnchor::parse("90,5", "2024-01-15T07:30:00Z").unwrap().video_secs, 90.5);
+
+        assert!(SyncAnchor::parse("abc", "2024-01-15T07:30:00Z").is_err());
+        assert!(SyncAnchor::parse("-10", "2024-01-15T07:30:00Z").is_err());
+        assert!(SyncAnchor::parse("10", "ontem").is_err());
+    }
+}
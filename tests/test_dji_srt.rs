@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use extrator_gpx::timezone::DEFAULT_TIMEZONE;
+
+    // Legendas sintéticas nos três formatos conhecidos: campos entre colchetes (Mini 3),
+    // GPS(...)/BAROMETER (Phantom 4, com CRLF) e lista separada por vírgulas (Mavic Air 2)
+    const MINI3_SRT: &str = "tests/fixtures/dji_mini3.srt";
+    const PHANTOM4_SRT: &str = "tests/fixtures/dji_phantom4.srt";
+    const MAVIC_AIR2_SRT: &str = "tests/fixtures/dji_mavic_air2.srt";
+
+    #[test]
+    fn test_detect_srt_file_type() {
+        assert_eq!(extrator_gpx::track::detect_file_type(&PathBuf::from("DJI_0042.SRT")), "SRT");
+    }
+
+    #[test]
+    fn test_bracketed_srt_conversion() {
+        let result = extrator_gpx::dji_srt::read_and_process_dji_srt(&PathBuf::from(MINI3_SRT), DEFAULT_TIMEZONE)
+            .expect("SRT conversion should succeed");
+
+        // 7 legendas a cada 0,5 s são reduzidas a um ponto por segundo
+        let points = &result.gpx.tracks[0].segments[0].points;
+        assert_eq!(points.len(), 4);
+        assert_eq!(result.telemetry.len(), 4, "Telemetry should be parallel to the points");
+        assert!(result.telemetry.iter().all(|t| t.camera.is_some()), "Every cue should carry the camera settings");
+
+        assert!((points[0].point().y() - -10.123456).abs() < 1e-9);
+        assert!((points[0].point().x() - -48.654321).abs() < 1e-9);
+        assert_eq!(points[0].elevation, Some(280.0), "Absolute altitude is preferred");
+        assert_eq!(points[1].elevation, Some(282.0));
+
+        // Hora local do drone (São Paulo, -03:00) convertida para UTC
+        assert_eq!(
+            points[1].time.and_then(|t| t.format().ok()).as_deref(),
+            Some("2024-01-15T07:30:12.000000000Z")
+        );
+        assert_eq!(
+            result.video_start_time.map(|t| t.to_rfc3339()),
+            Some("2024-01-15T07:30:11+00:00".to_string())
+        );
+
+        assert_eq!(result.telemetry[0].altitude_source, Some(extrator_gpx::telemetry::AltitudeSource::Gps));
+        assert_eq!(result.extra_data.device_name, Some("DJI".to_string()));
+        assert_eq!(result.extra_data.total_time_seconds, 3.0);
+    }
+
+    #[test]
+    fn test_srt_camera_metadata() {
+        let result = extrator_gpx::dji_srt::read_and_process_dji_srt(&PathBuf::from(MINI3_SRT), DEFAULT_TIMEZONE).unwrap();
+
+        let camera = result.telemetry[0].camera.expect("Camera settings should be in the telemetry");
+        assert_eq!(camera.iso, Some(100.0));
+        assert_eq!(camera.exposure_secs, Some(1.0 / 500.0));
+        assert_eq!(camera.shutter().as_deref(), Some("1/500"));
+        assert_eq!(camera.f_number, Some(1.7));
+        assert_eq!(camera.ev, Some(0.0));
+        assert_eq!(camera.focal_length_mm, Some(24.0));
+        assert_eq!(camera.describe(), "ISO 100  1/500  f/1.7  EV 0");
+    }
+
+    #[test]
+    fn test_legacy_srt_conversion() {
+        let result = extrator_gpx::dji_srt::read_and_process_dji_srt(&PathBuf::from(PHANTOM4_SRT), DEFAULT_TIMEZONE)
+            .expect("Legacy SRT conversion should succeed");
+
+        let points = &result.gpx.tracks[0].segments[0].points;
+        assert_eq!(points.len(), 3);
+        assert!((points[2].point().x() - -48.6541).abs() < 1e-9, "Longitude comes first in GPS(...)");
+        assert!((points[2].point().y() - -10.1236).abs() < 1e-9);
+        assert_eq!(points[0].elevation, Some(30.5), "Barometer height when there is no absolute altitude");
+        assert_eq!(
+            result.telemetry[0].altitude_source,
+            Some(extrator_gpx::telemetry::AltitudeSource::Barometric)
+        );
+
+        let camera = result.telemetry[0].camera.expect("Camera settings should be in the telemetry");
+        assert_eq!(camera.iso, Some(200.0));
+        assert_eq!(camera.shutter().as_deref(), Some("1/250"));
+        assert_eq!(camera.f_number, Some(2.8));
+        assert_eq!(camera.ev, Some(-0.3));
+    }
+
+    #[test]
+    fn test_comma_separated_srt_conversion() {
+        let result = extrator_gpx::dji_srt::read_and_process_dji_srt(&PathBuf::from(MAVIC_AIR2_SRT), DEFAULT_TIMEZONE)
+            .expect("Mavic Air 2 SRT conversion should succeed");
+
+        let points = &result.gpx.tracks[0].segments[0].points;
+        assert_eq!(points.len(), 2);
+        assert!((points[0].point().y() - -10.1234).abs() < 1e-9);
+        assert_eq!(points[1].elevation, Some(7.8));
+        assert_eq!(result.telemetry[1].speed_mps, Some(5.5), "Horizontal speed from H.S");
+        assert_eq!(result.extra_data.max_speed, 5.5);
+        let camera = result.telemetry[0].camera.expect("Camera settings should be in the telemetry");
+        assert_eq!(camera.shutter().as_deref(), Some("1/1000"));
+        assert_eq!(camera.f_number, Some(2.8));
+    }
+
+    #[test]
+    fn test_read_track_file_srt() {
+        let track_file_data = extrator_gpx::read_track_file(&PathBuf::from(MINI3_SRT), DEFAULT_TIMEZONE)
+            .expect("Should successfully read SRT file");
+
+        assert!(track_file_data.extra_data.is_some());
+        assert!(track_file_data.video_start_time.is_some(), "SRT cues give the exact video start");
+
+        let (interpolated, telemetry) = extrator_gpx::utils::interpolate_gpx_points(
+            track_file_data.gpx,
+            &track_file_data.telemetry,
+            1,
+        );
+        assert_eq!(interpolated.tracks[0].segments[0].points.len(), 4);
+        assert_eq!(telemetry.len(), 4);
+        assert!(telemetry.iter().all(|t| t.camera.is_some()), "Camera settings survive the interpolation");
+    }
+
+    #[test]
+    fn test_invalid_srt_handling() {
+        let result = extrator_gpx::dji_srt::parse_dji_srt("isto não é uma legenda", DEFAULT_TIMEZONE);
+        assert!(result.is_err(), "Should fail when there are no subtitle cues");
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use geo_types::Point;
+    use gpx::{Gpx, Track, TrackSegment, Waypoint};
+    use image::Rgba;
+    use extrator_gpx::{AthleteProfile, UnitSystem};
+    use extrator_gpx::elevation_profile::{gradient_color, ElevationProfile};
+    use extrator_gpx::heart_rate::TimeInZones;
+    use extrator_gpx::layout::Layout;
+    use extrator_gpx::widget::{Compositor, FrameContext, WidgetRegistry};
+
+    const CANVAS: (u32, u32) = (1280, 720);
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    // Subida de 100 m ao longo de ~1.1 km para leste no equador, um ponto a cada 10 s
+    fn climb_track(elevations: bool) -> Gpx {
+        let mut segment = TrackSegment::new();
+        for i in 0..=10 {
+            let mut point = Waypoint::new(Point::new(i as f64 * 0.001, 0.0));
+            point.elevation = elevations.then_some(100.0 + i as f64 * 10.0);
+            let time = start() + Duration::seconds(i * 10);
+            point.time = Some(gpx::Time::from(time::OffsetDateTime::from_unix_timestamp(time.timestamp()).unwrap()));
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        Gpx { tracks: vec![track], ..Default::default() }
+    }
+
+    fn frame_at(gpx: &Gpx, index: usize) -> FrameContext {
+        FrameContext {
+            point: gpx.tracks[0].segments[0].points[index].clone(),
+            point_time: start() + Duration::seconds(index as i64 * 10),
+            speed_kmh: 20.0,
+            bearing: 90.0,
+            g_force: 0.0,
+            elevation: 100.0 + index as f64 * 10.0,
+            distance_km: 0.0,
+            elevation_gain_m: 0.0,
+            heart_rate: None,
+            cadence: None,
+            sensor_speed_kmh: None,
+            calories: None,
+            camera: None,
+            time_in_zones: TimeInZones::default(),
+            power: None,
+            power_3s: None,
+            power_10s: None,
+            power_summary: None,
+            utc_offset_secs: 0,
+        }
+    }
+
+    fn compositor<'a>(layout: &'a Layout, gpx: &Gpx, athlete: &AthleteProfile) -> Compositor<'a> {
+        Compositor::new(layout, &WidgetRegistry::builtin(), gpx, CANVAS, "pt", UnitSystem::Metric, athlete).unwrap()
+    }
+
+    #[test]
+    fn test_profile_from_track() {
+        let profile = ElevationProfile::new(&climb_track(true)).unwrap();
+        let step_km = 0.001_f64.to_radians() * 6371.0;
+        assert!((profile.total_km() - 10.0 * step_km).abs() < 1e-6);
+        assert_eq!(profile.min_elevation(), 100.0);
+        assert_eq!(profile.max_elevation(), 200.0);
+        assert!((profile.elevation_at(2.5 * step_km) - 125.0).abs() < 1e-6);
+        assert_eq!(profile.elevation_at(-1.0), 100.0, "Clamped to the first point");
+        assert_eq!(profile.elevation_at(5.0), 200.0, "Clamped to the last point");
+
+        // 10 m de subida a cada ~111 m: cerca de 9%
+        assert!((profile.gradient_at(0.5) - 1000.0 / (step_km * 1000.0)).abs() < 0.01);
+        assert!(ElevationProfile::new(&climb_track(false)).is_none(), "No elevation means no profile");
+    }
+
+    #[test]
+    fn test_distance_at_time() {
+        let profile = ElevationProfile::new(&climb_track(true)).unwrap();
+        let step_km = profile.total_km() / 10.0;
+        assert_eq!(profile.distance_at(start() - Duration::seconds(30)), Some(0.0));
+        assert!((profile.distance_at(start() + Duration::seconds(35)).unwrap() - 3.5 * step_km).abs() < 1e-9);
+        assert_eq!(profile.distance_at(start() + Duration::hours(1)), Some(profile.total_km()));
+    }
+
+    #[test]
+    fn test_gradient_colors() {
+        assert_eq!(gradient_color(-6.0), Rgba([33, 150, 243, 255]), "Descent is blue");
+        assert_eq!(gradient_color(0.5), Rgba([76, 175, 80, 255]), "Flat is green");
+        assert_eq!(gradient_color(6.0), Rgba([255, 152, 0, 255]));
+        assert_eq!(gradient_color(20.0), Rgba([136, 14, 79, 255]));
+    }
+
+    #[test]
+    fn test_marker_advances_with_frames() {
+        let gpx = climb_track(true);
+        let layout = Layout::parse("[[widgets]]\nwidget = \"elevation_profile\"\nanchor = \"top-left\"").unwrap();
+        let athlete = AthleteProfile::default();
+        let compositor = compositor(&layout, &gpx, &athlete);
+
+        // A área já percorrida é preenchida com a cor de destaque (laranja)
+        let orange_columns = |index: usize| {
+            let frame = compositor.compose(Some(&frame_at(&gpx, index))).unwrap();
+            (0..frame.width()).filter(|&x| (0..frame.height()).any(|y| frame.get_pixel(x, y).0[..3] == [255, 152, 0])).count()
+        };
+        let (early, late) = (orange_columns(2), orange_columns(8));
+        assert!(early > 0);
+        assert!(late > early * 2, "{} columns at 20%, {} at 80%", early, late);
+        assert!(compositor.compose(None).unwrap().pixels().any(|p| p.0[3] > 0), "The full profile is drawn without a frame");
+    }
+
+    #[test]
+    fn test_gradient_option_and_missing_elevation() {
+        let gpx = climb_track(true);
+        let athlete = AthleteProfile::default();
+        let layout = Layout::parse("[[widgets]]\nwidget = \"elevation_profile\"\noptions = { color_by_gradient = true }").unwrap();
+        let frame = compositor(&layout, &gpx, &athlete).compose(Some(&frame_at(&gpx, 9))).unwrap();
+        assert!(frame.pixels().any(|p| *p == gradient_color(9.0)), "Climb colored as a ~9% gradient");
+
+        let flat = climb_track(false);
+        let empty = compositor(&layout, &flat, &athlete).compose(Some(&frame_at(&flat, 5))).unwrap();
+        assert!(empty.pixels().all(|p| p.0[3] == 0), "Nothing is drawn without elevation");
+
+        let invalid = Layout::parse("[[widgets]]\nwidget = \"elevation_profile\"\noptions = { color_by_gradient = \"sim\" }").unwrap();
+        let error = Compositor::new(&invalid, &WidgetRegistry::builtin(), &gpx, CANVAS, "pt", UnitSystem::Metric, &athlete).err().unwrap();
+        assert!(error.to_string().contains("color_by_gradient"), "{}", error);
+    }
+}
@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    // Fixtures binárias: atividade sintética Wahoo (com potência e cadência)
+    // e uma pedalada real gravada num Garmin Fenix 5 (sem cadência/potência)
+    const WAHOO_FIT: &str = "tests/fixtures/wahoo_bike_power.fit";
+    const FENIX_FIT: &str = "tests/fixtures/garmin_fenix5_bike.fit";
+
+    #[test]
+    fn test_detect_fit_file_type() {
+        assert_eq!(extrator_gpx::track::detect_file_type(&PathBuf::from("ride.fit")), "FIT");
+        assert_eq!(extrator_gpx::track::detect_file_type(&PathBuf::from("RIDE.FIT")), "FIT");
+    }
+
+    #[test]
+    fn test_fit_to_gpx_conversion() {
+        let result = extrator_gpx::fit_adapter::read_and_process_fit(&PathBuf::from(WAHOO_FIT))
+            .expect("FIT conversion should succeed");
+
+        let gpx = result.gpx;
+        assert_eq!(result.telemetry.len(), 10, "Telemetry should be parallel to the points");
+        assert_eq!(gpx.tracks.len(), 1, "Should have one track");
+        assert_eq!(gpx.tracks[0].segments[0].points.len(), 10, "Should have 10 points");
+        assert!(gpx.creator.unwrap().contains("FIT Adapter"), "Creator should mention FIT Adapter");
+        assert_eq!(gpx.tracks[0].type_, Some("Cycling".to_string()));
+
+        let first_point = &gpx.tracks[0].segments[0].points[0];
+        assert!((first_point.point().y() - -10.123456).abs() < 1e-6, "Latitude from semicircles");
+        assert!((first_point.point().x() - -48.654321).abs() < 1e-6, "Longitude from semicircles");
+        assert_eq!(first_point.elevation, Some(230.0));
+        assert_eq!(
+            first_point.time.and_then(|t| t.format().ok()).as_deref(),
+            Some("2024-01-15T07:30:00.000000000Z")
+        );
+
+        assert!(first_point.comment.is_none(), "Telemetry must not leak into comments");
+
+        let sample = &result.telemetry[0];
+        assert_eq!(sample.heart_rate, Some(140.0), "Should contain heart rate data");
+        assert_eq!(sample.cadence, Some(85.0), "Should contain cadence data");
+        assert_eq!(sample.speed_mps, Some(8.0), "Should contain speed data");
+        assert_eq!(sample.power, Some(200.0), "Should contain power data");
+        assert_eq!(sample.temperature, Some(24.0), "Should contain temperature data");
+        assert_eq!(sample.altitude_source, Some(extrator_gpx::telemetry::AltitudeSource::Gps));
+    }
+
+    #[test]
+    fn test_fit_extra_data_extraction() {
+        let extra_data = extrator_gpx::fit_adapter::read_and_process_fit(&PathBuf::from(WAHOO_FIT))
+            .expect("FIT conversion should succeed")
+            .extra_data;
+
+        assert_eq!(extra_data.sport, Some("Biking".to_string()));
+        assert_eq!(extra_data.device_name, Some("ELEMNT BOLT".to_string()));
+        assert_eq!(extra_data.total_time_seconds, 9.0);
+        assert_eq!(extra_data.total_distance_meters, 83.25);
+        assert_eq!(extra_data.total_calories, 12.0);
+        assert_eq!(extra_data.max_speed, 10.25);
+
+        assert_eq!(extra_data.power_data.len(), 10);
+        assert_eq!(extra_data.temperature_data.len(), 10);
+        assert_eq!(extra_data.average_heart_rate(), Some(144.5));
+        assert_eq!(extra_data.max_cadence(), Some(94.0));
+    }
+
+    #[test]
+    fn test_garmin_device_fit() {
+        let result = extrator_gpx::fit_adapter::read_and_process_fit(&PathBuf::from(FENIX_FIT))
+            .expect("Garmin FIT conversion should succeed");
+
+        assert_eq!(result.extra_data.device_name, Some("garmin fenix5".to_string()));
+        assert_eq!(result.extra_data.sport, Some("Biking".to_string()));
+        assert!(result.extra_data.cadence_data.is_empty(), "Fenix ride has no cadence sensor");
+        assert!(result.extra_data.power_data.is_empty(), "Fenix ride has no power meter");
+        assert!(!result.extra_data.heart_rate_data.is_empty(), "Fenix ride has heart rate");
+        assert!(!result.gpx.tracks.is_empty(), "Should have GPS track");
+        assert_eq!(
+            result.telemetry[0].altitude_source,
+            Some(extrator_gpx::telemetry::AltitudeSource::Barometric),
+            "Fenix 5 records altitude with its barometer"
+        );
+    }
+
+    #[test]
+    fn test_read_track_file_fit() {
+        let result = extrator_gpx::read_track_file(&PathBuf::from(WAHOO_FIT), extrator_gpx::timezone::DEFAULT_TIMEZONE);
+
+        assert!(result.is_ok(), "Should successfully read FIT file");
+        let track_file_data = result.unwrap();
+        assert!(track_file_data.extra_data.is_some(), "FIT files should carry extra data");
+
+        // Compatível com a interpolação existente
+        let (interpolated, telemetry) = extrator_gpx::utils::interpolate_gpx_points(
+            track_file_data.gpx,
+            &track_file_data.telemetry,
+            1,
+        );
+        assert_eq!(interpolated.tracks[0].segments[0].points.len(), 10);
+        assert_eq!(telemetry[9].power, Some(290.0));
+    }
+
+    #[test]
+    fn test_fit_telemetry_interpolation() {
+        let track_file_data = extrator_gpx::read_track_file(&PathBuf::from(WAHOO_FIT), extrator_gpx::timezone::DEFAULT_TIMEZONE).unwrap();
+
+        // Remove um ponto em cada dois e interpola de volta para verificar os canais
+        let mut gpx = track_file_data.gpx;
+        let points = std::mem::take(&mut gpx.tracks[0].segments[0].points);
+        let (kept_points, kept_samples): (Vec<_>, Vec<_>) = points.into_iter()
+            .zip(track_file_data.telemetry)
+            .step_by(2)
+            .unzip();
+        gpx.tracks[0].segments[0].points = kept_points;
+
+        let (interpolated, telemetry) = extrator_gpx::utils::interpolate_gpx_points(gpx, &kept_samples, 1);
+        assert_eq!(interpolated.tracks[0].segments[0].points.len(), 9);
+        assert_eq!(telemetry.len(), 9);
+        assert_eq!(telemetry[1].heart_rate, Some(141.0), "HR should be interpolated between 140 and 142");
+        assert_eq!(telemetry[1].power, Some(210.0), "Power should be interpolated between 200 and 220");
+
+        // Níveis abaixo de 1 valem como 1 em vez de pedirem infinitos pontos
+        let (clamped, _) = extrator_gpx::utils::interpolate_gpx_points(interpolated.clone(), &telemetry, 0);
+        assert_eq!(clamped.tracks[0].segments[0].points.len(), 9);
+    }
+
+    #[test]
+    fn test_invalid_fit_handling() {
+        // Um arquivo de texto não é um FIT válido e deve falhar graciosamente
+        let result = extrator_gpx::fit_adapter::read_and_process_fit(&PathBuf::from("tests/test_tcx_functionality.rs"));
+        assert!(result.is_err(), "Should fail for invalid FIT data");
+    }
+}
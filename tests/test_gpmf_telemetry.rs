@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use extrator_gpx::gpmf::{decode_gpmf, GpmfPayload};
+
+    // Pacotes `gpmd` sintéticos de uma HERO9 (GPS5 + GPSU a 10 Hz, ACCL e GYRO a 20 Hz; o GYRO é ignorado),
+    // um por segundo de vídeo, e um pacote GPS9 no formato das câmaras HERO11+
+    const HERO9_PAYLOADS: [&str; 3] = [
+        "tests/fixtures/gopro_gpmf/payload_000.bin",
+        "tests/fixtures/gopro_gpmf/payload_001.bin",
+        "tests/fixtures/gopro_gpmf/payload_002.bin",
+    ];
+    const HERO11_GPS9_PAYLOAD: &str = "tests/fixtures/gopro_gpmf/payload_gps9.bin";
+
+    fn load_payload(path: &str, video_time_secs: f64) -> GpmfPayload {
+        GpmfPayload {
+            video_time_secs,
+            duration_secs: 1.0,
+            data: std::fs::read(path).expect("Fixture should exist"),
+        }
+    }
+
+    fn hero9_payloads() -> Vec<GpmfPayload> {
+        HERO9_PAYLOADS.iter()
+            .enumerate()
+            .map(|(i, path)| load_payload(path, i as f64))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_gopro_video_as_track() {
+        assert_eq!(extrator_gpx::track::detect_file_type(&PathBuf::from("GX010042.MP4")), "GPMF");
+        assert_eq!(extrator_gpx::track::detect_file_type(&PathBuf::from("GX010042.LRV")), "GPMF");
+    }
+
+    #[test]
+    fn test_gps5_track_extraction() {
+        let result = decode_gpmf(&hero9_payloads()).expect("GPMF decoding should succeed");
+
+        // 30 amostras a 10 Hz são reduzidas a um ponto por segundo de vídeo
+        let points = &result.gpx.tracks[0].segments[0].points;
+        assert_eq!(points.len(), 3);
+        assert_eq!(result.telemetry.len(), 3, "Telemetry should be parallel to the points");
+
+        assert!((points[0].point().y() - -10.123456).abs() < 1e-6, "Latitude scaled by SCAL");
+        assert!((points[0].point().x() - -48.654321).abs() < 1e-6, "Longitude scaled by SCAL");
+        assert_eq!(points[0].elevation, Some(230.0));
+        assert_eq!(
+            points[1].time.and_then(|t| t.format().ok()).as_deref(),
+            Some("2024-01-15T07:30:01.000000000Z")
+        );
+
+        assert_eq!(result.telemetry[0].speed_mps, Some(8.0), "2D speed from GPS5");
+        assert_eq!(result.telemetry[2].speed_mps, Some(9.0));
+        assert_eq!(result.telemetry[0].altitude_source, Some(extrator_gpx::telemetry::AltitudeSource::Gps));
+
+        assert_eq!(result.extra_data.device_name, Some("HERO9 Black".to_string()));
+        assert_eq!(result.extra_data.max_speed, 9.0);
+        assert!(result.extra_data.total_distance_meters > 20.0);
+    }
+
+    #[test]
+    fn test_video_start_time_from_gps_clock() {
+        let result = decode_gpmf(&hero9_payloads()).unwrap();
+        assert_eq!(
+            result.video_start_time.map(|t| t.to_rfc3339()),
+            Some("2024-01-15T07:30:00+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_accelerometer_g_force() {
+        let result = decode_gpmf(&hero9_payloads()).unwrap();
+
+        // O ACCL da fixture é só a gravidade no eixo Z: sem aceleração própria
+        assert_eq!(result.telemetry.len(), 3);
+        for sample in &result.telemetry {
+            assert!(sample.g_force.unwrap().abs() < 1e-6, "Gravity should be removed, got {:?}", sample.g_force);
+        }
+
+        // Um empurrão de 1 m/s² no eixo X durante o segundo 1 (com SCAL 418, o valor 418 é 1 m/s²)
+        let mut payloads = hero9_payloads();
+        let data = &mut payloads[1].data;
+        let accl = data.windows(4).position(|w| w == b"ACCL").expect("Fixture should have ACCL");
+        for k in 0..20 {
+            let x = accl + 8 + k * 6;
+            data[x..x + 2].copy_from_slice(&418i16.to_be_bytes());
+        }
+
+        let result = decode_gpmf(&payloads).unwrap();
+        let g_force: Vec<f64> = result.telemetry.iter().map(|s| s.g_force.unwrap()).collect();
+        // A gravidade estimada passa a ter 1/3 m/s² em X; o segundo 1 fica com 2/3 m/s² acima dela
+        assert!((g_force[0] - (1.0 / 3.0) / 9.80665).abs() < 1e-3, "Got {}", g_force[0]);
+        assert!((g_force[1] - (2.0 / 3.0) / 9.80665).abs() < 1e-3, "Got {}", g_force[1]);
+        assert!((g_force[2] - g_force[0]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gps9_per_sample_time() {
+        // O pacote começa aos 3 s de vídeo com hora UTC 07:30:05 → vídeo começou às 07:30:02
+        let result = decode_gpmf(&[load_payload(HERO11_GPS9_PAYLOAD, 3.0)])
+            .expect("GPS9 decoding should succeed");
+
+        let points = &result.gpx.tracks[0].segments[0].points;
+        assert_eq!(points.len(), 1);
+        assert!((points[0].point().y() - -10.2).abs() < 1e-6);
+        assert_eq!(points[0].elevation, Some(500.0));
+        assert_eq!(result.telemetry[0].speed_mps, Some(5.0));
+        assert_eq!(result.extra_data.device_name, Some("HERO11 Black".to_string()));
+        assert_eq!(
+            result.video_start_time.map(|t| t.to_rfc3339()),
+            Some("2024-01-15T07:30:02+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_gps9_out_of_range_day_is_skipped() {
+        let mut payload = load_payload(HERO11_GPS9_PAYLOAD, 3.0);
+        // Dias desde 2000 (sexto campo de 4 bytes) da primeira amostra fora do intervalo de datas
+        let gps9 = payload.data.windows(4).position(|key| key == b"GPS9").unwrap();
+        payload.data[gps9 + 8 + 20..gps9 + 8 + 24].copy_from_slice(&i32::MAX.to_be_bytes());
+
+        let result = decode_gpmf(&[payload]).expect("A corrupt sample should not fail the payload");
+        assert_eq!(result.gpx.tracks[0].segments[0].points.len(), 1, "The following samples are kept");
+        assert_eq!(
+            result.video_start_time.map(|t| t.to_rfc3339()),
+            Some("2024-01-15T07:30:02+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_truncated_gpmf_handling() {
+        let mut payload = load_payload(HERO9_PAYLOADS[0], 0.0);
+        payload.data.truncate(100);
+        assert!(decode_gpmf(&[payload]).is_err(), "Should fail for truncated GPMF data");
+    }
+}
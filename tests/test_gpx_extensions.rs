@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    const GARMIN_GPX: &str = "tests/fixtures/garmin_trackpoint_extension.gpx";
+
+    #[test]
+    fn test_gpx_trackpoint_extension_telemetry() {
+        let result = extrator_gpx::gpx_adapter::read_and_process_gpx(&PathBuf::from(GARMIN_GPX))
+            .expect("GPX with extensions should be read");
+
+        assert_eq!(result.telemetry.len(), 3, "Telemetry should be parallel to the points");
+
+        let first = &result.telemetry[0];
+        assert_eq!(first.heart_rate, Some(131.0));
+        assert_eq!(first.cadence, Some(84.0));
+        assert_eq!(first.temperature, Some(24.0));
+        assert_eq!(first.power, Some(210.0), "Garmin PowerExtension should be read");
+
+        let second = &result.telemetry[1];
+        assert_eq!(second.heart_rate, Some(135.0));
+        assert_eq!(second.cadence, None, "Missing channels stay empty");
+
+        assert_eq!(result.telemetry[2].power, Some(250.0), "Strava <power> should be read");
+    }
+
+    #[test]
+    fn test_gpx_extensions_extra_data() {
+        let extra_data = extrator_gpx::gpx_adapter::read_and_process_gpx(&PathBuf::from(GARMIN_GPX))
+            .unwrap()
+            .extra_data
+            .expect("GPX with telemetry should produce extra data");
+
+        assert_eq!(extra_data.sport, Some("cycling".to_string()));
+        assert_eq!(extra_data.average_heart_rate(), Some(135.0));
+        assert_eq!(extra_data.max_cadence(), Some(90.0));
+        assert_eq!(extra_data.power_data, vec![210.0, 250.0]);
+    }
+
+    #[test]
+    fn test_gpx_comments_are_preserved() {
+        let result = extrator_gpx::read_track_file(&PathBuf::from(GARMIN_GPX), extrator_gpx::timezone::DEFAULT_TIMEZONE).unwrap();
+        let first_point = &result.gpx.tracks[0].segments[0].points[0];
+
+        assert_eq!(first_point.comment.as_deref(), Some("Saída de casa"));
+        assert_eq!(result.telemetry[0].heart_rate, Some(131.0), "Comments must not break telemetry");
+    }
+
+    #[test]
+    fn test_extensions_are_aligned_by_position() {
+        // O `<trkpt>` dentro das extensões da trilha não é um ponto para o leitor de GPX
+        let sample_gpx = r#"<?xml version="1.0"?>
+<gpx version="1.1" creator="Test" xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v1">
+  <trk>
+    <extensions><gpxtpx:trkpt lat="1.0" lon="2.0"><gpxtpx:hr>99</gpxtpx:hr></gpxtpx:trkpt></extensions>
+    <trkseg>
+      <trkpt lat="-10.1" lon="-48.6">
+        <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>120</gpxtpx:hr></gpxtpx:TrackPointExtension></extensions>
+      </trkpt>
+      <trkpt lat="-10.2" lon="-48.7">
+        <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>125</gpxtpx:hr></gpxtpx:TrackPointExtension></extensions>
+      </trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+        let path = std::env::temp_dir().join(format!("aligned_{}.gpx", uuid::Uuid::new_v4()));
+        std::fs::write(&path, sample_gpx).unwrap();
+        let result = extrator_gpx::gpx_adapter::read_and_process_gpx(&path);
+        std::fs::remove_file(&path).ok();
+
+        let result = result.unwrap();
+        assert_eq!(result.gpx.tracks[0].segments[0].points.len(), 2);
+        let heart_rates: Vec<_> = result.telemetry.iter().map(|s| s.heart_rate).collect();
+        assert_eq!(heart_rates, vec![Some(120.0), Some(125.0)], "Telemetry is kept and stays on its point");
+    }
+
+    #[test]
+    fn test_plain_gpx_has_no_telemetry() {
+        let sample_gpx = r#"<?xml version="1.0"?>
+<gpx version="1.1" creator="Test">
+  <trk>
+    <trkseg>
+      <trkpt lat="-10.123456" lon="-48.654321">
+        <ele>230.5</ele>
+        <time>2024-01-15T07:30:00Z</time>
+      </trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+        let gpx_path = std::env::temp_dir().join("test_plain_gpx_has_no_telemetry.gpx");
+        std::fs::write(&gpx_path, sample_gpx).expect("Failed to write GPX data");
+
+        let result = extrator_gpx::read_track_file(&gpx_path, extrator_gpx::timezone::DEFAULT_TIMEZONE).expect("Plain GPX should be read");
+        assert!(result.extra_data.is_none(), "Plain GPX has no extra data");
+        assert!(result.telemetry.is_empty(), "Plain GPX has no telemetry");
+
+        let _ = std::fs::remove_file(gpx_path);
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use extrator_gpx::AthleteProfile;
+    use extrator_gpx::heart_rate::{format_duration, HeartRateZones, TimeInZones, ZoneMethod, DEFAULT_MAX_HR};
+
+    #[test]
+    fn test_zones_from_max_hr() {
+        let zones = HeartRateZones::from_max_hr(200.0);
+        assert_eq!(zones.bounds, [100.0, 120.0, 140.0, 160.0, 180.0, 200.0]);
+        assert_eq!(zones.zone(90.0), 0, "Below Z1 counts as Z1");
+        assert_eq!(zones.zone(119.9), 0);
+        assert_eq!(zones.zone(120.0), 1);
+        assert_eq!(zones.zone(165.0), 3);
+        assert_eq!(zones.zone(215.0), 4);
+        assert_eq!(zones.ratio(150.0), 0.5);
+        assert_eq!(zones.ratio(250.0), 1.0);
+        assert_eq!(HeartRateZones::default(), HeartRateZones::from_max_hr(DEFAULT_MAX_HR));
+    }
+
+    #[test]
+    fn test_zones_from_lthr_and_reserve() {
+        let lthr = HeartRateZones::from_lthr(170.0);
+        assert_eq!(lthr.zone(150.0), 1, "88% of LTHR is Z2");
+        assert_eq!(lthr.zone(170.0), 4, "At the threshold is Z5");
+
+        // Reserva de 130 bpm: Z3 começa em 60 + 0.7 * 130
+        let reserve = HeartRateZones::from_reserve(190.0, 60.0);
+        assert!((reserve.bounds[2] - 151.0).abs() < 1e-9);
+        assert_eq!(reserve.zone(150.0), 1);
+        assert_eq!(reserve.zone(152.0), 2);
+    }
+
+    #[test]
+    fn test_settings_pick_the_method() {
+        let settings = |method, max, resting, lthr| HeartRateZones::from_settings(method, max, resting, lthr).map(|z| z.map(|z| z.method));
+        assert_eq!(settings(None, None, None, None), Ok(None));
+        assert_eq!(settings(None, Some(185.0), None, None), Ok(Some(ZoneMethod::MaxHr)));
+        assert_eq!(settings(None, Some(185.0), Some(55.0), None), Ok(Some(ZoneMethod::Reserve)));
+        assert_eq!(settings(None, Some(185.0), Some(55.0), Some(165.0)), Ok(Some(ZoneMethod::Lthr)));
+        assert_eq!(settings(Some(ZoneMethod::MaxHr), Some(185.0), Some(55.0), Some(165.0)), Ok(Some(ZoneMethod::MaxHr)));
+
+        assert!(settings(Some(ZoneMethod::Lthr), Some(185.0), None, None).is_err());
+        assert!(settings(Some(ZoneMethod::Reserve), Some(185.0), None, None).is_err());
+        assert!(settings(None, Some(120.0), Some(130.0), None).is_err(), "Resting above max");
+        assert!(settings(None, Some(400.0), None, None).is_err());
+    }
+
+    #[test]
+    fn test_profile_from_form_fields() {
+        let profile = AthleteProfile::from_params(Some(""), Some(" 180 "), Some(""), None, None, None).unwrap();
+        assert_eq!(profile.heart_rate_zones, Some(HeartRateZones::from_max_hr(180.0)));
+        assert_eq!(AthleteProfile::from_params(None, None, None, None, None, None).unwrap(), AthleteProfile::default());
+        assert!(AthleteProfile::from_params(Some("zones"), Some("180"), None, None, None, None).is_err());
+        assert!(AthleteProfile::from_params(None, Some("fast"), None, None, None, None).unwrap_err().contains("fast"));
+    }
+
+    #[test]
+    fn test_time_in_zones() {
+        let zones = HeartRateZones::from_max_hr(200.0);
+        let mut time = TimeInZones::default();
+        time.add(&zones, 110.0, 30.0);
+        time.add(&zones, 165.0, 12.5);
+        time.add(&zones, 170.0, 7.5);
+        time.add(&zones, 190.0, -3.0);
+        assert_eq!(time.0, [30.0, 0.0, 0.0, 20.0, 0.0]);
+        assert_eq!(time.total(), 50.0);
+
+        assert_eq!(format_duration(75.4), "1:15");
+        assert_eq!(format_duration(3725.0), "1:02:05");
+    }
+}
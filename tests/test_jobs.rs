@@ -0,0 +1,260 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use uuid::Uuid;
+    use extrator_gpx::jobs::{CancelOutcome, JobEvent, JobQueue, JobState, JobStatus};
+    use extrator_gpx::progress::{FrameProgress, ProgressEvent};
+
+    fn temp_queue(max_concurrent_jobs: usize) -> (Arc<JobQueue>, PathBuf) {
+        let root = std::env::temp_dir().join(format!("jobs_{}", Uuid::new_v4()));
+        let queue = JobQueue::new(root.join("jobs"), root.join("output"), max_concurrent_jobs, Duration::from_secs(60));
+        (Arc::new(queue), root)
+    }
+
+    async fn wait_until_finished(queue: &JobQueue, id: Uuid) -> JobStatus {
+        for _ in 0..200 {
+            let status = queue.status(id).expect("Job should exist");
+            if status.state.is_finished() {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Job {} did not finish", id);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_completed_job_has_download_url() {
+        let (queue, root) = temp_queue(1);
+        let paths = queue.prepare().unwrap();
+        let work_dir = paths.work_dir.clone();
+        assert!(work_dir.is_dir());
+
+        let id = queue.submit(paths, |paths, _, _| {
+            std::fs::write(&paths.output_file, b"video").unwrap();
+            Ok(vec!["Concluído".to_string()])
+        });
+
+        let status = wait_until_finished(&queue, id).await;
+        assert_eq!(status.state, JobState::Completed);
+        assert_eq!(status.logs, vec!["Concluído".to_string()]);
+        assert_eq!(status.download_url, Some(format!("/output/{}.mp4", id)));
+        assert!(status.finished_at.is_some());
+        assert!(root.join("output").join(format!("{}.mp4", id)).exists());
+        assert!(!work_dir.exists(), "The work dir is removed when the job finishes");
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_failed_job_keeps_error_and_logs() {
+        let (queue, root) = temp_queue(1);
+        let paths = queue.prepare().unwrap();
+        let output_file = paths.output_file.clone();
+        let id = queue.submit(paths, |paths, _, _| {
+            std::fs::write(&paths.output_file, b"video incompleto").unwrap();
+            Err(("FFmpeg falhou".to_string(), vec!["log".to_string()]))
+        });
+
+        let status = wait_until_finished(&queue, id).await;
+        assert_eq!(status.state, JobState::Failed);
+        assert_eq!(status.error.as_deref(), Some("FFmpeg falhou"));
+        assert_eq!(status.logs, vec!["log".to_string()]);
+        assert!(status.download_url.is_none());
+        assert!(!output_file.exists(), "The partial output of a failed job is removed");
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrency_limit() {
+        let (queue, root) = temp_queue(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let ids: Vec<Uuid> = (0..6)
+            .map(|_| {
+                let (running, peak) = (running.clone(), peak.clone());
+                queue.submit(queue.prepare().unwrap(), move |_, _, _| {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(50));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(vec![])
+                })
+            })
+            .collect();
+
+        // Com dois workers ocupados, os restantes esperam na fila
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let queued = ids.iter().filter(|id| queue.status(**id).unwrap().state == JobState::Queued).count();
+        assert!(queued >= 3, "{} jobs were queued", queued);
+
+        for id in &ids {
+            assert_eq!(wait_until_finished(&queue, *id).await.state, JobState::Completed);
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_progress_is_recorded_and_streamed() {
+        let (queue, root) = temp_queue(1);
+        let paths = queue.prepare().unwrap();
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+
+        let id = queue.submit(paths, move |_, progress, _| {
+            progress.report(ProgressEvent::Log { message: "A ler o vídeo".to_string() });
+            wait.recv().ok();
+            progress.report(ProgressEvent::Frames(FrameProgress { current: 5, total: 10 }));
+            Ok(vec!["A ler o vídeo".to_string(), "Concluído".to_string()])
+        });
+
+        // Quem se liga a meio recebe o estado e os logs já escritos
+        let mut subscription = loop {
+            let subscription = queue.subscribe(id).unwrap();
+            if subscription.history.len() >= 2 {
+                break subscription;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+        assert!(matches!(&subscription.history[0], JobEvent::State(change) if change.state == JobState::Running));
+        assert!(matches!(&subscription.history[1], JobEvent::Progress(ProgressEvent::Log { message }) if message == "A ler o vídeo"));
+        assert_eq!(queue.status(id).unwrap().logs, vec!["A ler o vídeo".to_string()]);
+
+        release.send(()).unwrap();
+        let receiver = subscription.receiver.as_mut().expect("Running jobs have live events");
+        let frames = receiver.recv().await.unwrap();
+        assert!(matches!(frames, JobEvent::Progress(ProgressEvent::Frames(FrameProgress { current: 5, total: 10 }))));
+        let JobEvent::State(change) = receiver.recv().await.unwrap() else { panic!("Expected the final state") };
+        assert_eq!(change.state, JobState::Completed);
+        assert_eq!(change.download_url, Some(format!("/output/{}.mp4", id)));
+
+        let status = wait_until_finished(&queue, id).await;
+        assert_eq!(status.frames, Some(FrameProgress { current: 5, total: 10 }));
+        assert!(queue.subscribe(id).unwrap().receiver.is_none(), "Finished jobs have no live events");
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cancel_running_job() {
+        let (queue, root) = temp_queue(1);
+        let paths = queue.prepare().unwrap();
+        let work_dir = paths.work_dir.clone();
+        let output_file = paths.output_file.clone();
+
+        // Simula o ciclo de frames: escreve parte do vídeo e para quando o cancelamento chega
+        let id = queue.submit(paths, |paths, _, cancel| {
+            std::fs::write(&paths.output_file, b"parcial").unwrap();
+            while !cancel.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            Err(("Processamento cancelado.".to_string(), vec![]))
+        });
+
+        while queue.status(id).unwrap().state != JobState::Running {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert!(matches!(queue.cancel(id), Some(CancelOutcome::Cancelling(_))));
+
+        let status = wait_until_finished(&queue, id).await;
+        assert_eq!(status.state, JobState::Cancelled);
+        assert!(status.error.is_none());
+        assert!(status.download_url.is_none());
+        assert!(!work_dir.exists());
+        assert!(!output_file.exists(), "The partial output is removed");
+        assert!(matches!(queue.cancel(id), Some(CancelOutcome::AlreadyFinished(_))));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cancel_queued_job() {
+        let (queue, root) = temp_queue(1);
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let blocker = queue.submit(queue.prepare().unwrap(), move |_, _, _| {
+            wait.recv().ok();
+            Ok(vec![])
+        });
+
+        let ran = Arc::new(AtomicUsize::new(0));
+        let queued_ran = ran.clone();
+        let paths = queue.prepare().unwrap();
+        let work_dir = paths.work_dir.clone();
+        let queued = queue.submit(paths, move |_, _, _| {
+            queued_ran.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![])
+        });
+
+        let Some(CancelOutcome::Cancelled(status)) = queue.cancel(queued) else { panic!("Queued jobs are cancelled at once") };
+        assert_eq!(status.state, JobState::Cancelled);
+
+        release.send(()).unwrap();
+        assert_eq!(wait_until_finished(&queue, blocker).await.state, JobState::Completed);
+        for _ in 0..100 {
+            if !work_dir.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!work_dir.exists());
+        assert_eq!(ran.load(Ordering::SeqCst), 0, "A cancelled job never runs");
+        assert_eq!(queue.status(queued).unwrap().state, JobState::Cancelled);
+        assert!(queue.cancel(Uuid::new_v4()).is_none());
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_finished_jobs_expire() {
+        let (queue, root) = temp_queue(2);
+        let completed = queue.submit(queue.prepare().unwrap(), |paths, _, _| {
+            std::fs::write(&paths.output_file, b"video").unwrap();
+            Ok(Vec::new())
+        });
+        let failed = queue.submit(queue.prepare().unwrap(), |_, _, _| Err(("falhou".to_string(), Vec::new())));
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let running = queue.submit(queue.prepare().unwrap(), move |_, _, _| {
+            wait.recv().ok();
+            Ok(Vec::new())
+        });
+        wait_until_finished(&queue, completed).await;
+        wait_until_finished(&queue, failed).await;
+        let output = root.join("output").join(format!("{}.mp4", completed));
+        assert!(output.exists());
+
+        assert!(queue.remove_expired(chrono::Utc::now()).is_empty(), "Finished jobs are kept for a TTL");
+        let mut removed = queue.remove_expired(chrono::Utc::now() + chrono::Duration::minutes(2));
+        removed.sort();
+        let mut expected = vec![completed, failed];
+        expected.sort();
+        assert_eq!(removed, expected);
+        assert!(queue.status(completed).is_none() && queue.status(failed).is_none());
+        assert!(!output.exists(), "The video of an expired job is removed");
+        assert!(!queue.status(running).unwrap().state.is_finished(), "Unfinished jobs never expire");
+
+        release.send(()).unwrap();
+        wait_until_finished(&queue, running).await;
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn test_jobs_have_unique_paths() {
+        let (queue, root) = temp_queue(1);
+        let first = queue.prepare().unwrap();
+        let second = queue.prepare().unwrap();
+        assert_ne!(first.work_dir, second.work_dir);
+        assert_ne!(first.output_file, second.output_file);
+
+        queue.discard(&first);
+        assert!(!first.work_dir.exists());
+        assert!(second.work_dir.exists());
+        assert!(queue.status(first.id).is_none(), "Prepared jobs are not listed until submitted");
+
+        std::fs::remove_dir_all(root).ok();
+    }
+}
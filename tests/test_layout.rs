@@ -0,0 +1,279 @@
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use extrator_gpx::drawing::{load_font, Theme};
+    use extrator_gpx::layout::{resolution_scale, Anchor, Color, Layout, LayoutResources, Length, Offset, Size, WidgetLayout, PRESETS};
+    use extrator_gpx::widget::WidgetRegistry;
+
+    const CANVAS: (u32, u32) = (1920, 1080);
+
+    #[test]
+    fn test_parse_toml_and_json() {
+        let toml = r##"
+            [[widgets]]
+            widget = "speedometer"
+            anchor = "bottom-right"
+            offset = { x = 40, y = "5%" }
+            opacity = 0.5
+            z = 2
+            style = { accent_color = "#ff8800", background_color = "#00000080" }
+        "##;
+        let json = r##"{"widgets": [{"widget": "speedometer", "anchor": "bottom-right",
+            "offset": {"x": "40px", "y": "5%"}, "opacity": 0.5, "z": 2,
+            "style": {"accent_color": "#ff8800", "background_color": "#00000080"}}]}"##;
+
+        let from_toml = Layout::parse(toml).unwrap();
+        assert_eq!(from_toml, Layout::parse(json).unwrap());
+
+        let widget = &from_toml.widgets[0];
+        assert_eq!(widget.widget, "speedometer");
+        assert_eq!(widget.anchor, Anchor::BottomRight);
+        assert_eq!(widget.offset, Offset { x: Some(Length::Pixels(40.0)), y: Some(Length::Percent(5.0)) });
+        assert_eq!(widget.scale, 1.0, "Scale defaults to 1");
+        assert_eq!(widget.style.accent_color, Some(Color(Rgba([255, 136, 0, 255]))));
+        assert_eq!(widget.style.background_color, Some(Color(Rgba([0, 0, 0, 128]))));
+    }
+
+    #[test]
+    fn test_anchor_origins_with_offsets() {
+        let layout = Layout::parse(r#"
+            [[widgets]]
+            widget = "stats"
+            anchor = "bottom-right"
+            offset = { x = 40, y = "5%" }
+
+            [[widgets]]
+            widget = "track_map"
+            anchor = "top-center"
+
+            [[widgets]]
+            widget = "speedometer"
+            anchor = "center"
+            offset = { x = -100 }
+        "#).unwrap();
+        let widget = (300, 300);
+
+        // 1920 - 300 - 40 e 1080 - 300 - 54
+        assert_eq!(layout.widgets[0].origin(CANVAS, widget), (1580, 726));
+        // Sem offset: margem padrão na borda e centrado no outro eixo
+        assert_eq!(layout.widgets[1].origin(CANVAS, widget), (810, 10));
+        assert_eq!(layout.widgets[2].origin(CANVAS, widget), (710, 390));
+    }
+
+    #[test]
+    fn test_output_size_keeps_aspect_ratio() {
+        let layout = Layout::parse(r#"
+            [[widgets]]
+            widget = "stats"
+            size = { height = "50%" }
+
+            [[widgets]]
+            widget = "speedometer"
+            size = { width = 200, height = 100 }
+            scale = 1.5
+
+            [[widgets]]
+            widget = "track_map"
+            scale = 0.5
+        "#).unwrap();
+
+        assert_eq!(layout.widgets[0].output_size((280, 420), CANVAS), (360, 540));
+        assert_eq!(layout.widgets[1].output_size((300, 300), CANVAS), (300, 150));
+        assert_eq!(layout.widgets[2].output_size((300, 300), CANVAS), (150, 150));
+
+        // Layouts montados no código não passam por `validate`: o widget continua a caber no vídeo
+        let mut huge = WidgetLayout::new("speedometer", Anchor::TopLeft);
+        huge.scale = 1000.0;
+        assert_eq!(huge.output_size((300, 300), CANVAS), (1080, 1080));
+        huge.size = Some(Size { width: Some(Length::Percent(100_000.0)), height: None });
+        huge.scale = 1.0;
+        assert_eq!(huge.output_size((300, 150), CANVAS), (1920, 960));
+    }
+
+    #[test]
+    fn test_invalid_layouts_are_rejected() {
+        let invalid = [
+            "[[widgets]]\nwidget = \"clock\"",
+            "[[widgets]]\nwidget = \"stats\"\nanchor = \"middle\"",
+            "[[widgets]]\nwidget = \"stats\"\nopacity = 1.5",
+            "[[widgets]]\nwidget = \"stats\"\nscale = 0",
+            "[[widgets]]\nwidget = \"stats\"\nscale = 1000",
+            "[[widgets]]\nwidget = \"stats\"\nsize = { width = \"100000%\" }",
+            "[[widgets]]\nwidget = \"stats\"\nsize = { height = 50000 }",
+            "[[widgets]]\nwidget = \"stats\"\nsize = { width = \"nan\" }",
+            "[[widgets]]\nwidget = \"stats\"\nsize = { width = \"-10%\" }",
+            "[[widgets]]\nwidget = \"stats\"\noffset = { x = \"ten\" }",
+            "[[widgets]]\nwidget = \"stats\"\nstyle = { text_color = \"white\" }",
+            "[[widgets]]\nwidget = \"stats\"\ncolour = \"#ffffff\"",
+            "{\"widgets\": [",
+        ];
+        for document in invalid {
+            assert!(Layout::parse(document).is_err(), "Expected an error for {:?}", document);
+        }
+    }
+
+    #[test]
+    fn test_presets_are_valid() {
+        assert!(!PRESETS.is_empty());
+        let registry = WidgetRegistry::builtin();
+        for (name, _) in PRESETS {
+            let layout = Layout::preset(name).unwrap();
+            assert!(!layout.widgets.is_empty(), "Preset {} has no widgets", name);
+            for widget in &layout.widgets {
+                widget.theme((registry.get(&widget.widget).unwrap().default_theme)()).unwrap();
+            }
+        }
+        assert!(Layout::preset("unknown").is_none());
+    }
+
+    #[test]
+    fn test_classic_preset_matches_form_defaults() {
+        let classic = Layout::preset("classic").unwrap();
+        let corners = Layout::from_corners(Some("bottom-left"), Some("top-right"), Some("bottom-right"));
+        assert_eq!(classic, corners);
+    }
+
+    #[test]
+    fn test_from_corners_and_draw_order() {
+        let layout = Layout::from_corners(Some("top-left"), None, Some(""));
+        assert!(layout.contains("speedometer"));
+        assert!(!layout.contains("track_map"));
+        assert_eq!(layout.widgets[1].anchor, Anchor::BottomLeft, "Unknown corners fall back to bottom-left");
+
+        let layered = Layout::parse(r#"
+            [[widgets]]
+            widget = "stats"
+            z = 3
+
+            [[widgets]]
+            widget = "speedometer"
+
+            [[widgets]]
+            widget = "track_map"
+        "#).unwrap();
+        let order: Vec<&str> = layered.ordered(CANVAS).iter().map(|w| w.widget.as_str()).collect();
+        assert_eq!(order, ["speedometer", "track_map", "stats"]);
+    }
+
+    #[test]
+    fn test_pixel_measures_follow_resolution() {
+        assert_eq!(resolution_scale(CANVAS), 1.0);
+        assert_eq!(resolution_scale((3840, 2160)), 2.0);
+        assert_eq!(resolution_scale((1080, 1920)), 1.0, "Portrait 1080p is the reference too");
+
+        let layout = Layout::parse(r#"
+            [[widgets]]
+            widget = "speedometer"
+            anchor = "bottom-right"
+            offset = { x = 40, y = "5%" }
+
+            [[widgets]]
+            widget = "stats"
+            size = { height = "50%" }
+        "#).unwrap();
+        let (speedometer, stats) = (&layout.widgets[0], &layout.widgets[1]);
+
+        // Em 4K o tamanho natural e os pixels dobram; as percentagens não mudam
+        let uhd = (3840, 2160);
+        assert_eq!(speedometer.output_size((300, 300), uhd), (600, 600));
+        assert_eq!(speedometer.origin(uhd, (600, 600)), (3840 - 600 - 80, 2160 - 600 - 108));
+        assert_eq!(stats.output_size((280, 420), uhd), (720, 1080));
+
+        // Em 720p ficam a dois terços
+        let hd = (1280, 720);
+        assert_eq!(speedometer.output_size((300, 300), hd), (200, 200));
+        assert_eq!(stats.origin(hd, (240, 360)), (7, 720 - 360 - 7), "Default margin is scaled");
+    }
+
+    #[test]
+    fn test_widget_options() {
+        let layout = Layout::parse(r#"
+            [[widgets]]
+            widget = "elevation_profile"
+            options = { color_by_gradient = true }
+        "#).unwrap();
+        let options = &layout.widgets[0].options;
+        assert_eq!(options.get("color_by_gradient"), Some(&serde_json::Value::Bool(true)));
+
+        // Opções definidas depois da leitura, como o --basemap da linha de comando
+        let mut corners = Layout::from_corners(None, Some("top-right"), Some("bottom-right"));
+        corners.set_option("track_map", "basemap", serde_json::json!("tiles.mbtiles"));
+        assert_eq!(corners.widgets[0].options.get("basemap"), Some(&serde_json::json!("tiles.mbtiles")));
+        assert!(corners.widgets[1].options.is_empty());
+
+        // Sem opções o campo não aparece ao serializar
+        let plain = Layout::parse("[[widgets]]\nwidget = \"stats\"").unwrap();
+        assert!(plain.widgets[0].options.is_empty());
+        assert!(!serde_json::to_string(&plain).unwrap().contains("options"));
+    }
+
+    #[test]
+    fn test_portrait_section() {
+        let layout = Layout::parse(r#"
+            [[widgets]]
+            widget = "speedometer"
+            anchor = "bottom-right"
+
+            [[portrait]]
+            widget = "speedometer"
+            anchor = "bottom-center"
+
+            [[portrait]]
+            widget = "track_map"
+            anchor = "top-center"
+        "#).unwrap();
+        assert!(layout.contains("track_map"));
+        assert_eq!(layout.widgets_for(CANVAS).len(), 1);
+        assert_eq!(layout.widgets_for((1080, 1920)).len(), 2);
+
+        // Sem secção `portrait` os vídeos verticais usam `widgets`
+        let landscape_only = Layout::preset("classic").unwrap();
+        assert_eq!(landscape_only.widgets_for((1080, 1920)), landscape_only.widgets.as_slice());
+        assert!(!Layout::preset("dashboard").unwrap().portrait.is_empty());
+
+        let error = Layout::parse("[[portrait]]\nwidget = \"clock\"").unwrap_err();
+        assert!(error.to_string().contains("portrait[0]"), "{}", error);
+    }
+
+    #[test]
+    fn test_client_fonts_are_confined() {
+        let fonts = tempfile::tempdir().unwrap();
+        std::fs::create_dir(fonts.path().join("marca")).unwrap();
+        std::fs::copy("DejaVuSans.ttf", fonts.path().join("marca").join("Titulo.ttf")).unwrap();
+        let resources = LayoutResources { fonts_dir: Some(fonts.path().to_path_buf()), ..Default::default() };
+        let with_fonts = |font: &str| Layout::parse(&format!(
+            "[[widgets]]\nwidget = \"stats\"\nstyle = {{ font = \"{}\", bold_font = \"DejaVuSans-Bold\" }}", font,
+        )).unwrap();
+
+        let mut layout = with_fonts("marca/Titulo.ttf");
+        layout.confine(&resources).unwrap();
+        let style = &layout.widgets[0].style;
+        assert_eq!(style.font.as_deref(), Some(fonts.path().canonicalize().unwrap().join("marca/Titulo.ttf").to_str().unwrap()));
+        assert_eq!(style.bold_font.as_deref(), Some("DejaVuSans-Bold"), "Built-in fonts are kept");
+        layout.widgets[0].theme(Theme::stats()).unwrap();
+
+        for font in ["/etc/passwd", "../DejaVuSans.ttf", "marca/../../x.ttf", "nao_existe.ttf"] {
+            assert!(with_fonts(font).confine(&resources).is_err(), "{}", font);
+        }
+        // Uma ligação simbólica para fora do diretório também é recusada
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::fs::canonicalize("DejaVuSans.ttf").unwrap(), fonts.path().join("fora.ttf")).unwrap();
+            assert!(with_fonts("fora.ttf").confine(&resources).is_err());
+        }
+        // Sem diretório configurado, só as fontes incluídas
+        assert!(with_fonts("marca/Titulo.ttf").confine(&LayoutResources::default()).is_err());
+        with_fonts("DejaVuSans").confine(&LayoutResources::default()).unwrap();
+    }
+
+    #[test]
+    fn test_font_files_are_limited() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_font(dir.path().to_str().unwrap()).err().unwrap().to_string().contains("não é um arquivo"));
+        let large = dir.path().join("grande.ttf");
+        std::fs::File::create(&large).unwrap().set_len(64 * 1024 * 1024).unwrap();
+        assert!(load_font(large.to_str().unwrap()).err().unwrap().to_string().contains("MB"));
+        #[cfg(unix)]
+        assert!(load_font("/dev/zero").is_err());
+    }
+}
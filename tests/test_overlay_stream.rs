@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use extrator_gpx::overlay_stream::{active_frame, ffmpeg_args, frame_count, OVERLAY_FPS};
+
+    #[test]
+    fn test_single_overlay_input_and_filter() {
+        let args = ffmpeg_args(Path::new("video.mp4"), 1920, 1080, Path::new("output/job.mp4"));
+
+        // Só duas entradas, qualquer que seja a duração do vídeo: o vídeo e o stdin
+        assert_eq!(args.iter().filter(|a| *a == "-i").count(), 2);
+        assert!(args.windows(2).any(|w| w[0] == "-i" && w[1] == "pipe:0"));
+        assert!(args.windows(2).any(|w| w[0] == "-video_size" && w[1] == "1920x1080"));
+        assert!(args.windows(2).any(|w| w[0] == "-pixel_format" && w[1] == "rgba"));
+        assert!(args.windows(2).any(|w| w[0] == "-progress" && w[1] == "pipe:1"));
+
+        let filter = args.iter().skip_while(|a| *a != "-filter_complex").nth(1).unwrap();
+        assert_eq!(filter.matches("overlay").count(), 1);
+        assert!(!filter.contains("enable"), "No per-frame enable expressions");
+        assert_eq!(args.last().map(String::as_str), Some("output/job.mp4"));
+    }
+
+    #[test]
+    fn test_frame_count_is_linear_in_duration() {
+        assert_eq!(frame_count(0.0), 0);
+        assert_eq!(frame_count(60.0), (60.0 * OVERLAY_FPS) as usize);
+        assert_eq!(frame_count(1800.0), 30 * frame_count(60.0));
+        assert_eq!(frame_count(0.1), 1, "A partial frame still needs one overlay frame");
+    }
+
+    #[test]
+    fn test_active_frame_schedule() {
+        let timestamps = [2.0, 3.0, 4.5];
+
+        assert_eq!(active_frame(&timestamps, 0.0), None, "Nothing before the first point");
+        assert_eq!(active_frame(&timestamps, 2.0), Some(0));
+        assert_eq!(active_frame(&timestamps, 2.9), Some(0));
+        assert_eq!(active_frame(&timestamps, 3.0), Some(1));
+        assert_eq!(active_frame(&timestamps, 4.4), Some(1));
+        assert_eq!(active_frame(&timestamps, 5.4), Some(2), "The last point stays for one second");
+        assert_eq!(active_frame(&timestamps, 5.5), None);
+        assert_eq!(active_frame(&[], 1.0), None);
+    }
+
+    #[test]
+    fn test_map_projection_matches_track_bounds() {
+        let gpx_path = std::path::PathBuf::from("tests/fixtures/garmin_trackpoint_extension.gpx");
+        let track = extrator_gpx::read_track_file(&gpx_path, extrator_gpx::timezone::DEFAULT_TIMEZONE).unwrap();
+        let projection = extrator_gpx::drawing::MapProjection::new(&track.gpx, 300, 300, 20.0).unwrap();
+
+        // Todos os pontos ficam dentro do mapa, respeitando a margem
+        for point in track.gpx.tracks.iter().flat_map(|t| t.segments.iter()).flat_map(|s| s.points.iter()) {
+            let (x, y) = projection.project(point.point().x(), point.point().y());
+            assert!((20.0 - 1e-6..=280.0 + 1e-6).contains(&x), "x = {}", x);
+            assert!((20.0 - 1e-6..=280.0 + 1e-6).contains(&y), "y = {}", y);
+        }
+        assert!(extrator_gpx::drawing::MapProjection::new(&gpx::Gpx::default(), 300, 300, 20.0).is_none());
+    }
+}
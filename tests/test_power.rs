@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use extrator_gpx::AthleteProfile;
+    use extrator_gpx::power::{gauge_max, PowerSeries, PowerTotals, DEFAULT_POWER_SCALE_W};
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        start() + Duration::seconds(secs)
+    }
+
+    #[test]
+    fn test_series_is_resampled_to_one_hertz() {
+        // Leituras a cada 2 s, e uma pausa de 60 s que fica fora da série
+        let series = PowerSeries::from_samples([(at(0), 100.0), (at(2), 200.0), (at(4), 300.0), (at(64), 400.0)]);
+        let watts: Vec<f64> = series.watts_between(at(-1), at(100)).collect();
+        assert_eq!(watts, [100.0, 100.0, 200.0, 200.0, 300.0, 400.0]);
+        assert_eq!(series.average(at(3), 3), Some(500.0 / 3.0), "Seconds 1, 2 and 3");
+        assert_eq!(series.average(at(30), 10), None, "No readings during the pause");
+        assert!(PowerSeries::from_samples([]).is_empty());
+    }
+
+    #[test]
+    fn test_steady_effort_summary() {
+        // Uma hora constante no FTP: NP = FTP, IF = 1 e TSS = 100
+        let series = PowerSeries::from_samples((0..3600).map(|s| (at(s), 250.0)));
+        let summary = series.summary(Some(250.0)).unwrap();
+        assert_eq!(summary.average_w, 250.0);
+        assert!((summary.normalized_w.unwrap() - 250.0).abs() < 1e-9);
+        assert!((summary.intensity_factor.unwrap() - 1.0).abs() < 1e-9);
+        assert!((summary.training_stress_score.unwrap() - 100.0).abs() < 1e-6);
+        assert_eq!(summary.describe(), "NP 250 W · IF 1.00 · TSS 100");
+
+        // Sem FTP só há NP
+        let without_ftp = series.summary(None).unwrap();
+        assert_eq!((without_ftp.intensity_factor, without_ftp.training_stress_score), (None, None));
+    }
+
+    #[test]
+    fn test_normalized_power_weights_surges() {
+        // Blocos de 30 s alternados entre 100 e 400 W: média 250 W, NP bem acima
+        let series = PowerSeries::from_samples((0..1200).map(|s| (at(s), if s / 30 % 2 == 0 { 100.0 } else { 400.0 })));
+        let summary = series.summary(Some(250.0)).unwrap();
+        assert_eq!(summary.average_w, 250.0);
+        assert_eq!(summary.max_w, 400.0);
+        assert!(summary.normalized_w.unwrap() > 280.0, "{:?}", summary.normalized_w);
+        assert!(summary.intensity_factor.unwrap() > 1.1);
+    }
+
+    #[test]
+    fn test_totals_need_thirty_seconds_for_np() {
+        let mut totals = PowerTotals::default();
+        assert!(totals.summary(Some(250.0)).is_none());
+        (0..29).for_each(|_| totals.push(200.0));
+        let short = totals.summary(Some(250.0)).unwrap();
+        assert_eq!(short.normalized_w, None);
+        assert_eq!(short.describe(), "AVG 200 W");
+        totals.push(200.0);
+        assert_eq!(totals.summary(Some(250.0)).unwrap().normalized_w, Some(200.0));
+    }
+
+    #[test]
+    fn test_athlete_power_settings() {
+        let athlete = AthleteProfile::from_params(None, None, None, None, Some(" 280 "), Some("70")).unwrap();
+        assert_eq!((athlete.ftp, athlete.weight_kg), (Some(280.0), Some(70.0)));
+        assert_eq!(athlete.watts_per_kg(350.0), Some(5.0));
+        assert!(AthleteProfile::from_params(None, None, None, None, Some("2000"), None).unwrap_err().contains("FTP"));
+        assert!(AthleteProfile::default().with_power(None, Some(10.0)).is_err());
+
+        assert_eq!(gauge_max(Some(280.0)), 500.0);
+        assert_eq!(gauge_max(None), DEFAULT_POWER_SCALE_W);
+    }
+}
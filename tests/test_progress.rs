@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use extrator_gpx::progress::{EncodeProgressParser, ProgressEvent, ProgressLog, ProgressReporter};
+
+    #[test]
+    fn test_parse_ffmpeg_progress_block() {
+        let mut parser = EncodeProgressParser::new(120.0);
+        let block = ["frame=720", "fps=48.00", "out_time_us=30000000", "out_time_ms=30000000", "out_time=00:00:30.000000", "speed=2.5x"];
+        for line in block {
+            assert!(parser.feed(line).is_none());
+        }
+
+        let progress = parser.feed("progress=continue").expect("A block ends at progress=");
+        assert_eq!(progress.out_time_secs, 30.0);
+        assert_eq!(progress.fps, Some(48.0));
+        assert_eq!(progress.speed, Some(2.5));
+        assert_eq!(progress.percent, Some(25.0));
+        // 90 s de vídeo por codificar a 2,5x
+        assert_eq!(progress.eta_secs, Some(36.0));
+    }
+
+    #[test]
+    fn test_progress_end_and_unknown_values() {
+        let mut parser = EncodeProgressParser::new(60.0);
+        parser.feed("out_time_us=N/A");
+        parser.feed("speed=N/A");
+        let progress = parser.feed("progress=continue").unwrap();
+        assert_eq!(progress.out_time_secs, 0.0);
+        assert_eq!(progress.speed, None);
+        assert_eq!(progress.eta_secs, None);
+
+        parser.feed("out_time_us=59900000");
+        parser.feed("speed=1x");
+        let progress = parser.feed("progress=end").unwrap();
+        assert_eq!(progress.percent, Some(100.0));
+        assert_eq!(progress.eta_secs, Some(0.0));
+    }
+
+    #[test]
+    fn test_log_lines_are_published() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let mut logs = ProgressLog::new(ProgressReporter::new(move |event| sink.lock().unwrap().push(event)));
+
+        logs.push("A ler o GPX".to_string());
+        logs.push("GPX lido".to_string());
+
+        assert_eq!(logs.into_lines(), vec!["A ler o GPX".to_string(), "GPX lido".to_string()]);
+        assert_eq!(events.lock().unwrap()[1], ProgressEvent::Log { message: "GPX lido".to_string() });
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use extrator_gpx::timezone::{DisplayTimezone, TimezoneSettings, DEFAULT_TIMEZONE};
+
+    #[test]
+    fn test_default_timezone_settings() {
+        let settings = TimezoneSettings::from_params(None, Some("")).unwrap();
+        assert_eq!(settings, TimezoneSettings::default());
+        assert_eq!(settings.camera, DEFAULT_TIMEZONE);
+    }
+
+    #[test]
+    fn test_display_defaults_to_camera_timezone() {
+        let settings = TimezoneSettings::from_params(Some("Europe/Lisbon"), None).unwrap();
+        assert_eq!(settings.camera, chrono_tz::Europe::Lisbon);
+        assert_eq!(settings.display, DisplayTimezone::Fixed(chrono_tz::Europe::Lisbon));
+    }
+
+    #[test]
+    fn test_invalid_timezone_is_rejected() {
+        let result = TimezoneSettings::from_params(Some("Mars/Olympus_Mons"), None);
+        assert!(result.is_err(), "Unknown IANA names should be rejected");
+        assert!(TimezoneSettings::from_params(None, Some("-03:00")).is_err());
+    }
+
+    #[test]
+    fn test_camera_clock_conversion() {
+        // 09:00 no relógio da câmara em Lisboa (horário de verão, +01:00) = 08:00 UTC
+        let naive = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(
+            extrator_gpx::utils::local_to_utc(&naive, chrono_tz::Europe::Lisbon),
+            Some(Utc.with_ymd_and_hms(2024, 7, 1, 8, 0, 0).unwrap())
+        );
+        assert_eq!(
+            extrator_gpx::utils::local_to_utc(&naive, DEFAULT_TIMEZONE),
+            Some(Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_timezone_for_coordinate() {
+        assert_eq!(extrator_gpx::timezone::timezone_for_coordinate(38.7223, -9.1393), Some(chrono_tz::Europe::Lisbon));
+        assert_eq!(extrator_gpx::timezone::timezone_for_coordinate(35.6762, 139.6503), Some(chrono_tz::Asia::Tokyo));
+        assert_eq!(extrator_gpx::timezone::timezone_for_coordinate(-23.5505, -46.6333), Some(chrono_tz::America::Sao_Paulo));
+    }
+
+    #[test]
+    fn test_auto_display_timezone_from_track() {
+        // A trilha de exemplo começa em Tocantins
+        let gpx_path = PathBuf::from("tests/fixtures/garmin_trackpoint_extension.gpx");
+        let track = extrator_gpx::read_track_file(&gpx_path, DEFAULT_TIMEZONE).unwrap();
+
+        let settings = TimezoneSettings::from_params(None, Some("auto")).unwrap();
+        assert_eq!(settings.display, DisplayTimezone::Auto);
+        assert_eq!(settings.display_timezone_for(&track.gpx), chrono_tz::America::Araguaina);
+
+        // Sem pontos, o modo automático recai no fuso da câmara
+        assert_eq!(settings.display_timezone_for(&gpx::Gpx::default()), DEFAULT_TIMEZONE);
+    }
+
+    #[test]
+    fn test_utc_offset_follows_daylight_saving() {
+        let winter = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let summer = Utc.with_ymd_and_hms(2024, 7, 15, 12, 0, 0).unwrap();
+        assert_eq!(extrator_gpx::timezone::utc_offset_secs(chrono_tz::Europe::Madrid, &winter), 3600);
+        assert_eq!(extrator_gpx::timezone::utc_offset_secs(chrono_tz::Europe::Madrid, &summer), 7200);
+        assert_eq!(extrator_gpx::timezone::utc_offset_secs(DEFAULT_TIMEZONE, &winter), -3 * 3600);
+    }
+}
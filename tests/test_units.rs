@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use extrator_gpx::units::{format_pace, GaugeScale, UnitSystem};
+
+    #[test]
+    fn test_parse_and_serialize() {
+        for units in UnitSystem::ALL {
+            assert_eq!(units.as_str().parse::<UnitSystem>(), Ok(units));
+            assert_eq!(serde_json::to_string(&units).unwrap(), format!("\"{}\"", units));
+        }
+        assert_eq!(" Imperial ".parse::<UnitSystem>(), Ok(UnitSystem::Imperial));
+        assert!("furlongs".parse::<UnitSystem>().unwrap_err().contains("pace-km"));
+        assert_eq!(UnitSystem::default(), UnitSystem::Metric);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+        assert!(close(UnitSystem::Metric.distance(10.0), 10.0));
+        assert!(close(UnitSystem::Imperial.distance(1.609344), 1.0));
+        assert!(close(UnitSystem::Nautical.speed(1.852), 1.0));
+        assert!(close(UnitSystem::Imperial.elevation(1000.0), 3280.839895));
+        assert!(close(UnitSystem::Nautical.elevation(1000.0), 1000.0));
+
+        assert_eq!(UnitSystem::Imperial.speed_unit(), "MPH");
+        assert_eq!(UnitSystem::Nautical.distance_unit(), "NM");
+        assert_eq!(UnitSystem::PaceMi.elevation_unit(), "FT");
+        assert_eq!(UnitSystem::PaceKm.speed_unit(), "/KM");
+    }
+
+    #[test]
+    fn test_speed_and_pace_formatting() {
+        assert_eq!(UnitSystem::Metric.format_speed(25.44, 1), "25.4");
+        assert_eq!(UnitSystem::Nautical.format_speed(18.52, 0), "10");
+        // 12 km/h = 5:00 /km = 8:03 /mi
+        assert_eq!(UnitSystem::PaceKm.format_speed(12.0, 1), "5:00");
+        assert_eq!(UnitSystem::PaceMi.format_speed(12.0, 1), "8:03");
+        // Parado ou mais lento que uma hora por km
+        assert_eq!(UnitSystem::PaceKm.format_speed(0.0, 1), "--:--");
+        assert_eq!(format_pace(0.5), "--:--");
+        assert_eq!(format_pace(f64::NAN), "--:--");
+    }
+
+    #[test]
+    fn test_gauge_scale_follows_units() {
+        // Métrico sem máximo detectado: o velocímetro de sempre, 0 a 120 com traços de 5 e 25
+        assert_eq!(UnitSystem::Metric.gauge_scale(None), GaugeScale { max: 120.0, minor_step: 5, major_step: 25 });
+        assert_eq!(UnitSystem::Imperial.gauge_scale(None).max, 80.0);
+        assert_eq!(UnitSystem::Nautical.gauge_scale(Some(50.0)), GaugeScale { max: 30.0, minor_step: 1, major_step: 5 });
+
+        let pace = UnitSystem::PaceKm.gauge_scale(None);
+        assert_eq!(pace, GaugeScale { max: 20.0, minor_step: 1, major_step: 5 });
+        assert_eq!(UnitSystem::PaceKm.gauge_label(0), None);
+        assert_eq!(UnitSystem::PaceKm.gauge_label(10), Some("6:00".to_string()));
+        assert_eq!(UnitSystem::Imperial.gauge_label(50), Some("50".to_string()));
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use axum::body::Bytes;
+    use axum::http::StatusCode;
+    use futures_util::stream;
+    use extrator_gpx::upload::{sanitize_file_name, save_stream, UploadError};
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("upload_{}.bin", uuid::Uuid::new_v4()))
+    }
+
+    fn chunks(parts: &[&'static [u8]]) -> Vec<Result<Bytes, UploadError>> {
+        parts.iter().map(|part| Ok(Bytes::from_static(part))).collect()
+    }
+
+    #[tokio::test]
+    async fn test_chunks_are_written_and_hashed() {
+        let path = temp_path();
+        let (size, sha256) = save_stream(stream::iter(chunks(&[b"a", b"bc"])), &path, 1024).await.unwrap();
+
+        assert_eq!(size, 3);
+        assert_eq!(sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(std::fs::read(&path).unwrap(), b"abc");
+        // A hora da gravação no servidor não pode passar pela do vídeo
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), std::time::UNIX_EPOCH);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_upload_over_limit_is_rejected() {
+        let path = temp_path();
+        let error = save_stream(stream::iter(chunks(&[b"1234", b"5678"])), &path, 6).await.unwrap_err();
+
+        assert!(matches!(error, UploadError::TooLarge { limit: 6 }));
+        assert_eq!(error.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(!path.exists(), "The partial file is removed");
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_upload_is_removed() {
+        let path = temp_path();
+        let parts = vec![
+            Ok(Bytes::from_static(b"parte")),
+            Err(UploadError::Invalid("ligação interrompida".to_string())),
+        ];
+        let error = save_stream(stream::iter(parts), &path, 1024).await.unwrap_err();
+
+        assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("GX010042.MP4"), "GX010042.MP4");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("C:\\Users\\ana\\treino.gpx"), "treino.gpx");
+        assert_eq!(sanitize_file_name(".."), "upload");
+        assert_eq!(sanitize_file_name(""), "upload");
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use extrator_gpx::video_geometry::VideoGeometry;
+
+    #[test]
+    fn test_reads_first_video_stream() {
+        let probe = json!({"streams": [
+            {"codec_type": "audio", "sample_rate": "48000"},
+            {"codec_type": "video", "width": 640, "height": 360, "disposition": {"attached_pic": 1}},
+            {"codec_type": "video", "width": 1920, "height": 1080, "sample_aspect_ratio": "1:1"},
+        ]});
+        let geometry = VideoGeometry::from_ffprobe_json(&probe).unwrap();
+        assert_eq!(geometry, VideoGeometry { width: 1920, height: 1080, sample_aspect_ratio: (1, 1), rotation: 0 });
+        assert_eq!(geometry.frame_size(), (1920, 1080));
+        assert!(!geometry.is_portrait());
+        assert_eq!(geometry.describe("en"), "1920x1080 (landscape, 0°, SAR 1:1)");
+    }
+
+    #[test]
+    fn test_rotation_from_display_matrix() {
+        // Telemóvel na vertical com FFmpeg 5+: a matriz de exibição indica -90 (anti-horário)
+        let probe = json!({"streams": [{
+            "codec_type": "video", "width": 1920, "height": 1080,
+            "side_data_list": [{"side_data_type": "Display Matrix", "rotation": -90}],
+        }]});
+        let geometry = VideoGeometry::from_ffprobe_json(&probe).unwrap();
+        assert_eq!(geometry.rotation, 90);
+        assert_eq!(geometry.frame_size(), (1080, 1920));
+        assert_eq!(geometry.display_size(), (1080, 1920));
+        assert!(geometry.is_portrait());
+        assert_eq!(geometry.describe("pt"), "1080x1920 (retrato, 90°, SAR 1:1)");
+    }
+
+    #[test]
+    fn test_rotation_from_legacy_tag() {
+        let rotated = |rotate: &str| {
+            let probe = json!({"streams": [{
+                "codec_type": "video", "width": 1280, "height": 720, "tags": {"rotate": rotate},
+            }]});
+            VideoGeometry::from_ffprobe_json(&probe).unwrap().rotation
+        };
+        assert_eq!(rotated("90"), 90);
+        assert_eq!(rotated("270"), 270);
+        assert_eq!(rotated("-90"), 270);
+        assert_eq!(rotated("180"), 180);
+    }
+
+    #[test]
+    fn test_sample_aspect_ratio_widens_display() {
+        // DV PAL anamórfico 16:9: 720x576 com pixels de 64:45
+        let probe = json!({"streams": [{
+            "codec_type": "video", "width": 720, "height": 576, "sample_aspect_ratio": "64:45",
+        }]});
+        let geometry = VideoGeometry::from_ffprobe_json(&probe).unwrap();
+        assert_eq!(geometry.sample_aspect_ratio, (64, 45));
+        assert_eq!(geometry.frame_size(), (720, 576));
+        assert_eq!(geometry.display_size(), (1024, 576));
+
+        // SAR desconhecido conta como pixels quadrados
+        let unknown = json!({"streams": [{"codec_type": "video", "width": 720, "height": 576, "sample_aspect_ratio": "0:1"}]});
+        assert_eq!(VideoGeometry::from_ffprobe_json(&unknown).unwrap().sample_aspect_ratio, (1, 1));
+    }
+
+    #[test]
+    fn test_missing_video_stream() {
+        assert!(VideoGeometry::from_ffprobe_json(&json!({"streams": [{"codec_type": "audio"}]})).is_none());
+        assert!(VideoGeometry::from_ffprobe_json(&json!({"streams": [{"codec_type": "video", "width": 0, "height": 0}]})).is_none());
+        assert!(VideoGeometry::from_ffprobe_json(&json!({"format": {}})).is_none());
+    }
+}
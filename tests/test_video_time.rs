@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+    use ffprobe::{FfProbe, Format, FormatTags, Stream, StreamTags};
+    use extrator_gpx::timezone::DEFAULT_TIMEZONE;
+    use extrator_gpx::video_time::{parse_file_name_time, resolve_start_time, VideoTimeSource};
+
+    // Caminho inexistente: sem data de modificação, só contam os metadados e o nome
+    const MISSING_VIDEO: &str = "tests/fixtures/nao_existe.mp4";
+
+    fn video_stream(tags: StreamTags) -> Stream {
+        Stream {
+            codec_type: Some("video".to_string()),
+            r_frame_rate: "25/1".to_string(),
+            tags: Some(tags),
+            ..Default::default()
+        }
+    }
+
+    fn probe(streams: Vec<Stream>, format_tags: Option<FormatTags>) -> FfProbe {
+        FfProbe {
+            streams,
+            format: Format { tags: format_tags, ..Default::default() },
+        }
+    }
+
+    fn resolve(metadata: &FfProbe, path: &str) -> Option<(chrono::DateTime<Utc>, VideoTimeSource)> {
+        resolve_start_time(metadata, Path::new(path), DEFAULT_TIMEZONE, Duration::seconds(60))
+    }
+
+    #[test]
+    fn test_stream_tag_is_preferred() {
+        let metadata = probe(
+            vec![video_stream(StreamTags {
+                creation_time: Some("2024-01-15T04:30:00.000000Z".to_string()),
+                ..Default::default()
+            })],
+            Some(FormatTags { creation_time: Some("2024-01-15T05:00:00.000000Z".to_string()), ..Default::default() }),
+        );
+
+        // O "Z" da câmara é falso: 04:30 em São Paulo = 07:30 UTC
+        let (start, source) = resolve(&metadata, MISSING_VIDEO).unwrap();
+        assert_eq!(source, VideoTimeSource::StreamTag);
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_format_tag_fallback() {
+        let metadata = probe(
+            vec![video_stream(StreamTags::default())],
+            Some(FormatTags { creation_time: Some("2024-01-15T04:30:00.000000Z".to_string()), ..Default::default() }),
+        );
+        let (start, source) = resolve(&metadata, MISSING_VIDEO).unwrap();
+        assert_eq!(source, VideoTimeSource::FormatTag);
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_implausible_creation_time_is_skipped() {
+        // Câmaras sem relógio acertado gravam 1970 ou 1904
+        let metadata = probe(
+            vec![video_stream(StreamTags {
+                creation_time: Some("1970-01-01T00:00:00.000000Z".to_string()),
+                ..Default::default()
+            })],
+            None,
+        );
+        let (_, source) = resolve(&metadata, "tests/fixtures/VID_20240115_073000.mp4").unwrap();
+        assert_eq!(source, VideoTimeSource::FileName);
+    }
+
+    #[test]
+    fn test_quicktime_date_with_offset() {
+        let extra = HashMap::from([(
+            "com.apple.quicktime.creationdate".to_string(),
+            "2024-01-15T09:30:00+0100".into(),
+        )]);
+        let metadata = probe(vec![video_stream(StreamTags::default())], Some(FormatTags { extra, ..Default::default() }));
+
+        // O fuso escrito pelo telemóvel prevalece sobre o fuso da câmara
+        let (start, source) = resolve(&metadata, MISSING_VIDEO).unwrap();
+        assert_eq!(source, VideoTimeSource::QuickTime);
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 15, 8, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_timecode_with_date_from_file_name() {
+        let metadata = probe(
+            vec![video_stream(StreamTags { timecode: Some("04:30:10:05".to_string()), ..Default::default() })],
+            None,
+        );
+
+        // Hora do timecode (05 frames a 25 fps = 0,2 s) e data do nome do arquivo
+        let (start, source) = resolve(&metadata, "tests/fixtures/CLIP_20240115_000000.mov").unwrap();
+        assert_eq!(source, VideoTimeSource::Timecode);
+        assert_eq!(
+            start,
+            Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 10).unwrap() + Duration::milliseconds(200)
+        );
+        assert!(source.is_approximate());
+    }
+
+    #[test]
+    fn test_file_name_fallback() {
+        let metadata = probe(vec![video_stream(StreamTags::default())], None);
+        let (start, source) = resolve(&metadata, "uploads/VID_20240115_043000.mp4").unwrap();
+        assert_eq!(source, VideoTimeSource::FileName);
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_file_modified_fallback() {
+        let path = std::env::temp_dir().join(format!("video_time_{}.mp4", uuid::Uuid::new_v4()));
+        let file = std::fs::File::create(&path).unwrap();
+        let closed_at = Utc.with_ymd_and_hms(2024, 1, 15, 7, 31, 0).unwrap();
+        file.set_modified(closed_at.into()).unwrap();
+
+        let metadata = probe(vec![video_stream(StreamTags::default())], None);
+        let result = resolve(&metadata, path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+
+        // O arquivo é fechado no fim da gravação de 60 s
+        let (start, source) = result.unwrap();
+        assert_eq!(source, VideoTimeSource::FileModified);
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 15, 7, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_upload_without_last_modified_is_not_a_source() {
+        // Uploads sem `lastModified` ficam com a data de modificação no início da época Unix
+        let path = std::env::temp_dir().join(format!("video_time_{}.mp4", uuid::Uuid::new_v4()));
+        std::fs::File::create(&path).unwrap().set_modified(std::time::UNIX_EPOCH).unwrap();
+
+        let metadata = probe(vec![video_stream(StreamTags::default())], None);
+        let without_timecode = resolve(&metadata, path.to_str().unwrap());
+        let timecode = StreamTags { timecode: Some("07:30:00:00".to_string()), ..Default::default() };
+        let with_timecode = resolve(&probe(vec![video_stream(timecode)], None), path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+
+        assert!(without_timecode.is_none());
+        assert!(with_timecode.is_none(), "The timecode date cannot come from the upload either");
+    }
+
+    #[test]
+    fn test_no_source_available() {
+        let metadata = probe(vec![video_stream(StreamTags::default())], None);
+        assert!(resolve(&metadata, "tests/fixtures/GX010042.MP4").is_none());
+    }
+
+    #[test]
+    fn test_parse_file_name_time() {
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap().and_hms_opt(7, 30, 0).unwrap();
+
+        assert_eq!(parse_file_name_time("VID_20240115_073000"), Some(expected));
+        assert_eq!(parse_file_name_time("PXL_20240115_073000123"), Some(expected));
+        assert_eq!(parse_file_name_time("Screen Recording 2024-01-15 at 07.30.00"), Some(expected));
+        assert_eq!(parse_file_name_time("20240115073000"), Some(expected));
+        assert_eq!(
+            parse_file_name_time("0b9f5c1e-3f7a-4c52-9d1e-8a2b6c4d5e6f-VID_20240115_073000"),
+            Some(expected),
+            "The upload UUID prefix is ignored"
+        );
+        assert_eq!(parse_file_name_time("GX010042"), None);
+        assert_eq!(parse_file_name_time("DJI_0042"), None);
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use chrono::{TimeZone, Utc};
+    use geo_types::Point;
+    use gpx::{Gpx, Track, TrackSegment, Waypoint};
+    use image::{Rgba, RgbaImage};
+    use extrator_gpx::{AthleteProfile, UnitSystem};
+    use extrator_gpx::drawing::Theme;
+    use extrator_gpx::heart_rate::{HeartRateZones, TimeInZones};
+    use extrator_gpx::layout::{Anchor, Layout, WidgetLayout};
+    use extrator_gpx::telemetry::CameraSettings;
+    use extrator_gpx::widget::{Compositor, FrameContext, OverlayWidget, WidgetContext, WidgetFactory, WidgetRegistry};
+
+    const CANVAS: (u32, u32) = (640, 360);
+
+    // Quadrado da cor de destaque, só desenhado quando há um ponto visível
+    struct SquareWidget {
+        color: Rgba<u8>,
+    }
+
+    impl OverlayWidget for SquareWidget {
+        fn render(&self, frame: Option<&FrameContext>) -> Result<Option<RgbaImage>, Box<dyn Error>> {
+            Ok(frame.map(|_| RgbaImage::from_pixel(10, 10, self.color)))
+        }
+    }
+
+    fn build_square(context: &WidgetContext) -> Result<Box<dyn OverlayWidget>, Box<dyn Error>> {
+        Ok(Box::new(SquareWidget { color: context.theme.accent_color }))
+    }
+
+    fn registry_with_square() -> WidgetRegistry {
+        let mut registry = WidgetRegistry::builtin();
+        registry.register("square", WidgetFactory {
+            natural_size: (10, 10),
+            default_theme: Theme::stats,
+            build: build_square,
+        });
+        registry
+    }
+
+    fn sample_track() -> Gpx {
+        let mut segment = TrackSegment::new();
+        for i in 0..20 {
+            segment.points.push(Waypoint::new(Point::new(-48.0 + i as f64 * 0.001, -10.0 + i as f64 * 0.0005)));
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        Gpx { tracks: vec![track], ..Default::default() }
+    }
+
+    fn sample_frame(gpx: &Gpx) -> FrameContext {
+        FrameContext {
+            point: gpx.tracks[0].segments[0].points[10].clone(),
+            point_time: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+            speed_kmh: 25.0,
+            bearing: 45.0,
+            g_force: 0.1,
+            elevation: 120.0,
+            distance_km: 1.5,
+            elevation_gain_m: 12.0,
+            heart_rate: Some(130.0),
+            cadence: None,
+            sensor_speed_kmh: None,
+            calories: None,
+            camera: Some(CameraSettings { iso: Some(100.0), exposure_secs: Some(0.002), ..Default::default() }),
+            time_in_zones: TimeInZones::default(),
+            power: None,
+            power_3s: None,
+            power_10s: None,
+            power_summary: None,
+            utc_offset_secs: -3 * 3600,
+        }
+    }
+
+    #[test]
+    fn test_builtin_registry() {
+        let registry = WidgetRegistry::builtin();
+        assert_eq!(registry.names(), ["elevation_profile", "heart_rate", "power", "speedometer", "stats", "track_map"]);
+        assert_eq!(registry.get("stats").map(|f| f.natural_size), Some((280, 420)));
+        assert!(registry.get("clock").is_none());
+    }
+
+    #[test]
+    fn test_custom_widget_is_composed_with_layout() {
+        let registry = registry_with_square();
+        let layout = Layout::parse_with(r##"
+            [[widgets]]
+            widget = "square"
+            anchor = "top-left"
+            offset = { x = 0, y = 0 }
+            scale = 2
+            opacity = 0.5
+            style = { accent_color = "#ff0000" }
+        "##, &registry).unwrap();
+        assert!(Layout::parse(&toml::to_string(&layout).unwrap()).is_err(), "Builtin registry rejects unknown widgets");
+
+        let gpx = sample_track();
+        let compositor = Compositor::new(&layout, &registry, &gpx, CANVAS, "pt", UnitSystem::Metric, &AthleteProfile::default()).unwrap();
+        assert!(compositor.compose(None).unwrap().pixels().all(|p| p.0[3] == 0));
+
+        let frame = compositor.compose(Some(&sample_frame(&gpx))).unwrap();
+        assert_eq!(frame.dimensions(), CANVAS);
+        // 20x20 de referência num vídeo de 360p (escala 1/3): 7x7 e a meia opacidade
+        assert_eq!(*frame.get_pixel(6, 6), Rgba([255, 0, 0, 128]));
+        assert_eq!(frame.get_pixel(7, 7).0[3], 0);
+    }
+
+    #[test]
+    fn test_builtin_widgets_render() {
+        let registry = WidgetRegistry::builtin();
+        let layout = Layout::from_corners(Some("bottom-left"), Some("top-right"), Some("bottom-right"));
+        let gpx = sample_track();
+        let compositor = Compositor::new(&layout, &registry, &gpx, (1280, 720), "en", UnitSystem::Imperial, &AthleteProfile::default()).unwrap();
+
+        // Sem ponto visível só o mapa fica desenhado
+        let base = compositor.compose(None).unwrap();
+        assert!(base.get_pixel(1280 - 160, 160).0[3] > 0, "Track map is drawn");
+        assert_eq!(base.get_pixel(160, 720 - 160).0[3], 0, "Speedometer needs a frame");
+
+        let frame = compositor.compose(Some(&sample_frame(&gpx))).unwrap();
+        assert!(frame.get_pixel(160, 720 - 160).0[3] > 0, "Speedometer is drawn");
+        assert_ne!(frame, base);
+    }
+
+    #[test]
+    fn test_unknown_widget_fails_to_compose() {
+        let layout = Layout { widgets: vec![WidgetLayout::new("clock", Anchor::Center)], ..Default::default() };
+        let error = Compositor::new(&layout, &WidgetRegistry::builtin(), &sample_track(), CANVAS, "pt", UnitSystem::Metric, &AthleteProfile::default()).err().unwrap();
+        assert!(error.to_string().contains("clock"));
+    }
+
+    #[test]
+    fn test_heart_rate_widget() {
+        let layout = Layout { widgets: vec![WidgetLayout::new("heart_rate", Anchor::TopLeft)], ..Default::default() };
+        let athlete = AthleteProfile { heart_rate_zones: Some(HeartRateZones::from_max_hr(180.0)), ..Default::default() };
+        let gpx = sample_track();
+        let compositor = Compositor::new(&layout, &WidgetRegistry::builtin(), &gpx, (1920, 1080), "pt", UnitSystem::Metric, &athlete).unwrap();
+
+        let mut frame = sample_frame(&gpx);
+        frame.time_in_zones = TimeInZones([60.0, 30.0, 0.0, 0.0, 0.0]);
+        let image = compositor.compose(Some(&frame)).unwrap();
+        // Tamanho natural 300x360 à margem padrão
+        assert!(image.get_pixel(10 + 150, 10 + 300).0[3] > 0);
+        assert_eq!(image.get_pixel(10 + 320, 10 + 180).0[3], 0);
+
+        // Sem sensor de FC o mostrador não aparece
+        frame.heart_rate = None;
+        assert!(compositor.compose(Some(&frame)).unwrap().pixels().all(|p| p.0[3] == 0));
+    }
+
+    #[test]
+    fn test_power_widget() {
+        let layout = Layout { widgets: vec![WidgetLayout::new("power", Anchor::TopLeft)], ..Default::default() };
+        let athlete = AthleteProfile::default().with_power(Some(250.0), Some(70.0)).unwrap();
+        let gpx = sample_track();
+        let compositor = Compositor::new(&layout, &WidgetRegistry::builtin(), &gpx, (1920, 1080), "pt", UnitSystem::Metric, &athlete).unwrap();
+
+        let mut frame = sample_frame(&gpx);
+        frame.power = Some(260.0);
+        frame.power_3s = Some(250.0);
+        let image = compositor.compose(Some(&frame)).unwrap();
+        // Tamanho natural 300x320 à margem padrão
+        assert!(image.get_pixel(10 + 150, 10 + 300).0[3] > 0);
+        assert_eq!(image.get_pixel(10 + 150, 10 + 340).0[3], 0);
+
+        // Sem medidor de potência o mostrador não aparece
+        frame.power = None;
+        assert!(compositor.compose(Some(&frame)).unwrap().pixels().all(|p| p.0[3] == 0));
+    }
+}